@@ -1,17 +1,23 @@
-use crate::common::tokens::TokenKind;
+use crate::common::tokens::{Span, Token, TokenKind};
 
 #[derive(Debug)]
 pub struct Assignment {
     pub key: String,
-    pub value: String,
+    pub values: Vec<Token>,
     pub kind: TokenKind,
-    pub ident_kind: TokenKind,
+    pub line: usize,
+    pub col: usize,
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub struct Block {
     pub keyword: String,
-    pub identifiers: Vec<String>,
+    pub identifiers: Vec<Token>,
     pub assignments: Vec<Assignment>,
+    /// Keys whose assignment failed to parse, so later stages can skip checks that depend on them.
+    pub invalid_keys: Vec<String>,
     pub blocks: Vec<Block>,
+    pub line: usize,
+    pub span: Span,
 }
@@ -0,0 +1,139 @@
+pub mod python;
+pub mod rust;
+
+use crate::spec::{ArgType, Command, Program};
+use std::collections::{HashMap, HashSet};
+
+pub const LANGS: [&str; 2] = ["python", "rust"];
+
+/// Types for reading the JSON output of `program` in `lang` (one of [`LANGS`]); `source` is the
+/// spec path, for comments. Fails with one message per pair of names that would become the same
+/// identifier.
+pub fn generate(
+    lang: &str,
+    program: &Program,
+    source: &str,
+) -> Option<Result<String, Vec<String>>> {
+    match lang {
+        "python" => Some(python::generate(program, source)),
+        "rust" => Some(rust::generate(program, source)),
+        _ => None,
+    }
+}
+
+/// Shape of an argument's `value` in the JSON output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// Always present: a flag, or an argument that is required or has a default.
+    One,
+    /// `null` when the argument is unset.
+    Optional,
+    /// An array, for arguments that accept more than one value.
+    Many,
+}
+
+#[derive(Clone)]
+pub struct Field<'a> {
+    pub name: String,
+    pub desc: &'a Option<String>,
+    pub ty: ArgType,
+    pub shape: Shape,
+}
+
+/// The entries of the `args` object when the subcommands in `path` are selected, in output order.
+pub struct CommandArgs<'a> {
+    pub path: Vec<String>,
+    pub fields: Vec<Field<'a>>,
+}
+
+/// Report each pair of arguments that `ident` maps to the same identifier, once, in the first
+/// command whose type (named by `type_name`) holds both.
+pub fn field_clashes(
+    cmds: &[CommandArgs],
+    ident: fn(&str) -> String,
+    type_name: fn(&[String]) -> String,
+) -> Vec<String> {
+    let mut reported = HashSet::new();
+    let mut out = Vec::new();
+    for cmd in cmds {
+        let mut first: HashMap<String, &str> = HashMap::new();
+        for f in &cmd.fields {
+            let id = ident(&f.name);
+            match first.get(&id) {
+                Some(prev) => {
+                    if reported.insert((prev.to_string(), f.name.clone())) {
+                        out.push(format!(
+                            "argument `{prev}` and argument `{}` both become `{id}` in `{}`",
+                            f.name,
+                            type_name(&cmd.path)
+                        ));
+                    }
+                }
+                None => {
+                    first.insert(id, &f.name);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn shape(required: bool, has_default: bool, max: Option<usize>) -> Shape {
+    if max != Some(1) {
+        Shape::Many
+    } else if required || has_default {
+        Shape::One
+    } else {
+        Shape::Optional
+    }
+}
+
+fn own_fields(cmd: &Command) -> Vec<Field<'_>> {
+    let flags = cmd.flags.iter().map(|f| Field {
+        name: f.names.primary(),
+        desc: &f.desc,
+        ty: ArgType::Bool,
+        shape: Shape::One,
+    });
+    let positionals = cmd.positionals.iter().map(|p| Field {
+        name: p.name.clone(),
+        desc: &p.desc,
+        ty: p.ty,
+        shape: shape(p.required, !p.default.is_empty(), p.nargs.max),
+    });
+    let named = cmd.named.iter().map(|n| Field {
+        name: n.names.primary(),
+        desc: &n.desc,
+        ty: n.ty,
+        shape: shape(n.required, !n.default.is_empty(), n.nargs.max),
+    });
+    flags.chain(positionals).chain(named).collect()
+}
+
+/// Every command in `program`, outermost first. Like the output, each holds the arguments of the
+/// commands above it, with an inner command's argument replacing an outer one of the same name.
+pub fn commands(program: &Program) -> Vec<CommandArgs<'_>> {
+    let mut out = Vec::new();
+    walk(&program.cmd, Vec::new(), Vec::new(), &mut out);
+    out
+}
+
+fn walk<'a>(
+    cmd: &'a Command,
+    path: Vec<String>,
+    mut fields: Vec<Field<'a>>,
+    out: &mut Vec<CommandArgs<'a>>,
+) {
+    let own = own_fields(cmd);
+    fields.retain(|f| own.iter().all(|o| o.name != f.name));
+    fields.extend(own);
+    out.push(CommandArgs {
+        path: path.clone(),
+        fields: fields.clone(),
+    });
+    for sub in &cmd.commands {
+        let mut path = path.clone();
+        path.push(sub.names[0].clone());
+        walk(sub, path, fields.clone(), out);
+    }
+}
@@ -0,0 +1,249 @@
+use super::{CommandArgs, Shape, commands, field_clashes};
+use crate::codegen::{Writer, clashes, command_origin, pascal, str_lit};
+use crate::runtime::JSON_VERSION;
+use crate::spec::{ArgType, Program};
+
+const KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+const TYPES: &str = r#"
+Action = Literal["show_help", "show_version"]
+Source = Literal["argv", "default", "unset"]
+ErrorKind = Literal[
+    "unknown_option",
+    "unexpected_argument",
+    "flag_with_value",
+    "missing_value",
+    "too_many_values",
+    "missing_required",
+    "invalid_value",
+    "not_allowed",
+]
+
+T = TypeVar("T")
+
+
+@dataclass(frozen=True)
+class Arg(Generic[T]):
+    source: Source
+    value: T
+
+
+def _arg(args, name, convert=None):
+    arg = args[name]
+    value = arg["value"]
+    if convert is not None and isinstance(value, list):
+        value = [convert(v) for v in value]
+    elif convert is not None and value is not None:
+        value = convert(value)
+    return Arg(arg["source"], value)
+"#;
+
+const DOCUMENT: &str = r#"
+
+@dataclass(frozen=True)
+class Matches:
+    command: Command
+    # Words after `--` that no positional argument took
+    trailing: list[str]
+
+
+@dataclass(frozen=True)
+class ActionRequest:
+    """A flag with an action was given to the command at `command`."""
+
+    action: Action
+    command: list[str]
+
+
+@dataclass(frozen=True)
+class UsageError:
+    command: list[str]
+    kind: ErrorKind
+    # The option or argument the error is about
+    subject: str
+    message: str
+"#;
+
+const PARSE: &str = r#"
+
+def parse(text: str | bytes) -> Matches | ActionRequest | UsageError:
+    """Read the JSON printed by the parser."""
+    doc = json.loads(text)
+    if doc["version"] != VERSION:
+        raise ValueError(f"unsupported document version {doc['version']}")
+    if "error" in doc:
+        e = doc["error"]
+        return UsageError(doc["command"], e["kind"], e["subject"], e["message"])
+    if doc["action"] is not None:
+        return ActionRequest(doc["action"], doc["command"])
+    path = " ".join(doc["command"])
+    if path not in _COMMANDS:
+        raise ValueError(f"unknown command {path!r}")
+    return Matches(_COMMANDS[path].from_json(doc["args"]), doc["trailing"])
+"#;
+
+// Attribute for an argument; `from_json` would replace the classmethod of that name
+fn ident(s: &str) -> String {
+    let s: String = s
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match s.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{s}"),
+        _ if KEYWORDS.contains(&s.as_str()) || s == "from_json" => format!("{s}_"),
+        _ => s,
+    }
+}
+
+fn class_name(path: &[String]) -> String {
+    format!("{}Args", pascal(path))
+}
+
+fn py_type(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str => "str",
+        ArgType::Int | ArgType::UInt => "int",
+        ArgType::Float => "float",
+        ArgType::Bool => "bool",
+        ArgType::Path => "Path",
+    }
+}
+
+// JSON numbers for floats may be written without a fraction, and paths are strings
+fn convert(ty: ArgType) -> Option<&'static str> {
+    match ty {
+        ArgType::Float => Some("float"),
+        ArgType::Path => Some("Path"),
+        _ => None,
+    }
+}
+
+fn comment(w: &mut Writer, desc: &Option<String>) {
+    if let Some(desc) = desc {
+        for line in desc.lines() {
+            w.line(format!("# {line}").trim_end());
+        }
+    }
+}
+
+fn args_class(w: &mut Writer, cmd: &CommandArgs) {
+    let name = class_name(&cmd.path);
+    w.line("");
+    w.line("");
+    w.line("@dataclass(frozen=True)");
+    w.open(&format!("class {name}:"));
+    if cmd.path.is_empty() {
+        w.line("\"\"\"Top-level arguments.\"\"\"");
+    } else {
+        w.line(&format!(
+            "\"\"\"Arguments of `{}`, with those of the commands above it.\"\"\"",
+            cmd.path.join(" ")
+        ));
+    }
+    w.line("");
+    for f in &cmd.fields {
+        let ty = py_type(f.ty);
+        let ty = match f.shape {
+            Shape::One => ty.to_string(),
+            Shape::Optional => format!("{ty} | None"),
+            Shape::Many => format!("list[{ty}]"),
+        };
+        comment(w, f.desc);
+        w.line(&format!("{}: Arg[{ty}]", ident(&f.name)));
+    }
+    if !cmd.fields.is_empty() {
+        w.line("");
+    }
+    w.line("@classmethod");
+    w.open(&format!("def from_json(cls, args: dict) -> {name}:"));
+    if cmd.fields.is_empty() {
+        w.line("return cls()");
+    } else {
+        w.open("return cls(");
+        for f in &cmd.fields {
+            let mut call = format!("_arg(args, {}", str_lit(&f.name));
+            if let Some(convert) = convert(f.ty) {
+                call.push_str(&format!(", {convert}"));
+            }
+            w.line(&format!("{}={call}),", ident(&f.name)));
+        }
+        w.close(")");
+    }
+    w.dedent();
+    w.dedent();
+}
+
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let cmds = commands(program);
+    let mut errors = field_clashes(&cmds, ident, class_name);
+    let classes: Vec<(String, String)> = cmds
+        .iter()
+        .map(|cmd| (class_name(&cmd.path), command_origin(&cmd.path)))
+        .collect();
+    errors.extend(clashes("the module", &classes));
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let mut w = Writer::new("    ");
+    w.line(&format!(
+        "# Generated by argolify from `{source}`; do not edit."
+    ));
+    w.line("#");
+    w.line("# Types for the JSON printed by `argolify run --output json` and by executables from");
+    w.line("# `argolify build` for this spec:");
+    w.line("#");
+    w.line("#     doc = parse(proc.stdout)");
+    w.line("");
+    w.line("from __future__ import annotations");
+    w.line("");
+    w.line("import json");
+    w.line("from dataclasses import dataclass");
+    let paths = cmds
+        .iter()
+        .flat_map(|c| &c.fields)
+        .any(|f| f.ty == ArgType::Path);
+    if paths {
+        w.line("from pathlib import Path");
+    }
+    w.line("from typing import Generic, Literal, TypeVar, Union");
+    w.line("");
+    w.line(&format!("VERSION = {JSON_VERSION}"));
+    let mut out = w.finish();
+    out.push_str(TYPES);
+
+    let mut w = Writer::new("    ");
+    for cmd in &cmds {
+        args_class(&mut w, cmd);
+    }
+    w.line("");
+    w.line("");
+    w.line("# The selected command's arguments");
+    w.open("Command = Union[");
+    for cmd in &cmds {
+        w.line(&format!("{},", class_name(&cmd.path)));
+    }
+    w.close("]");
+    out.push_str(&w.finish());
+    out.push_str(DOCUMENT);
+
+    let mut w = Writer::new("    ");
+    w.line("");
+    w.line("");
+    w.open("_COMMANDS = {");
+    for cmd in &cmds {
+        w.line(&format!(
+            "{}: {},",
+            str_lit(&cmd.path.join(" ")),
+            class_name(&cmd.path)
+        ));
+    }
+    w.close("}");
+    out.push_str(&w.finish());
+    out.push_str(PARSE);
+    Ok(out)
+}
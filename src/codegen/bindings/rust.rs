@@ -0,0 +1,311 @@
+use super::{CommandArgs, Shape, commands, field_clashes};
+use crate::codegen::{Writer, clashes, command_origin, pascal};
+use crate::runtime::JSON_VERSION;
+use crate::spec::{ArgType, Program};
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+
+const DOCUMENT: &str = r#"
+/// What the parser printed: the matched arguments, an action a flag asked for, or a usage error.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Raw")]
+pub enum Document {
+    Matches(Matches),
+    Action(ActionRequest),
+    Error(UsageError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matches {
+    pub command: Command,
+    /// Words after `--` that no positional argument took.
+    pub trailing: Vec<String>,
+}
+"#;
+
+const TYPES: &str = r#"
+/// A flag with an action was given to the command at `command`; matching stopped there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequest {
+    pub action: Action,
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageError {
+    pub command: Vec<String>,
+    pub kind: ErrorKind,
+    /// The option or argument the error is about.
+    pub subject: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ShowHelp,
+    ShowVersion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    UnknownOption,
+    UnexpectedArgument,
+    FlagWithValue,
+    MissingValue,
+    TooManyValues,
+    MissingRequired,
+    InvalidValue,
+    NotAllowed,
+}
+
+/// Where an argument's value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Argv,
+    Default,
+    Unset,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Arg<T> {
+    pub source: Source,
+    pub value: T,
+}
+"#;
+
+const RAW: &str = r#"
+#[derive(Deserialize)]
+struct Raw {
+    version: u32,
+    command: Vec<String>,
+    action: Option<Action>,
+    #[serde(default)]
+    args: serde_json::Value,
+    #[serde(default)]
+    trailing: Vec<String>,
+    error: Option<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawError {
+    kind: ErrorKind,
+    subject: String,
+    message: String,
+}
+
+fn args<T: serde::de::DeserializeOwned>(args: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(args).map_err(|e| e.to_string())
+}
+"#;
+
+fn field(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{name}"),
+        // Keywords that cannot be raw identifiers
+        _ if ["crate", "self", "super"].contains(&name.as_str()) => format!("{name}_"),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("r#{name}"),
+        _ => name,
+    }
+}
+
+fn rust_type(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str => "String",
+        ArgType::Int => "i64",
+        ArgType::UInt => "u64",
+        ArgType::Float => "f64",
+        ArgType::Bool => "bool",
+        ArgType::Path => "PathBuf",
+    }
+}
+
+fn struct_name(path: &[String]) -> String {
+    format!("{}Args", pascal(path))
+}
+
+// Variant of `Command`; a subcommand that would take the top level's `Root` gets a `Command`
+// suffix
+fn variant(path: &[String]) -> String {
+    if path.is_empty() {
+        return "Root".into();
+    }
+    let name = pascal(path);
+    if name == "Root" {
+        format!("{name}Command")
+    } else {
+        name
+    }
+}
+
+fn doc(w: &mut Writer, desc: &Option<String>) {
+    if let Some(desc) = desc {
+        for line in desc.lines() {
+            w.line(format!("/// {line}").trim_end());
+        }
+    }
+}
+
+fn args_struct(w: &mut Writer, cmd: &CommandArgs) {
+    w.line("");
+    if cmd.path.is_empty() {
+        w.line("/// Top-level arguments.");
+    } else {
+        w.line(&format!(
+            "/// Arguments of `{}`, with those of the commands above it.",
+            cmd.path.join(" ")
+        ));
+    }
+    w.line("#[derive(Debug, Clone, PartialEq, Deserialize)]");
+    if cmd.fields.is_empty() {
+        w.line(&format!("pub struct {} {{}}", struct_name(&cmd.path)));
+        return;
+    }
+    w.open(&format!("pub struct {} {{", struct_name(&cmd.path)));
+    for f in &cmd.fields {
+        let ident = field(&f.name);
+        doc(w, f.desc);
+        if ident.trim_start_matches("r#") != f.name {
+            w.line(&format!("#[serde(rename = {:?})]", f.name));
+        }
+        let ty = rust_type(f.ty);
+        let ty = match f.shape {
+            Shape::One => ty.to_string(),
+            Shape::Optional => format!("Option<{ty}>"),
+            Shape::Many => format!("Vec<{ty}>"),
+        };
+        w.line(&format!("pub {ident}: Arg<{ty}>,"));
+    }
+    w.close("}");
+}
+
+fn try_from(w: &mut Writer, cmds: &[CommandArgs]) {
+    w.line("");
+    w.open("impl TryFrom<Raw> for Document {");
+    w.line("type Error = String;");
+    w.line("");
+    w.open("fn try_from(raw: Raw) -> Result<Self, String> {");
+    w.open("if raw.version != VERSION {");
+    w.line("return Err(format!(\"unsupported document version {}\", raw.version));");
+    w.close("}");
+    w.open("if let Some(error) = raw.error {");
+    w.open("return Ok(Document::Error(UsageError {");
+    w.line("command: raw.command,");
+    w.line("kind: error.kind,");
+    w.line("subject: error.subject,");
+    w.line("message: error.message,");
+    w.close("}));");
+    w.close("}");
+    w.open("if let Some(action) = raw.action {");
+    w.open("return Ok(Document::Action(ActionRequest {");
+    w.line("action,");
+    w.line("command: raw.command,");
+    w.close("}));");
+    w.close("}");
+    w.open("let command = match raw.command.join(\" \").as_str() {");
+    for cmd in cmds {
+        w.line(&format!(
+            "{:?} => Command::{}(args(raw.args)?),",
+            cmd.path.join(" "),
+            variant(&cmd.path)
+        ));
+    }
+    w.line("path => return Err(format!(\"unknown command `{path}`\")),");
+    w.close("};");
+    w.open("Ok(Document::Matches(Matches {");
+    w.line("command,");
+    w.line("trailing: raw.trailing,");
+    w.close("}))");
+    w.close("}");
+    w.close("}");
+}
+
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let cmds = commands(program);
+    let mut errors = field_clashes(&cmds, field, struct_name);
+    let names = |name: fn(&[String]) -> String| -> Vec<(String, String)> {
+        cmds.iter()
+            .map(|cmd| (name(&cmd.path), command_origin(&cmd.path)))
+            .collect()
+    };
+    errors.extend(clashes("the module", &names(struct_name)));
+    errors.extend(clashes("`Command`", &names(variant)));
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let mut w = Writer::new("    ");
+    w.line(&format!(
+        "// Generated by argolify from `{source}`; do not edit."
+    ));
+    w.line("//");
+    w.line("// Types for the JSON printed by `argolify run --output json` and by executables from");
+    w.line("// `argolify build` for this spec. Needs `serde` (with the `derive` feature) and `serde_json`:");
+    w.line("//");
+    w.line("//     let doc: Document = serde_json::from_slice(&output.stdout)?;");
+    w.line("");
+    w.line("use serde::Deserialize;");
+    let paths = cmds
+        .iter()
+        .flat_map(|c| &c.fields)
+        .any(|f| f.ty == ArgType::Path);
+    if paths {
+        w.line("use std::path::PathBuf;");
+    }
+    w.line("");
+    w.line("/// Version of the document these types read.");
+    w.line(&format!("pub const VERSION: u32 = {JSON_VERSION};"));
+    let mut out = w.finish();
+    out.push_str(DOCUMENT);
+
+    let mut w = Writer::new("    ");
+    w.line("");
+    w.line("/// The selected command and its arguments.");
+    w.line("#[derive(Debug, Clone, PartialEq)]");
+    w.open("pub enum Command {");
+    for cmd in &cmds {
+        w.line(&format!(
+            "{}({}),",
+            variant(&cmd.path),
+            struct_name(&cmd.path)
+        ));
+    }
+    w.close("}");
+    out.push_str(&w.finish());
+    out.push_str(TYPES);
+
+    let mut w = Writer::new("    ");
+    for cmd in &cmds {
+        args_struct(&mut w, cmd);
+    }
+    out.push_str(&w.finish());
+    out.push_str(RAW);
+    let mut w = Writer::new("    ");
+    try_from(&mut w, &cmds);
+    out.push_str(&w.finish());
+    Ok(out)
+}
@@ -0,0 +1,1452 @@
+use crate::codegen::{Writer, clashes, command_origin};
+use crate::help;
+use crate::spec::{Action, ArgType, Command, Nargs, Options, Program, Value};
+
+const KEYWORDS: [&str; 58] = [
+    "alignas",
+    "alignof",
+    "and",
+    "asm",
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "not",
+    "operator",
+    "or",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+];
+
+const HEAD: &str = r#"
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Return codes of argol_parse: ARGOL_OK, or one per kind of error in argolify's JSON output. */
+enum argol_error {
+    ARGOL_OK = 0,
+    ARGOL_UNKNOWN_OPTION,
+    ARGOL_UNEXPECTED_ARGUMENT,
+    ARGOL_FLAG_WITH_VALUE,
+    ARGOL_MISSING_VALUE,
+    ARGOL_TOO_MANY_VALUES,
+    ARGOL_MISSING_REQUIRED,
+    ARGOL_INVALID_VALUE,
+    ARGOL_NOT_ALLOWED,
+    /* The lists of values could not be allocated */
+    ARGOL_NO_MEMORY
+};
+
+enum argol_action {
+    ARGOL_ACTION_NONE = 0,
+    ARGOL_SHOW_HELP,
+    ARGOL_SHOW_VERSION
+};
+"#;
+
+const DECLARATIONS: &str = r#"
+/*
+ * Match argv, as given to main, against the spec. Returns ARGOL_OK or an error code, with the
+ * message in out->error. When out->action is set, print out->message and exit instead of using
+ * the arguments. Either way, call argol_free(out) afterwards.
+ */
+int argol_parse(int argc, char **argv, struct argol_args *out);
+
+/* Free the lists of values allocated by argol_parse. */
+void argol_free(struct argol_args *args);
+
+/* Kind of an error code as in argolify's JSON output, e.g. "unknown_option". */
+const char *argol_error_kind(int code);
+
+#ifdef __cplusplus
+}
+#endif
+"#;
+
+const MATCHER: &str = r#"
+#include <errno.h>
+#include <math.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+struct argol_option {
+    const char *key;
+    /* One UTF-8 character, or NULL */
+    const char *short_name;
+    /* Space-separated */
+    const char *long_names;
+    bool flag;
+    enum argol_action action;
+    int min;
+    /* -1 when there is no limit */
+    int max;
+};
+
+struct argol_positional {
+    const char *key;
+    int min;
+    int max;
+};
+
+struct argol_spec {
+    const struct argol_option *options;
+    size_t options_len;
+    const struct argol_positional *positionals;
+    size_t positionals_len;
+    /* Space-separated names of each subcommand */
+    const char *const *commands;
+    size_t commands_len;
+    const char *help;
+    const char *version;
+};
+
+struct argol_list {
+    const char **items;
+    size_t len;
+};
+
+/* The words given to one command, before they are converted */
+struct argol_matched {
+    struct argol_args *root;
+    const struct argol_spec *spec;
+    /* By option */
+    bool *flags;
+    /* By option, then by positional */
+    struct argol_list *values;
+    /* First word left over before `--`, reported by argol_leftover */
+    const char *unexpected;
+    /* Index of the subcommand given, or -1, and where its arguments start */
+    int sub;
+    int rest;
+};
+
+static void *argol_alloc(size_t n, size_t size)
+{
+    return calloc(n > 0 ? n : 1, size);
+}
+
+/* Format the message for an error into root->error, with the path of subcommands given. */
+static int argol_fail(struct argol_args *root, int code, const char *format, ...)
+{
+    va_list args;
+    size_t len;
+
+    va_start(args, format);
+    vsnprintf(root->error, sizeof root->error, format, args);
+    va_end(args);
+    for (size_t i = 0; i < root->command_len; i++) {
+        len = strlen(root->error);
+        snprintf(root->error + len, sizeof root->error - len, "%s%s", i == 0 ? " (in " : " ",
+                 root->command[i]);
+    }
+    if (root->command_len > 0) {
+        len = strlen(root->error);
+        snprintf(root->error + len, sizeof root->error - len, ")");
+    }
+    return code;
+}
+
+static int argol_no_memory(struct argol_args *root)
+{
+    return argol_fail(root, ARGOL_NO_MEMORY, "out of memory");
+}
+
+static const char *argol_expects(char *buf, size_t size, int min, int max)
+{
+    if (min == max) {
+        snprintf(buf, size, "%d", min);
+    } else if (max < 0 && min == 0) {
+        snprintf(buf, size, "any number of");
+    } else if (max < 0) {
+        snprintf(buf, size, "at least %d", min);
+    } else {
+        snprintf(buf, size, "%d to %d", min, max);
+    }
+    return buf;
+}
+
+static int argol_count(struct argol_args *root, int code, const char *name, int min, int max)
+{
+    char expects[32];
+    argol_expects(expects, sizeof expects, min, max);
+    return argol_fail(root, code, "'%s' expects %s value(s)", name, expects);
+}
+
+static bool argol_is_digit(char c)
+{
+    return c >= '0' && c <= '9';
+}
+
+/* Whether s is a number as argolify reads one: a finite decimal with an optional exponent */
+static bool argol_is_number(const char *s)
+{
+    const char *start = s;
+    size_t digits = 0;
+
+    if (*s == '+' || *s == '-') {
+        s++;
+    }
+    for (; argol_is_digit(*s); s++) {
+        digits++;
+    }
+    if (*s == '.') {
+        for (s++; argol_is_digit(*s); s++) {
+            digits++;
+        }
+    }
+    if (digits == 0) {
+        return false;
+    }
+    if (*s == 'e' || *s == 'E') {
+        s++;
+        if (*s == '+' || *s == '-') {
+            s++;
+        }
+        if (!argol_is_digit(*s)) {
+            return false;
+        }
+        while (argol_is_digit(*s)) {
+            s++;
+        }
+    }
+    return *s == '\0' && isfinite(strtod(start, NULL));
+}
+
+/* Length of the UTF-8 character s starts with */
+static size_t argol_char_len(const char *s)
+{
+    unsigned char c = (unsigned char)*s;
+    size_t n = c < 0x80 ? 1 : c >= 0xf0 ? 4 : c >= 0xe0 ? 3 : 2;
+    size_t len = strlen(s);
+    return n < len ? n : len;
+}
+
+/* Whether the space-separated names include the first len bytes of name */
+static bool argol_has_name(const char *names, const char *name, size_t len)
+{
+    while (*names != '\0') {
+        size_t n = strcspn(names, " ");
+        if (n == len && strncmp(names, name, len) == 0) {
+            return true;
+        }
+        names += n;
+        if (*names == ' ') {
+            names++;
+        }
+    }
+    return false;
+}
+
+static const struct argol_option *argol_long(const struct argol_spec *spec, const char *name,
+                                             size_t len)
+{
+    for (size_t i = 0; i < spec->options_len; i++) {
+        if (argol_has_name(spec->options[i].long_names, name, len)) {
+            return &spec->options[i];
+        }
+    }
+    return NULL;
+}
+
+static const struct argol_option *argol_short(const struct argol_spec *spec, const char *c,
+                                              size_t len)
+{
+    for (size_t i = 0; i < spec->options_len; i++) {
+        const char *s = spec->options[i].short_name;
+        if (s != NULL && strlen(s) == len && strncmp(s, c, len) == 0) {
+            return &spec->options[i];
+        }
+    }
+    return NULL;
+}
+
+static int argol_subcommand(const struct argol_spec *spec, const char *arg)
+{
+    for (size_t i = 0; i < spec->commands_len; i++) {
+        if (argol_has_name(spec->commands[i], arg, strlen(arg))) {
+            return (int)i;
+        }
+    }
+    return -1;
+}
+
+/* A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short name claims it. */
+static bool argol_is_option(const struct argol_spec *spec, const char *arg)
+{
+    if (strcmp(arg, "--") == 0) {
+        return true;
+    }
+    if (arg[0] != '-' || arg[1] == '\0') {
+        return false;
+    }
+    return argol_short(spec, arg + 1, argol_char_len(arg + 1)) != NULL
+        || !argol_is_number(arg + 1);
+}
+
+/* Lists hold at most one item per word of argv, so their size is fixed when first used. */
+static bool argol_push(struct argol_list *list, const char *s, int argc)
+{
+    if (list->items == NULL) {
+        list->items = (const char **)argol_alloc((size_t)argc, sizeof *list->items);
+        if (list->items == NULL) {
+            return false;
+        }
+    }
+    list->items[list->len++] = s;
+    return true;
+}
+
+/* Record a flag, and the action when it has one. Returns whether it had one. */
+static bool argol_set_flag(struct argol_matched *m, const struct argol_option *opt)
+{
+    m->flags[opt - m->spec->options] = true;
+    if (opt->action == ARGOL_ACTION_NONE) {
+        return false;
+    }
+    m->root->action = opt->action;
+    m->root->message = opt->action == ARGOL_SHOW_HELP ? m->spec->help : m->spec->version;
+    return true;
+}
+
+/*
+ * Collect the values of a named argument: the inline one, if any, then following words up to the
+ * maximum, stopping at anything that looks like an option.
+ */
+static int argol_take(struct argol_matched *m, const struct argol_option *opt, const char *value,
+                      int argc, char **argv, int *i)
+{
+    struct argol_list *list = &m->values[opt - m->spec->options];
+    int given = 0;
+
+    if (opt->max == 1) {
+        list->len = 0;
+    }
+    if (value != NULL) {
+        if (!argol_push(list, value, argc)) {
+            return argol_no_memory(m->root);
+        }
+        given++;
+    }
+    while ((opt->max < 0 || given < opt->max) && *i + 1 < argc
+           && !argol_is_option(m->spec, argv[*i + 1])) {
+        if (!argol_push(list, argv[++*i], argc)) {
+            return argol_no_memory(m->root);
+        }
+        given++;
+    }
+    if (given < opt->min) {
+        char name[128];
+        snprintf(name, sizeof name, "--%s", opt->key);
+        return argol_count(m->root, ARGOL_MISSING_VALUE, name, opt->min, opt->max);
+    }
+    return ARGOL_OK;
+}
+
+static int argol_long_option(struct argol_matched *m, const char *name, int argc, char **argv,
+                             int *i)
+{
+    const char *eq = strchr(name, '=');
+    int len = eq != NULL ? (int)(eq - name) : (int)strlen(name);
+    const struct argol_option *opt = argol_long(m->spec, name, (size_t)len);
+
+    if (opt == NULL) {
+        return argol_fail(m->root, ARGOL_UNKNOWN_OPTION, "unknown option '--%.*s'", len, name);
+    }
+    if (opt->flag && eq != NULL) {
+        return argol_fail(m->root, ARGOL_FLAG_WITH_VALUE, "flag '--%.*s' does not take a value",
+                          len, name);
+    }
+    if (opt->flag) {
+        argol_set_flag(m, opt);
+        return ARGOL_OK;
+    }
+    return argol_take(m, opt, eq != NULL ? eq + 1 : NULL, argc, argv, i);
+}
+
+static int argol_cluster(struct argol_matched *m, const char *c, int argc, char **argv, int *i)
+{
+    while (*c != '\0') {
+        size_t len = argol_char_len(c);
+        const struct argol_option *opt = argol_short(m->spec, c, len);
+        if (opt == NULL) {
+            return argol_fail(m->root, ARGOL_UNKNOWN_OPTION, "unknown option '-%.*s'", (int)len,
+                              c);
+        }
+        c += len;
+        if (opt->flag) {
+            if (argol_set_flag(m, opt)) {
+                return ARGOL_OK;
+            }
+            continue;
+        }
+        if (*c == '=') {
+            c++;
+        }
+        return argol_take(m, opt, *c != '\0' ? c : NULL, argc, argv, i);
+    }
+    return ARGOL_OK;
+}
+
+/*
+ * Hand out positional words in order, leaving enough for the minimum of later positionals.
+ * Leftover words are only allowed after `--`, where they are passed through as trailing; see
+ * argol_leftover.
+ */
+static int argol_finish(struct argol_matched *m, const struct argol_list *words, int dashes,
+                        int argc, char **argv)
+{
+    const struct argol_spec *spec = m->spec;
+    size_t next = 0;
+
+    for (size_t p = 0; p < spec->positionals_len; p++) {
+        const struct argol_positional *pos = &spec->positionals[p];
+        size_t reserved = 0;
+        size_t n;
+        for (size_t q = p + 1; q < spec->positionals_len; q++) {
+            reserved += (size_t)spec->positionals[q].min;
+        }
+        n = words->len - next > reserved ? words->len - next - reserved : 0;
+        if (pos->max >= 0 && n > (size_t)pos->max) {
+            n = (size_t)pos->max;
+        }
+        if (n > 0 && n < (size_t)pos->min) {
+            return argol_count(m->root, ARGOL_MISSING_VALUE, pos->key, pos->min, pos->max);
+        }
+        for (size_t j = 0; j < n; j++) {
+            if (!argol_push(&m->values[spec->options_len + p], words->items[next + j], argc)) {
+                return argol_no_memory(m->root);
+            }
+        }
+        next += n;
+    }
+
+    /* Words after `--` come last in argv */
+    m->root->trailing = argv + argc - (words->len - next);
+    m->root->trailing_len = words->len - next;
+    if (next < words->len && (dashes < 0 || next < (size_t)dashes)) {
+        m->unexpected = words->items[next];
+    }
+    return ARGOL_OK;
+}
+
+/* Match argv[from..] against spec, stopping at the first subcommand or at an action. */
+static int argol_match(struct argol_matched *m, const struct argol_spec *spec,
+                       struct argol_args *root, int argc, char **argv, int from)
+{
+    struct argol_list words = {NULL, 0};
+    int dashes = -1;
+    int err = ARGOL_OK;
+
+    memset(m, 0, sizeof *m);
+    m->root = root;
+    m->spec = spec;
+    m->sub = -1;
+    m->flags = (bool *)argol_alloc(spec->options_len, sizeof *m->flags);
+    m->values = (struct argol_list *)argol_alloc(spec->options_len + spec->positionals_len,
+                                                 sizeof *m->values);
+    if (m->flags == NULL || m->values == NULL) {
+        return argol_no_memory(root);
+    }
+
+    for (int i = from; i < argc && err == ARGOL_OK; i++) {
+        const char *arg = argv[i];
+        if (dashes >= 0) {
+            err = argol_push(&words, arg, argc) ? ARGOL_OK : argol_no_memory(root);
+        } else if (strcmp(arg, "--") == 0) {
+            dashes = (int)words.len;
+        } else if (strncmp(arg, "--", 2) == 0) {
+            err = argol_long_option(m, arg + 2, argc, argv, &i);
+        } else if (argol_is_option(spec, arg)) {
+            err = argol_cluster(m, arg + 1, argc, argv, &i);
+        } else if (words.len == 0 && (m->sub = argol_subcommand(spec, arg)) >= 0) {
+            m->rest = i + 1;
+            break;
+        } else {
+            err = argol_push(&words, arg, argc) ? ARGOL_OK : argol_no_memory(root);
+        }
+        if (root->action != ARGOL_ACTION_NONE) {
+            break;
+        }
+    }
+    if (err == ARGOL_OK && root->action == ARGOL_ACTION_NONE) {
+        err = argol_finish(m, &words, dashes, argc, argv);
+    }
+    free(words.items);
+    return err;
+}
+
+static void argol_release(struct argol_matched *m)
+{
+    if (m->values != NULL) {
+        for (size_t i = 0; i < m->spec->options_len + m->spec->positionals_len; i++) {
+            free(m->values[i].items);
+        }
+    }
+    free(m->values);
+    free(m->flags);
+}
+
+/* Fail on words left over before `--`, after positionals are converted. */
+static int argol_leftover(const struct argol_matched *m)
+{
+    if (m->unexpected == NULL) {
+        return ARGOL_OK;
+    }
+    return argol_fail(m->root, ARGOL_UNEXPECTED_ARGUMENT, "unexpected argument '%s'",
+                      m->unexpected);
+}
+"#;
+
+const TOO_MANY_HELPER: &str = r#"
+static int argol_too_many(struct argol_args *root, const char *key, int min, int max)
+{
+    char name[128];
+    snprintf(name, sizeof name, "--%s", key);
+    return argol_count(root, ARGOL_TOO_MANY_VALUES, name, min, max);
+}
+"#;
+
+const MISSING_HELPER: &str = r#"
+static int argol_missing(struct argol_args *root, const char *key)
+{
+    return argol_fail(root, ARGOL_MISSING_REQUIRED, "missing required argument '%s'", key);
+}
+"#;
+
+const NOT_ALLOWED_HELPER: &str = r#"
+static int argol_not_allowed(struct argol_args *root, const char *key, const char *s,
+                             const char *choices)
+{
+    return argol_fail(root, ARGOL_NOT_ALLOWED, "invalid value '%s' for '%s' (possible values: %s)",
+                      s, key, choices);
+}
+"#;
+
+const INVALID_HELPER: &str = r#"
+static int argol_invalid(struct argol_args *root, const char *key, const char *s, const char *type)
+{
+    return argol_fail(root, ARGOL_INVALID_VALUE, "invalid value '%s' for '%s': expected %s", s, key,
+                      type);
+}
+"#;
+
+const INTEGER_HELPER: &str = r#"
+static bool argol_is_integer(const char *s, bool negative)
+{
+    if (*s == '+' || (negative && *s == '-')) {
+        s++;
+    }
+    if (!argol_is_digit(*s)) {
+        return false;
+    }
+    while (argol_is_digit(*s)) {
+        s++;
+    }
+    return *s == '\0';
+}
+"#;
+
+const INT_HELPER: &str = r#"
+static int argol_to_int(struct argol_args *root, const char *key, const char *s, int64_t *x)
+{
+    errno = 0;
+    *x = strtoll(s, NULL, 10);
+    if (!argol_is_integer(s, true) || errno == ERANGE) {
+        return argol_invalid(root, key, s, "int");
+    }
+    return ARGOL_OK;
+}
+"#;
+
+const UINT_HELPER: &str = r#"
+static int argol_to_uint(struct argol_args *root, const char *key, const char *s, uint64_t *x)
+{
+    long long n;
+    errno = 0;
+    n = strtoll(s, NULL, 10);
+    if (!argol_is_integer(s, false) || errno == ERANGE) {
+        return argol_invalid(root, key, s, "uint");
+    }
+    *x = (uint64_t)n;
+    return ARGOL_OK;
+}
+"#;
+
+const FLOAT_HELPER: &str = r#"
+static int argol_to_float(struct argol_args *root, const char *key, const char *s, double *x)
+{
+    if (!argol_is_number(s)) {
+        return argol_invalid(root, key, s, "float");
+    }
+    *x = strtod(s, NULL);
+    return ARGOL_OK;
+}
+"#;
+
+const BOOL_HELPER: &str = r#"
+static int argol_to_bool(struct argol_args *root, const char *key, const char *s, bool *x)
+{
+    if (strcmp(s, "true") != 0 && strcmp(s, "false") != 0) {
+        return argol_invalid(root, key, s, "bool");
+    }
+    *x = strcmp(s, "true") == 0;
+    return ARGOL_OK;
+}
+"#;
+
+const FOOT: &str = r#"
+int argol_parse(int argc, char **argv, struct argol_args *out)
+{
+    memset(out, 0, sizeof *out);
+    return argol_build_args(out, out, argc, argv, 1);
+}
+
+const char *argol_error_kind(int code)
+{
+    static const char *const kinds[] = {
+        "ok", "unknown_option", "unexpected_argument", "flag_with_value", "missing_value",
+        "too_many_values", "missing_required", "invalid_value", "not_allowed", "no_memory",
+    };
+    if (code < 0 || (size_t)code >= sizeof kinds / sizeof *kinds) {
+        return "unknown";
+    }
+    return kinds[code];
+}
+"#;
+
+fn snake(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Members of `struct argol_args` that are not arguments.
+const TOP_MEMBERS: [&str; 7] = [
+    "action",
+    "message",
+    "error",
+    "command",
+    "command_len",
+    "trailing",
+    "trailing_len",
+];
+
+// Member for an argument or subcommand; one that would take the name of a member in `reserved`
+// gets an `_arg` suffix
+fn field(name: &str, reserved: &[&str]) -> String {
+    let name = snake(name);
+    match name.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{name}"),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("{name}_"),
+        _ if reserved.contains(&name.as_str()) => format!("{name}_arg"),
+        _ => name,
+    }
+}
+
+// Identifier for a command in the names of its spec and functions. Named after the command's
+// struct, so it is unique whenever the struct names are, and never takes the name of a helper.
+fn ident(path: &[String]) -> String {
+    let mut parts: Vec<String> = path.iter().map(|p| snake(p)).collect();
+    parts.push("args".into());
+    parts.join("_")
+}
+
+fn struct_name(path: &[String]) -> String {
+    format!("argol_{}", ident(path))
+}
+
+// Name of the enum of a command's subcommands, and the prefix of its constants
+fn enum_name(path: &[String]) -> (String, String) {
+    let name = if path.is_empty() {
+        "argol_command".to_string()
+    } else {
+        format!("argol_{}_command", ident(path))
+    };
+    let prefix = name.to_ascii_uppercase();
+    (name, prefix)
+}
+
+fn c_type(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str | ArgType::Path => "const char *",
+        ArgType::Int => "int64_t ",
+        ArgType::UInt => "uint64_t ",
+        ArgType::Float => "double ",
+        ArgType::Bool => "bool ",
+    }
+}
+
+fn type_name(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str => "str",
+        ArgType::Int => "int",
+        ArgType::UInt => "uint",
+        ArgType::Float => "float",
+        ArgType::Bool => "bool",
+        ArgType::Path => "path",
+    }
+}
+
+fn c_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    let mut prev = '\0';
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            // Keep `??` from being read as a trigraph
+            '?' if prev == '?' => out.push_str("\\?"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\{:03o}", c as u32)),
+            c => out.push(c),
+        }
+        prev = c;
+    }
+    out.push('"');
+    out
+}
+
+fn value_lit(v: &Value) -> String {
+    match v {
+        Value::Str(s) => c_str(s),
+        Value::Int(i) => i.to_string(),
+        Value::Float(x) => format!("{x:?}"),
+        Value::Bool(b) => b.to_string(),
+    }
+}
+
+fn max_lit(n: Nargs) -> String {
+    n.max.map_or("-1".into(), |m| m.to_string())
+}
+
+fn comment(w: &mut Writer, desc: &Option<String>) {
+    if let Some(desc) = desc {
+        let text = desc.lines().collect::<Vec<_>>().join(" ");
+        w.line(&format!("/* {} */", text.replace("*/", "* /")));
+    }
+}
+
+// `static const char name[] = "line\n" ...;`, one literal per line
+fn text(w: &mut Writer, name: &str, text: &str) {
+    let lines: Vec<String> = text.split_inclusive('\n').map(c_str).collect();
+    w.line(&format!("static const char {name}[] ="));
+    w.indent();
+    let (last, init) = lines.split_last().unwrap();
+    for line in init {
+        w.line(line);
+    }
+    w.line(&format!("{last};"));
+    w.dedent();
+}
+
+fn depth(cmd: &Command) -> usize {
+    cmd.commands.iter().map(|c| depth(c) + 1).max().unwrap_or(0)
+}
+
+// An argument as the generated `argol_build_*` function converts it
+struct Arg<'a> {
+    key: String,
+    field: String,
+    desc: &'a Option<String>,
+    ty: ArgType,
+    options: &'a Option<Options>,
+    default: &'a [Value],
+    nargs: Nargs,
+    required: bool,
+    // Index into the matched values
+    index: usize,
+    named: bool,
+}
+
+impl Arg<'_> {
+    fn multiple(&self) -> bool {
+        self.nargs.max != Some(1)
+    }
+
+    // Optional single values that are not pointers say whether they were given
+    fn has_flag(&self) -> bool {
+        !self.multiple()
+            && !self.required
+            && self.default.is_empty()
+            && !matches!(self.ty, ArgType::Str | ArgType::Path)
+    }
+
+    fn members(&self, w: &mut Writer) {
+        comment(w, self.desc);
+        let ty = c_type(self.ty);
+        if self.multiple() {
+            w.line(&format!("{ty}*{};", self.field));
+            w.line(&format!("size_t {}_len;", self.field));
+        } else {
+            if self.has_flag() {
+                w.line(&format!("bool has_{};", self.field));
+            }
+            w.line(&format!("{ty}{};", self.field));
+        }
+    }
+
+    // Condition on `x` for the allowed values, and how they are listed in errors
+    fn allowed(&self, x: &str) -> Option<(String, String)> {
+        match self.options {
+            Some(Options::Values(vs)) => {
+                let tests: Vec<String> = vs
+                    .iter()
+                    .map(|v| match self.ty {
+                        ArgType::Str | ArgType::Path => {
+                            format!("strcmp({x}, {}) == 0", value_lit(v))
+                        }
+                        _ => format!("{x} == {}", value_lit(v)),
+                    })
+                    .collect();
+                let choices: Vec<String> = vs.iter().map(|v| v.to_string()).collect();
+                Some((tests.join(" || "), choices.join(", ")))
+            }
+            Some(Options::Range(r))
+                if matches!(self.ty, ArgType::Int | ArgType::UInt | ArgType::Float) =>
+            {
+                let f = |v: &Value| match v {
+                    Value::Int(i) => format!("{:?}", *i as f64),
+                    v => value_lit(v),
+                };
+                let mut tests = Vec::new();
+                if let Some(start) = &r.start {
+                    tests.push(format!("(double){x} >= {}", f(start)));
+                }
+                if let Some(end) = &r.end {
+                    let op = if r.inclusive { "<=" } else { "<" };
+                    tests.push(format!("(double){x} {op} {}", f(end)));
+                }
+                if tests.is_empty() {
+                    return None;
+                }
+                Some((tests.join(" && "), r.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct Generator<'a> {
+    program: &'a Program,
+    types: Vec<ArgType>,
+    too_many: bool,
+    missing: bool,
+    not_allowed: bool,
+    structs: Vec<String>,
+    functions: Vec<String>,
+    // Struct names and enum constants, which are declared at file level, with what they are for
+    globals: Vec<(String, String)>,
+    clashes: Vec<String>,
+}
+
+impl Generator<'_> {
+    fn spec(&mut self, cmd: &Command, path: &[String]) -> String {
+        let id = ident(path);
+        let mut w = Writer::new("    ");
+
+        let has = |action| cmd.flags.iter().any(|f| f.action == Some(action));
+        let help = if has(Action::ShowHelp) {
+            text(
+                &mut w,
+                &format!("argol_{id}_help"),
+                &help::render(self.program, path, 80),
+            );
+            format!("argol_{id}_help")
+        } else {
+            "NULL".into()
+        };
+        let version = if has(Action::ShowVersion) {
+            let version = self.program.version.clone().unwrap_or_default();
+            let line = match &self.program.name {
+                Some(name) => format!("{name} {version}\n"),
+                None => format!("{version}\n"),
+            };
+            text(&mut w, &format!("argol_{id}_version"), &line);
+            format!("argol_{id}_version")
+        } else {
+            "NULL".into()
+        };
+
+        let mut options = Vec::new();
+        for f in &cmd.flags {
+            let action = match f.action {
+                Some(Action::ShowHelp) => "ARGOL_SHOW_HELP",
+                Some(Action::ShowVersion) => "ARGOL_SHOW_VERSION",
+                None => "ARGOL_ACTION_NONE",
+            };
+            options.push(format!(
+                "{{{}, {}, {}, true, {action}, 0, 0}}",
+                c_str(&f.names.primary()),
+                f.names
+                    .short
+                    .map_or("NULL".into(), |s| c_str(&s.to_string())),
+                c_str(&f.names.long.join(" "))
+            ));
+        }
+        for n in &cmd.named {
+            options.push(format!(
+                "{{{}, {}, {}, false, ARGOL_ACTION_NONE, {}, {}}}",
+                c_str(&n.names.primary()),
+                n.names
+                    .short
+                    .map_or("NULL".into(), |s| c_str(&s.to_string())),
+                c_str(&n.names.long.join(" ")),
+                n.nargs.min,
+                max_lit(n.nargs)
+            ));
+        }
+        let positionals: Vec<String> = cmd
+            .positionals
+            .iter()
+            .map(|p| {
+                format!(
+                    "{{{}, {}, {}}}",
+                    c_str(&p.name),
+                    p.nargs.min,
+                    max_lit(p.nargs)
+                )
+            })
+            .collect();
+        let commands: Vec<String> = cmd
+            .commands
+            .iter()
+            .map(|sub| c_str(&sub.names.join(" ")))
+            .collect();
+
+        let mut table = |ty: &str, name: &str, items: &[String]| -> String {
+            if items.is_empty() {
+                return "NULL, 0".into();
+            }
+            w.open(&format!("static {ty} argol_{id}_{name}[] = {{"));
+            for item in items {
+                w.line(&format!("{item},"));
+            }
+            w.close("};");
+            format!("argol_{id}_{name}, {}", items.len())
+        };
+        let fields = [
+            table("const struct argol_option", "options", &options),
+            table("const struct argol_positional", "positionals", &positionals),
+            table("const char *const", "commands", &commands),
+            help,
+            version,
+        ];
+        w.open(&format!(
+            "static const struct argol_spec argol_{id}_spec = {{"
+        ));
+        for f in fields {
+            w.line(&format!("{f},"));
+        }
+        w.close("};");
+        self.functions.push(w.finish());
+        format!("argol_{id}_spec")
+    }
+
+    // Conversion of the values of `arg` from `v` into `out`
+    fn convert(&mut self, w: &mut Writer, arg: &Arg) {
+        let key = c_str(&arg.key);
+        let target = if arg.multiple() {
+            format!("out->{}[i]", arg.field)
+        } else {
+            format!("out->{}", arg.field)
+        };
+
+        w.line("");
+        w.line(&format!("v = &m.values[{}];", arg.index));
+        if let Some(max) = arg.nargs.max
+            && arg.named
+            && max != 1
+        {
+            self.too_many = true;
+            w.open(&format!("if (v->len > {max}) {{"));
+            w.line(&format!(
+                "err = argol_too_many(root, {key}, {}, {max});",
+                arg.nargs.min
+            ));
+            w.line("goto done;");
+            w.close("}");
+        }
+        if arg.required {
+            self.missing = true;
+            w.open("if (v->len == 0) {");
+            w.line(&format!("err = argol_missing(root, {key});"));
+            w.line("goto done;");
+            w.close("}");
+        }
+
+        let defaults: Vec<String> = arg.default.iter().map(value_lit).collect();
+        if arg.multiple() {
+            let len = if defaults.is_empty() {
+                "v->len".to_string()
+            } else {
+                format!("v->len > 0 ? v->len : {}", defaults.len())
+            };
+            let cast = match arg.ty {
+                ArgType::Str | ArgType::Path => "const char **",
+                ArgType::Int => "int64_t *",
+                ArgType::UInt => "uint64_t *",
+                ArgType::Float => "double *",
+                ArgType::Bool => "bool *",
+            };
+            w.line(&format!("out->{}_len = {len};", arg.field));
+            w.line(&format!(
+                "out->{f} = ({cast})argol_alloc(out->{f}_len, sizeof *out->{f});",
+                f = arg.field
+            ));
+            w.open(&format!("if (out->{} == NULL) {{", arg.field));
+            w.line("err = argol_no_memory(root);");
+            w.line("goto done;");
+            w.close("}");
+            if !defaults.is_empty() {
+                w.open("if (v->len == 0) {");
+                for (i, d) in defaults.iter().enumerate() {
+                    w.line(&format!("out->{}[{i}] = {d};", arg.field));
+                }
+                w.close("}");
+            }
+        } else if let [default] = defaults.as_slice() {
+            w.line(&format!("out->{} = {default};", arg.field));
+        }
+
+        w.open("for (size_t i = 0; i < v->len; i++) {");
+        match arg.ty {
+            ArgType::Str | ArgType::Path => w.line(&format!("{target} = v->items[i];")),
+            ty => {
+                if !self.types.contains(&ty) {
+                    self.types.push(ty);
+                }
+                w.line(&format!(
+                    "err = argol_to_{}(root, {key}, v->items[i], &{target});",
+                    type_name(ty)
+                ));
+                w.open("if (err != ARGOL_OK) {");
+                w.line("goto done;");
+                w.close("}");
+            }
+        }
+        if let Some((test, choices)) = arg.allowed(&target) {
+            self.not_allowed = true;
+            w.open(&format!("if (!({test})) {{"));
+            w.line(&format!(
+                "err = argol_not_allowed(root, {key}, v->items[i], {});",
+                c_str(&choices)
+            ));
+            w.line("goto done;");
+            w.close("}");
+        }
+        if arg.has_flag() {
+            w.line(&format!("out->has_{} = true;", arg.field));
+        }
+        w.close("}");
+    }
+
+    // Whether `argol_free_*` has anything to free for `cmd`
+    fn owns(cmd: &Command) -> bool {
+        cmd.positionals.iter().any(|p| p.nargs.max != Some(1))
+            || cmd.named.iter().any(|n| n.nargs.max != Some(1))
+            || cmd.commands.iter().any(Self::owns)
+    }
+
+    fn command(&mut self, cmd: &Command, path: &[String]) {
+        let subs: Vec<(&Command, Vec<String>)> = cmd
+            .commands
+            .iter()
+            .map(|sub| {
+                let mut sub_path = path.to_vec();
+                sub_path.push(sub.names[0].clone());
+                (sub, sub_path)
+            })
+            .collect();
+        for (sub, sub_path) in &subs {
+            self.command(sub, sub_path);
+        }
+
+        let mut reserved: Vec<&str> = Vec::new();
+        if path.is_empty() {
+            reserved.extend(TOP_MEMBERS);
+        }
+        if !subs.is_empty() {
+            reserved.push("subcommand");
+        }
+        let field = |name: &str| field(name, &reserved);
+        let flags: Vec<_> = cmd.flags.iter().filter(|f| f.action.is_none()).collect();
+        let offset = cmd.flags.len() + cmd.named.len();
+        let args: Vec<Arg> = cmd
+            .positionals
+            .iter()
+            .enumerate()
+            .map(|(i, p)| Arg {
+                key: p.name.clone(),
+                field: field(&p.name),
+                desc: &p.desc,
+                ty: p.ty,
+                options: &p.options,
+                default: &p.default,
+                nargs: p.nargs,
+                required: p.required,
+                index: offset + i,
+                named: false,
+            })
+            .chain(cmd.named.iter().enumerate().map(|(i, n)| Arg {
+                key: n.names.primary(),
+                field: field(&n.names.primary()),
+                desc: &n.desc,
+                ty: n.ty,
+                options: &n.options,
+                default: &n.default,
+                nargs: n.nargs,
+                required: n.required,
+                index: cmd.flags.len() + i,
+                named: true,
+            }))
+            .collect();
+        let name = struct_name(path);
+        let (enum_name, enum_prefix) = enum_name(path);
+        let constant = |sub: &str| format!("{enum_prefix}_{}", snake(sub).to_ascii_uppercase());
+
+        let mut members: Vec<(String, String)> = reserved
+            .iter()
+            .map(|m| (m.to_string(), "a member argolify adds".to_string()))
+            .collect();
+        for f in &flags {
+            let key = f.names.primary();
+            members.push((field(&key), format!("argument `{key}`")));
+        }
+        for arg in &args {
+            let origin = format!("argument `{}`", arg.key);
+            members.push((arg.field.clone(), origin.clone()));
+            if arg.multiple() {
+                members.push((format!("{}_len", arg.field), origin));
+            } else if arg.has_flag() {
+                members.push((format!("has_{}", arg.field), origin));
+            }
+        }
+        for (sub, _) in &subs {
+            let origin = format!("command `{}`", sub.names[0]);
+            members.push((field(&sub.names[0]), origin));
+        }
+        self.clashes
+            .extend(clashes(&format!("`struct {name}`"), &members));
+        self.globals.push((name.clone(), command_origin(path)));
+        if !subs.is_empty() {
+            let origin = format!("the constant for no subcommand of {}", command_origin(path));
+            self.globals.push((format!("{enum_prefix}_NONE"), origin));
+        }
+        for (sub, sub_path) in &subs {
+            let origin = format!("the constant for {}", command_origin(sub_path));
+            self.globals.push((constant(&sub.names[0]), origin));
+        }
+
+        // The struct, after the enum of its subcommands
+        let mut w = Writer::new("    ");
+        if !subs.is_empty() {
+            w.open(&format!("enum {enum_name} {{"));
+            w.line(&format!("{enum_prefix}_NONE = 0,"));
+            for (i, (sub, _)) in subs.iter().enumerate() {
+                let sep = if i + 1 < subs.len() { "," } else { "" };
+                w.line(&format!("{}{sep}", constant(&sub.names[0])));
+            }
+            w.close("};");
+            w.line("");
+        }
+        let program = self.program.name.as_deref().unwrap_or("the program");
+        if path.is_empty() {
+            w.line(&format!("/* Arguments matched for {program}. */"));
+        } else {
+            w.line(&format!(
+                "/* Arguments of `{program} {}`. */",
+                path.join(" ")
+            ));
+        }
+        w.open(&format!("struct {name} {{"));
+        if path.is_empty() {
+            w.line("/* Set instead of the arguments when a flag like --help is given */");
+            w.line("enum argol_action action;");
+            w.line("/* Help or version text to print for the action */");
+            w.line("const char *message;");
+            w.line("/* Message for the error argol_parse returned */");
+            w.line("char error[256];");
+            w.line("/* Path of subcommands given, by their first names */");
+            w.line(&format!(
+                "const char *command[{}];",
+                depth(&self.program.cmd).max(1)
+            ));
+            w.line("size_t command_len;");
+            w.line("/* Words after `--` that no positional took */");
+            w.line("char **trailing;");
+            w.line("size_t trailing_len;");
+            if !flags.is_empty() || !args.is_empty() || !subs.is_empty() {
+                w.line("");
+            }
+        }
+        for f in &flags {
+            comment(&mut w, &f.desc);
+            w.line(&format!("bool {};", field(&f.names.primary())));
+        }
+        for arg in &args {
+            arg.members(&mut w);
+        }
+        if !subs.is_empty() {
+            w.line("/* Which of the subcommands below was given */");
+            w.line(&format!("enum {enum_name} subcommand;"));
+            for (sub, sub_path) in &subs {
+                w.line(&format!(
+                    "struct {} {};",
+                    struct_name(sub_path),
+                    field(&sub.names[0])
+                ));
+            }
+        }
+        if !path.is_empty() && flags.is_empty() && args.is_empty() && subs.is_empty() {
+            w.line("/* No arguments; C does not allow empty structs */");
+            w.line("char unused;");
+        }
+        w.close("};");
+        self.structs.push(w.finish());
+
+        let spec = self.spec(cmd, path);
+
+        // The build function
+        let id = ident(path);
+        let mut w = Writer::new("    ");
+        w.line(&format!(
+            "static int argol_build_{id}(struct argol_args *root, struct {name} *out, int argc,"
+        ));
+        w.line(&format!(
+            "{}char **argv, int from)",
+            " ".repeat("static int argol_build_(".len() + id.len())
+        ));
+        w.open("{");
+        w.line("struct argol_matched m;");
+        if !args.is_empty() {
+            w.line("const struct argol_list *v;");
+        }
+        w.line(&format!(
+            "int err = argol_match(&m, &{spec}, root, argc, argv, from);"
+        ));
+        w.line("");
+        w.open("if (err != ARGOL_OK || root->action != ARGOL_ACTION_NONE) {");
+        w.line("goto done;");
+        w.close("}");
+        for (i, f) in cmd.flags.iter().enumerate() {
+            if f.action.is_none() {
+                w.line(&format!(
+                    "out->{} = m.flags[{i}];",
+                    field(&f.names.primary())
+                ));
+            }
+        }
+        for (i, arg) in args.iter().enumerate() {
+            if i == cmd.positionals.len() {
+                leftover(&mut w);
+            }
+            self.convert(&mut w, arg);
+        }
+        if cmd.named.is_empty() {
+            leftover(&mut w);
+        }
+
+        if !subs.is_empty() {
+            w.line("");
+            w.line("switch (m.sub) {");
+            for (i, (sub, sub_path)) in subs.iter().enumerate() {
+                w.line(&format!("case {i}:"));
+                w.indent();
+                w.line(&format!(
+                    "root->command[root->command_len++] = {};",
+                    c_str(&sub.names[0])
+                ));
+                w.line(&format!("out->subcommand = {};", constant(&sub.names[0])));
+                w.line(&format!(
+                    "err = argol_build_{}(root, &out->{}, argc, argv, m.rest);",
+                    ident(sub_path),
+                    field(&sub.names[0])
+                ));
+                w.line("break;");
+                w.dedent();
+            }
+            w.line("}");
+        }
+        w.line("");
+        w.dedent();
+        w.line("done:");
+        w.indent();
+        w.line("argol_release(&m);");
+        w.line("return err;");
+        w.close("}");
+        self.functions.push(w.finish());
+
+        // The function freeing the lists
+        if !path.is_empty() && !Self::owns(cmd) {
+            return;
+        }
+        let mut w = Writer::new("    ");
+        if path.is_empty() {
+            w.line("void argol_free(struct argol_args *args)");
+        } else {
+            w.line(&format!("static void argol_free_{id}(struct {name} *args)"));
+        }
+        w.open("{");
+        let mut empty = true;
+        for arg in args.iter().filter(|a| a.multiple()) {
+            w.line(&format!("free(args->{});", arg.field));
+            empty = false;
+        }
+        for (sub, sub_path) in subs.iter().filter(|(sub, _)| Self::owns(sub)) {
+            w.line(&format!(
+                "argol_free_{}(&args->{});",
+                ident(sub_path),
+                field(&sub.names[0])
+            ));
+            empty = false;
+        }
+        if empty {
+            w.line("(void)args;");
+        }
+        w.close("}");
+        self.functions.push(w.finish());
+    }
+}
+
+fn leftover(w: &mut Writer) {
+    w.line("");
+    w.line("err = argol_leftover(&m);");
+    w.open("if (err != ARGOL_OK) {");
+    w.line("goto done;");
+    w.close("}");
+}
+
+/// A single C header: a struct per command and `argol_parse`, with the implementation compiled
+/// where `ARGOL_IMPLEMENTATION` is defined.
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let mut generator = Generator {
+        program,
+        types: Vec::new(),
+        too_many: false,
+        missing: false,
+        not_allowed: false,
+        structs: Vec::new(),
+        functions: Vec::new(),
+        globals: Vec::new(),
+        clashes: Vec::new(),
+    };
+    generator.command(&program.cmd, &[]);
+    generator
+        .clashes
+        .extend(clashes("the header", &generator.globals));
+    if !generator.clashes.is_empty() {
+        return Err(generator.clashes);
+    }
+
+    let guard = match &program.name {
+        Some(name) => format!("ARGOL_{}_H", snake(name).to_ascii_uppercase()),
+        None => "ARGOL_H".into(),
+    };
+    let mut out = format!(
+        "/*\n * Generated by argolify from `{source}`; do not edit.\n *\n * Define \
+         ARGOL_IMPLEMENTATION in one C source file before including this header.\n */\n"
+    );
+    out.push_str(&format!("#ifndef {guard}\n#define {guard}\n"));
+    out.push_str(HEAD);
+    for item in &generator.structs {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out.push_str(DECLARATIONS);
+    out.push_str(&format!("\n#endif /* {guard} */\n"));
+
+    out.push_str("\n#if defined(ARGOL_IMPLEMENTATION) && !defined(ARGOL_IMPLEMENTED)\n");
+    out.push_str("#define ARGOL_IMPLEMENTED\n");
+    out.push_str(MATCHER);
+    let has = |ty| generator.types.contains(&ty);
+    let helpers = [
+        (generator.too_many, TOO_MANY_HELPER),
+        (generator.missing, MISSING_HELPER),
+        (generator.not_allowed, NOT_ALLOWED_HELPER),
+        (!generator.types.is_empty(), INVALID_HELPER),
+        (has(ArgType::Int) || has(ArgType::UInt), INTEGER_HELPER),
+    ];
+    for (used, helper) in helpers {
+        if used {
+            out.push_str(helper);
+        }
+    }
+    let helpers = [
+        (ArgType::Int, INT_HELPER),
+        (ArgType::UInt, UINT_HELPER),
+        (ArgType::Float, FLOAT_HELPER),
+        (ArgType::Bool, BOOL_HELPER),
+    ];
+    for (ty, helper) in helpers {
+        if has(ty) {
+            out.push_str(helper);
+        }
+    }
+    for item in &generator.functions {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out.push_str(FOOT);
+    out.push_str("\n#endif /* ARGOL_IMPLEMENTATION */\n");
+    Ok(out)
+}
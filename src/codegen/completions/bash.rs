@@ -0,0 +1,230 @@
+use super::{Node, Pos, Values, nodes};
+use crate::codegen::Writer;
+use crate::spec::Program;
+
+// Single-quoted shell word
+fn sq(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn words(words: &[String]) -> String {
+    sq(&words.join(" "))
+}
+
+fn compgen(w: &mut Writer, values: &Values) {
+    match values {
+        // `compgen -W` would split, unquote and expand the choices, so match them by prefix and
+        // quote the ones offered
+        Values::Choices(choices) => {
+            let choices: Vec<String> = choices.iter().map(|c| sq(c)).collect();
+            w.open(&format!("for word in {}; do", choices.join(" ")));
+            w.open("if [[ $word == \"$cur\"* ]]; then");
+            w.line("printf -v word %q \"$word\"");
+            w.line("COMPREPLY+=(\"$word\")");
+            w.close("fi");
+            w.close("done");
+        }
+        Values::Files => {
+            w.line("compopt -o filenames");
+            w.line("COMPREPLY+=($(compgen -f -- \"$cur\"))");
+        }
+        Values::Any => {}
+    }
+}
+
+// Arithmetic test for the positional words `p` takes, or `None` when it takes every one
+fn pos_test(p: &Pos) -> Option<String> {
+    match (p.first, p.last) {
+        (0, None) => None,
+        (first, None) => Some(format!("pos >= {first}")),
+        (first, Some(last)) if first == last => Some(format!("pos == {first}")),
+        (first, Some(last)) => Some(format!("pos >= {first} && pos <= {last}")),
+    }
+}
+
+fn guarded(w: &mut Writer, test: Option<String>, body: impl FnOnce(&mut Writer)) {
+    match test {
+        None => body(w),
+        Some(test) => {
+            w.open(&format!("if (({test})); then"));
+            body(w);
+            w.close("fi");
+        }
+    }
+}
+
+fn patterns(node: &Node, switches: &[String]) -> String {
+    switches
+        .iter()
+        .map(|s| format!("{},{s}", node.id))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn walk(w: &mut Writer, nodes: &[Node]) {
+    w.line(
+        "# Walk the words before the cursor the way the parser does: find the selected command,",
+    );
+    w.line("# count positional words and track an option that is still taking values");
+    w.open("for ((i = 1; i < COMP_CWORD; i++)); do");
+    w.line("word=${COMP_WORDS[i]}");
+    w.line("if ((dashes)); then");
+    w.line("    pos+=1");
+    w.line("elif [[ $word == = ]]; then");
+    w.line("    continue");
+    w.line("elif ((left != 0)) && [[ $word != -?* ]]; then");
+    w.line("    left=left-1");
+    w.line("elif [[ $word == -- ]]; then");
+    w.line("    dashes=1 left=0");
+    w.open("elif [[ $word == -?* ]]; then");
+    w.line("opt=$cmd,$word left=0");
+
+    // Options grouped by the most values they take, `-1` for unbounded
+    let mut takes: Vec<(String, Vec<String>)> = Vec::new();
+    for node in nodes {
+        for opt in &node.opts {
+            let Some((max, _)) = &opt.value else { continue };
+            let left = max.map_or("-1".into(), |m| m.to_string());
+            let pattern = patterns(node, &opt.switches());
+            match takes.iter_mut().find(|(l, _)| *l == left) {
+                Some((_, list)) => list.push(pattern),
+                None => takes.push((left, vec![pattern])),
+            }
+        }
+    }
+    if !takes.is_empty() {
+        w.open("case $opt in");
+        for (left, list) in &takes {
+            w.line(&format!("{}) left={left} ;;", list.join(" | ")));
+        }
+        w.close("esac");
+    }
+    w.dedent();
+    w.line("elif ((pos > 0)); then");
+    w.line("    pos+=1");
+    w.open("else");
+    w.open("case $cmd,$word in");
+    for node in nodes {
+        for (sub, id) in node.cmd.commands.iter().zip(&node.subs) {
+            w.line(&format!("{}) cmd={id} ;;", patterns(node, &sub.names)));
+        }
+    }
+    w.line("*) pos=1 ;;");
+    w.close("esac");
+    w.close("fi");
+    w.close("done");
+}
+
+fn option_values(w: &mut Writer, nodes: &[Node]) {
+    let arms: Vec<_> = nodes
+        .iter()
+        .flat_map(|node| node.opts.iter().map(move |opt| (node, opt)))
+        .filter_map(|(node, opt)| match &opt.value {
+            Some((_, Values::Any)) | None => None,
+            Some((_, values)) => Some((patterns(node, &opt.switches()), values)),
+        })
+        .collect();
+    if arms.is_empty() {
+        return;
+    }
+    w.line("");
+    w.open("if ((left != 0)) && [[ $cur != -?* ]]; then");
+    w.open("case $opt in");
+    for (pattern, values) in arms {
+        w.open(&format!("{pattern})"));
+        compgen(w, values);
+        w.line(";;");
+        w.dedent();
+    }
+    w.close("esac");
+    w.line("return");
+    w.close("fi");
+}
+
+fn option_names(w: &mut Writer, nodes: &[Node]) {
+    if nodes.iter().all(|n| n.opts.is_empty()) {
+        return;
+    }
+    w.line("");
+    w.open("if [[ $cur == -* ]] && ((!dashes)); then");
+    w.open("case $cmd in");
+    for node in nodes.iter().filter(|n| !n.opts.is_empty()) {
+        let switches: Vec<String> = node.opts.iter().flat_map(|o| o.switches()).collect();
+        w.line(&format!(
+            "{}) COMPREPLY=($(compgen -W {} -- \"$cur\")) ;;",
+            node.id,
+            words(&switches)
+        ));
+    }
+    w.close("esac");
+    w.line("return");
+    w.close("fi");
+}
+
+fn positionals(w: &mut Writer, nodes: &[Node]) {
+    let offers = |n: &Node| {
+        !n.subs.is_empty()
+            || n.positionals
+                .iter()
+                .any(|p| !matches!(p.values, Values::Any))
+    };
+    if !nodes.iter().any(offers) {
+        return;
+    }
+    w.line("");
+    w.open("case $cmd in");
+    for node in nodes.iter().filter(|n| offers(n)) {
+        w.open(&format!("{})", node.id));
+        if !node.subs.is_empty() {
+            let names: Vec<String> = node
+                .cmd
+                .commands
+                .iter()
+                .flat_map(|c| c.names.iter().cloned())
+                .collect();
+            guarded(w, Some("pos == 0 && !dashes".into()), |w| {
+                compgen(w, &Values::Choices(names))
+            });
+        }
+        for p in &node.positionals {
+            if !matches!(p.values, Values::Any) {
+                guarded(w, pos_test(p), |w| compgen(w, &p.values));
+            }
+        }
+        w.line(";;");
+        w.dedent();
+    }
+    w.close("esac");
+}
+
+pub fn generate(program: &Program, name: &str, source: &str) -> String {
+    let nodes = nodes(program, name);
+    let root = &nodes[0].id;
+
+    let mut w = Writer::new("    ");
+    w.line(&format!(
+        "# Generated by argolify from `{source}`; do not edit."
+    ));
+    w.line("#");
+    w.line(&format!(
+        "# bash completion for `{name}`: source it from ~/.bashrc, or install it as"
+    ));
+    w.line(&format!("# /usr/share/bash-completion/completions/{name}."));
+    w.line("");
+    w.open(&format!("_{root}() {{"));
+    w.line(&format!(
+        "local cur=${{COMP_WORDS[COMP_CWORD]}} cmd={root} opt= word"
+    ));
+    w.line("local -i i pos=0 left=0 dashes=0");
+    w.line("COMPREPLY=()");
+    w.line("[[ $cur == = ]] && cur=");
+    w.line("");
+    walk(&mut w, &nodes);
+    option_values(&mut w, &nodes);
+    option_names(&mut w, &nodes);
+    positionals(&mut w, &nodes);
+    w.close("}");
+    w.line("");
+    w.line(&format!("complete -F _{root} {}", sq(name)));
+    w.finish()
+}
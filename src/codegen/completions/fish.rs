@@ -0,0 +1,215 @@
+use super::{Node, Pos, Values, nodes};
+use crate::codegen::Writer;
+use crate::spec::Program;
+
+// Single-quoted fish string
+fn sq(s: &str) -> String {
+    format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+// A word `complete -a` keeps as is: it tokenizes and expands its argument, so escape everything
+// that could split or expand it
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if !c.is_alphanumeric() && !"-_.,:=+@/".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn state(w: &mut Writer, root: &str, nodes: &[Node]) {
+    w.line("");
+    w.line("# Print the command selected by the words before the cursor, the number of positional");
+    w.line("# words given to it and whether `--` was seen, walking them the way the parser does");
+    w.open(&format!("function __{root}_state"));
+    w.line(&format!("set -l cmd {root}"));
+    w.line("set -l pos 0");
+    w.line("set -l left 0");
+    w.line("set -l dashes 0");
+    w.line("set -l words (commandline -opc)");
+    w.line("set -e words[1]");
+    w.open("for word in $words");
+    w.open("if test $dashes = 1");
+    w.line("set pos (math $pos + 1)");
+    w.dedent();
+    w.open("else if test $left != 0; and not string match -qr -- '^-.' $word");
+    w.line("set left (math $left - 1)");
+    w.dedent();
+    w.open("else if test $word = --");
+    w.line("set dashes 1");
+    w.line("set left 0");
+    w.dedent();
+    w.open("else if string match -qr -- '^-.' $word");
+    w.line("set left 0");
+
+    // Options grouped by the most values they take, `-1` for unbounded
+    let mut takes: Vec<(String, Vec<String>)> = Vec::new();
+    for node in nodes {
+        for opt in &node.opts {
+            let Some((max, _)) = &opt.value else { continue };
+            let left = max.map_or("-1".into(), |m| m.to_string());
+            let patterns: Vec<String> = opt
+                .switches()
+                .iter()
+                .map(|s| format!("{},{s}", node.id))
+                .collect();
+            match takes.iter_mut().find(|(l, _)| *l == left) {
+                Some((_, list)) => list.extend(patterns),
+                None => takes.push((left, patterns)),
+            }
+        }
+    }
+    if !takes.is_empty() {
+        w.open("switch $cmd,$word");
+        for (left, list) in &takes {
+            w.open(&format!("case {}", list.join(" ")));
+            w.line(&format!("set left {left}"));
+            w.dedent();
+        }
+        w.close("end");
+    }
+    w.dedent();
+
+    w.open("else if test $pos != 0");
+    w.line("set pos (math $pos + 1)");
+    w.dedent();
+    w.open("else");
+    w.open("switch $cmd,$word");
+    for node in nodes {
+        for (sub, id) in node.cmd.commands.iter().zip(&node.subs) {
+            let names: Vec<String> = sub
+                .names
+                .iter()
+                .map(|n| format!("{},{n}", node.id))
+                .collect();
+            w.open(&format!("case {}", names.join(" ")));
+            w.line(&format!("set cmd {id}"));
+            w.dedent();
+        }
+    }
+    w.open("case '*'");
+    w.line("set pos 1");
+    w.dedent();
+    w.close("end");
+    w.close("end");
+    w.close("end");
+    w.line("echo $cmd $pos $dashes");
+    w.close("end");
+
+    w.line("");
+    w.line(
+        "# Whether the words so far select command $argv[1], with a positional count from $argv[2]",
+    );
+    w.line("# up to $argv[3] when given");
+    w.open(&format!("function __{root}_at"));
+    w.line(&format!("set -l state (string split ' ' (__{root}_state))"));
+    w.line("test $state[1] = $argv[1]; or return 1");
+    w.line("set -q argv[2]; and test $state[2] -lt $argv[2]; and return 1");
+    w.line("set -q argv[3]; and test $state[2] -gt $argv[3]; and return 1");
+    w.line("return 0");
+    w.close("end");
+
+    if nodes.iter().all(|n| n.subs.is_empty()) {
+        return;
+    }
+    w.line("");
+    w.line("# Whether a subcommand of $argv[1] can come next");
+    w.open(&format!("function __{root}_sub"));
+    w.line(&format!("test (__{root}_state) = \"$argv[1] 0 0\""));
+    w.close("end");
+}
+
+fn choices(values: &Values) -> Option<String> {
+    match values {
+        Values::Choices(choices) => {
+            let words: Vec<String> = choices.iter().map(|c| escape(c)).collect();
+            Some(format!("-a {}", sq(&words.join(" "))))
+        }
+        _ => None,
+    }
+}
+
+fn pos_range(p: &Pos) -> String {
+    match p.last {
+        None => p.first.to_string(),
+        Some(last) => format!("{} {last}", p.first),
+    }
+}
+
+fn completions(w: &mut Writer, name: &str, root: &str, node: &Node) {
+    let complete = |cond: String| format!("complete -c {} -n {}", sq(name), sq(&cond));
+    let at = format!("__{root}_at {}", node.id);
+    w.line("");
+
+    for opt in &node.opts {
+        let mut line = complete(at.clone());
+        if let Some(c) = opt.names.short {
+            line.push_str(&format!(" -s {}", sq(&c.to_string())));
+        }
+        for l in &opt.names.long {
+            line.push_str(&format!(" -l {}", sq(l)));
+        }
+        match &opt.value {
+            None => {}
+            Some((_, Values::Files)) => line.push_str(" -r -F"),
+            Some((_, values)) => {
+                line.push_str(" -x");
+                if let Some(a) = choices(values) {
+                    line.push_str(&format!(" {a}"));
+                }
+            }
+        }
+        if let Some(desc) = opt.desc {
+            line.push_str(&format!(" -d {}", sq(desc)));
+        }
+        w.line(&line);
+    }
+
+    for sub in &node.cmd.commands {
+        for n in &sub.names {
+            let mut line = complete(format!("__{root}_sub {}", node.id));
+            line.push_str(&format!(" -a {}", sq(n)));
+            if let Some(desc) = &sub.desc {
+                line.push_str(&format!(" -d {}", sq(desc)));
+            }
+            w.line(&line);
+        }
+    }
+
+    for p in &node.positionals {
+        let mut line = complete(format!("{at} {}", pos_range(p)));
+        match &p.values {
+            Values::Any => continue,
+            Values::Files => line.push_str(" -F"),
+            values => line.push_str(&format!(" {}", choices(values).unwrap())),
+        }
+        if let Some(desc) = p.desc {
+            line.push_str(&format!(" -d {}", sq(desc)));
+        }
+        w.line(&line);
+    }
+}
+
+pub fn generate(program: &Program, name: &str, source: &str) -> String {
+    let nodes = nodes(program, name);
+    let root = nodes[0].id.clone();
+
+    let mut w = Writer::new("    ");
+    w.line(&format!(
+        "# Generated by argolify from `{source}`; do not edit."
+    ));
+    w.line("#");
+    w.line(&format!(
+        "# fish completion for `{name}`: install it as ~/.config/fish/completions/{name}.fish."
+    ));
+    state(&mut w, &root, &nodes);
+    w.line("");
+    w.line(&format!("complete -c {} -f", sq(name)));
+    for node in &nodes {
+        completions(&mut w, name, &root, node);
+    }
+    w.finish()
+}
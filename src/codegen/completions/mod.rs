@@ -0,0 +1,160 @@
+pub mod bash;
+pub mod fish;
+pub mod zsh;
+
+use crate::spec::{ArgType, Command, Names, Options, Program, Range, Value};
+use std::path::Path;
+
+pub const SHELLS: [&str; 3] = ["bash", "fish", "zsh"];
+
+/// Integer ranges with at most this many values are completed like a list of options.
+const MAX_RANGE: i128 = 100;
+
+/// Completion script for `program` in `shell` (one of [`SHELLS`]); `source` is the spec path, for
+/// comments and as the program name when the spec has no `!program`.
+pub fn generate(shell: &str, program: &Program, source: &str) -> Option<String> {
+    let name = match &program.name {
+        Some(name) => name.clone(),
+        None => Path::new(source)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+    match shell {
+        "bash" => Some(bash::generate(program, &name, source)),
+        "fish" => Some(fish::generate(program, &name, source)),
+        "zsh" => Some(zsh::generate(program, &name, source)),
+        _ => None,
+    }
+}
+
+/// What to offer for an argument's values.
+pub enum Values {
+    Choices(Vec<String>),
+    Files,
+    Any,
+}
+
+impl Values {
+    fn new(ty: ArgType, options: &Option<Options>) -> Self {
+        match (ty, options) {
+            (_, Some(Options::Values(values))) => {
+                Values::Choices(values.iter().map(|v| v.to_string()).collect())
+            }
+            (_, Some(Options::Range(range))) if let Some(values) = range_values(range) => {
+                Values::Choices(values)
+            }
+            (ArgType::Bool, _) => Values::Choices(vec!["true".into(), "false".into()]),
+            (ArgType::Path, _) => Values::Files,
+            _ => Values::Any,
+        }
+    }
+}
+
+fn range_values(r: &Range) -> Option<Vec<String>> {
+    let (Some(Value::Int(start)), Some(Value::Int(end))) = (&r.start, &r.end) else {
+        return None;
+    };
+    // Widened so that spans of ranges near the ends of `i64` cannot overflow
+    let (start, end) = (i128::from(*start), i128::from(*end));
+    let end = if r.inclusive { end } else { end - 1 };
+    if end - start >= MAX_RANGE {
+        return None;
+    }
+    Some((start..=end).map(|i| i.to_string()).collect())
+}
+
+/// A flag (`value` is `None`) or a named argument, with the most values it takes after its name.
+pub struct Opt<'a> {
+    pub names: &'a Names,
+    pub desc: &'a Option<String>,
+    pub value: Option<(Option<usize>, Values)>,
+}
+
+impl Opt<'_> {
+    /// `-s` and `--long` forms, short first.
+    pub fn switches(&self) -> Vec<String> {
+        let short = self.names.short.map(|c| format!("-{c}"));
+        let long = self.names.long.iter().map(|l| format!("--{l}"));
+        short.into_iter().chain(long).collect()
+    }
+}
+
+/// A positional argument and the range of positional words it takes, counted from 0 within its
+/// command (`last` is `None` when unbounded).
+pub struct Pos<'a> {
+    pub name: &'a str,
+    pub desc: &'a Option<String>,
+    pub values: Values,
+    pub first: usize,
+    pub last: Option<usize>,
+}
+
+/// A command in the spec, with `id` naming it in generated functions (`prog__sub__subsub`).
+pub struct Node<'a> {
+    pub id: String,
+    pub cmd: &'a Command,
+    pub opts: Vec<Opt<'a>>,
+    pub positionals: Vec<Pos<'a>>,
+    /// Ids of the subcommands, in the same order as `cmd.commands`.
+    pub subs: Vec<String>,
+}
+
+pub fn ident(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Every command in `program`, outermost first; `name` is the program name.
+pub fn nodes<'a>(program: &'a Program, name: &str) -> Vec<Node<'a>> {
+    let mut out = Vec::new();
+    walk(&program.cmd, ident(name), &mut out);
+    out
+}
+
+fn walk<'a>(cmd: &'a Command, id: String, out: &mut Vec<Node<'a>>) {
+    let flags = cmd.flags.iter().map(|f| Opt {
+        names: &f.names,
+        desc: &f.desc,
+        value: None,
+    });
+    let named = cmd.named.iter().map(|n| Opt {
+        names: &n.names,
+        desc: &n.desc,
+        value: Some((n.nargs.max, Values::new(n.ty, &n.options))),
+    });
+
+    let mut positionals = Vec::new();
+    let mut first = Some(0);
+    for p in &cmd.positionals {
+        // Positionals after an unbounded one never get a word of their own
+        let Some(start) = first else { break };
+        let last = p.nargs.max.map(|max| start + max.max(1) - 1);
+        positionals.push(Pos {
+            name: &p.name,
+            desc: &p.desc,
+            values: Values::new(p.ty, &p.options),
+            first: start,
+            last,
+        });
+        first = last.map(|last| last + 1);
+    }
+
+    let subs: Vec<String> = cmd
+        .commands
+        .iter()
+        .map(|c| format!("{id}__{}", ident(&c.names[0])))
+        .collect();
+    out.push(Node {
+        id,
+        cmd,
+        opts: flags.chain(named).collect(),
+        positionals,
+        subs: subs.clone(),
+    });
+    for (sub, id) in cmd.commands.iter().zip(subs) {
+        walk(sub, id, out);
+    }
+}
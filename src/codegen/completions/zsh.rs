@@ -0,0 +1,221 @@
+use super::{Node, Opt, Pos, Values, nodes};
+use crate::codegen::Writer;
+use crate::spec::Program;
+
+const FOOT: &str = r#"
+if [[ $zsh_eval_context[-1] == loadautofunc ]]; then
+    # Autoloaded from $fpath
+    _ROOT "$@"
+else
+    # Sourced
+    compdef _ROOT NAME
+fi
+"#;
+
+// Single-quoted shell word
+fn sq(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn escape(s: &str, special: &[char]) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if c == '\\' || special.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// `_arguments` action completing `values`
+fn action(values: &Values) -> String {
+    match values {
+        Values::Choices(choices) => {
+            let choices: Vec<String> = choices
+                .iter()
+                .map(|c| escape(c, &[' ', '(', ')', ':', '\'', '"']))
+                .collect();
+            format!("({})", choices.join(" "))
+        }
+        Values::Files => "_files".into(),
+        Values::Any => String::new(),
+    }
+}
+
+fn opt_spec(opt: &Opt) -> String {
+    let multiple = matches!(opt.value, Some((max, _)) if max != Some(1));
+    let mut switches = opt.switches();
+    let prefix = if multiple {
+        "*".to_string()
+    } else if switches.len() > 1 {
+        format!("({})", switches.join(" "))
+    } else {
+        String::new()
+    };
+
+    let mut rest = String::new();
+    if let Some(desc) = opt.desc {
+        rest.push_str(&format!("[{}]", escape(desc, &['[', ']'])));
+    }
+    if let Some((_, values)) = &opt.value {
+        // `-x+` takes the value in the same word or the next, `--xx=` after `=` or in the next word
+        for s in &mut switches {
+            s.push(if s.starts_with("--") { '=' } else { '+' });
+        }
+        let message = escape(&opt.names.primary(), &[':']);
+        rest.push_str(&format!(":{message}:{}", action(values)));
+    }
+
+    match switches.as_slice() {
+        [switch] => sq(&format!("{prefix}{switch}{rest}")),
+        _ => {
+            let head = if prefix.is_empty() {
+                String::new()
+            } else {
+                sq(&prefix)
+            };
+            let tail = if rest.is_empty() {
+                String::new()
+            } else {
+                sq(&rest)
+            };
+            format!("{head}{{{}}}{tail}", switches.join(","))
+        }
+    }
+}
+
+fn pos_specs(positionals: &[Pos]) -> Vec<String> {
+    let mut specs = Vec::new();
+    for p in positionals {
+        let message = escape(p.desc.as_deref().unwrap_or(p.name), &[':']);
+        let action = action(&p.values);
+        match p.last {
+            None => specs.push(sq(&format!("*:{message}:{action}"))),
+            Some(last) => {
+                for _ in p.first..=last {
+                    specs.push(sq(&format!(":{message}:{action}")));
+                }
+            }
+        }
+    }
+    specs
+}
+
+fn arguments(w: &mut Writer, head: &str, specs: &[String]) {
+    w.line(&format!("{head} \\"));
+    w.indent();
+    for (i, spec) in specs.iter().enumerate() {
+        if i + 1 == specs.len() {
+            w.line(spec);
+        } else {
+            w.line(&format!("{spec} \\"));
+        }
+    }
+    w.dedent();
+}
+
+fn function(w: &mut Writer, node: &Node) {
+    let opts: Vec<String> = node.opts.iter().map(opt_spec).collect();
+    let positionals = pos_specs(&node.positionals);
+
+    w.line("");
+    w.open(&format!("_{}() {{", node.id));
+    if node.subs.is_empty() {
+        let specs = [opts, positionals].concat();
+        if specs.is_empty() {
+            w.line("_message 'no arguments'");
+        } else {
+            arguments(w, "_arguments -s -S", &specs);
+        }
+        w.close("}");
+        return;
+    }
+
+    w.line("local curcontext=$curcontext state line");
+    w.line("typeset -A opt_args");
+    w.line("");
+    let specs = [
+        opts.clone(),
+        vec!["': :->command'".into(), "'*:: :->args'".into()],
+    ]
+    .concat();
+    arguments(w, "_arguments -s -S -C", &specs);
+    w.line("");
+    w.open("case $state in");
+
+    w.open("command)");
+    w.open("local -a commands=(");
+    for sub in &node.cmd.commands {
+        for name in &sub.names {
+            let name = escape(name, &[':']);
+            match &sub.desc {
+                Some(desc) => w.line(&sq(&format!("{name}:{desc}"))),
+                None => w.line(&sq(&name)),
+            }
+        }
+    }
+    w.close(")");
+    w.line("_describe -t commands command commands");
+    match node.positionals.first().map(|p| &p.values) {
+        Some(Values::Choices(choices)) => {
+            let choices: Vec<String> = choices.iter().map(|c| sq(c)).collect();
+            w.line(&format!("compadd -- {}", choices.join(" ")));
+        }
+        Some(Values::Files) => w.line("_files"),
+        _ => {}
+    }
+    w.line(";;");
+    w.dedent();
+
+    w.open("args)");
+    w.open("case $line[1] in");
+    for (sub, id) in node.cmd.commands.iter().zip(&node.subs) {
+        w.line(&format!("{}) _{id} ;;", sub.names.join(" | ")));
+    }
+    // Otherwise the first word was a positional: complete the rest at this level
+    if !positionals.is_empty() {
+        let mut rest = positionals;
+        if !rest[0].starts_with("'*") {
+            rest.remove(0);
+        }
+        let specs = [opts, rest].concat();
+        w.open("*)");
+        arguments(w, "_arguments -s -S", &specs);
+        w.line(";;");
+        w.dedent();
+    }
+    w.close("esac");
+    w.line(";;");
+    w.dedent();
+
+    w.close("esac");
+    w.close("}");
+}
+
+pub fn generate(program: &Program, name: &str, source: &str) -> String {
+    let nodes = nodes(program, name);
+    let root = &nodes[0].id;
+
+    let mut w = Writer::new("    ");
+    w.line(&format!("#compdef {name}"));
+    w.line("");
+    w.line(&format!(
+        "# Generated by argolify from `{source}`; do not edit."
+    ));
+    w.line("#");
+    w.line(&format!(
+        "# zsh completion for `{name}`: install it as `_{name}` in a directory on $fpath, or"
+    ));
+    w.line("# source it after compinit.");
+    for node in &nodes {
+        function(&mut w, node);
+    }
+    let mut out = w.finish();
+    out.push_str(
+        &FOOT
+            .replace("_ROOT", &format!("_{root}"))
+            .replace("NAME", &sq(name)),
+    );
+    out
+}
@@ -0,0 +1,877 @@
+use crate::codegen::{Writer, clashes, command_origin, pascal, str_lit};
+use crate::help;
+use crate::spec::{Action, ArgType, Command, Nargs, Options, Program, Value};
+
+const MATCHER: &str = r#"
+// Error is a usage error. Kind and Subject are those of the error object in argolify's JSON
+// output.
+type Error struct {
+	Kind    string
+	Subject string
+	Command []string
+	Message string
+}
+
+func (e *Error) Error() string {
+	if len(e.Command) == 0 {
+		return e.Message
+	}
+	return e.Message + " (in " + strings.Join(e.Command, " ") + ")"
+}
+
+// option is a flag or named argument: {key, short, long names, flag, action, min, max}, where a max
+// of -1 means no limit.
+type option struct {
+	key    string
+	short  rune
+	long   []string
+	flag   bool
+	action string
+	min    int
+	max    int
+}
+
+// positional is {key, min, max}.
+type positional struct {
+	key string
+	min int
+	max int
+}
+
+// command is {options, positionals, subcommands by every name, help text, version text}.
+type command struct {
+	options     []option
+	positionals []positional
+	commands    map[string]string
+	help        string
+	version     string
+}
+
+func (c *command) long(name string) *option {
+	for i := range c.options {
+		for _, l := range c.options[i].long {
+			if l == name {
+				return &c.options[i]
+			}
+		}
+	}
+	return nil
+}
+
+func (c *command) short(r rune) *option {
+	for i := range c.options {
+		if c.options[i].short == r {
+			return &c.options[i]
+		}
+	}
+	return nil
+}
+
+// A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short name claims it.
+func (c *command) isValue(arg string) bool {
+	r, _ := utf8.DecodeRuneInString(arg[1:])
+	if c.short(r) != nil {
+		return false
+	}
+	_, err := parseFloat(arg[1:])
+	return err == nil
+}
+
+// parseFloat reads a finite decimal the way argolify does, so no inf, nan or hex literals and
+// nothing that overflows.
+func parseFloat(s string) (float64, error) {
+	if strings.ContainsAny(s, "nNxX") {
+		return 0, strconv.ErrSyntax
+	}
+	return strconv.ParseFloat(s, 64)
+}
+
+func (c *command) isOption(arg string) bool {
+	return arg == "--" || len(arg) > 1 && arg[0] == '-' && !c.isValue(arg)
+}
+
+func expects(min, max int) string {
+	switch {
+	case min == max:
+		return strconv.Itoa(min)
+	case max < 0 && min == 0:
+		return "any number of"
+	case max < 0:
+		return "at least " + strconv.Itoa(min)
+	default:
+		return fmt.Sprintf("%d to %d", min, max)
+	}
+}
+
+// matched holds the words given to one command, before they are converted.
+type matched struct {
+	path     []string
+	flags    map[string]bool
+	values   map[string][]string
+	trailing []string
+	// unexpected is set when trailing holds words left over before `--`.
+	unexpected bool
+	action     string
+	message    string
+	sub        string
+	rest       []string
+}
+
+func (m *matched) fail(kind, subject, message string) error {
+	return &Error{Kind: kind, Subject: subject, Command: m.path, Message: message}
+}
+
+func (m *matched) missing(name string) error {
+	return m.fail("missing_required", name, "missing required argument '"+name+"'")
+}
+
+func (m *matched) invalid(name, ty, s string) error {
+	message := fmt.Sprintf("invalid value '%s' for '%s': expected %s", s, name, ty)
+	return m.fail("invalid_value", name, message)
+}
+
+func (m *matched) notAllowed(name, s string, choices ...string) error {
+	message := fmt.Sprintf(
+		"invalid value '%s' for '%s' (possible values: %s)", s, name, strings.Join(choices, ", "))
+	return m.fail("not_allowed", name, message)
+}
+
+func (m *matched) setFlag(c *command, opt *option) {
+	m.flags[opt.key] = true
+	switch opt.action {
+	case "show_help":
+		m.action, m.message = opt.action, c.help
+	case "show_version":
+		m.action, m.message = opt.action, c.version
+	}
+}
+
+// Collect the values of a named argument: given inline ones, then following words up to the
+// maximum, stopping at anything that looks like an option. Returns how many words were used.
+func (m *matched) take(c *command, opt *option, given, rest []string) (int, error) {
+	n := 0
+	for (opt.max < 0 || len(given) < opt.max) && n < len(rest) && !c.isOption(rest[n]) {
+		given = append(given, rest[n])
+		n++
+	}
+	if len(given) < opt.min {
+		name := "--" + opt.key
+		message := fmt.Sprintf("'%s' expects %s value(s)", name, expects(opt.min, opt.max))
+		return 0, m.fail("missing_value", name, message)
+	}
+	if opt.max == 1 {
+		m.values[opt.key] = given
+	} else {
+		m.values[opt.key] = append(m.values[opt.key], given...)
+	}
+	return n, nil
+}
+
+func (m *matched) short(c *command, cluster string, rest []string) (int, error) {
+	for i, r := range cluster {
+		opt := c.short(r)
+		if opt == nil {
+			name := "-" + string(r)
+			return 0, m.fail("unknown_option", name, "unknown option '"+name+"'")
+		}
+		if opt.flag {
+			m.setFlag(c, opt)
+			if m.action != "" {
+				return 0, nil
+			}
+			continue
+		}
+		var given []string
+		if value := strings.TrimPrefix(cluster[i+utf8.RuneLen(r):], "="); value != "" {
+			given = append(given, value)
+		}
+		return m.take(c, opt, given, rest)
+	}
+	return 0, nil
+}
+
+// Hand out positional words in order, leaving enough for the minimum of later positionals. Leftover
+// words are only allowed after `--`, where they are passed through as trailing; see leftover.
+func (m *matched) finish(c *command, words []string, dashes int) error {
+	total := len(words)
+	for i, p := range c.positionals {
+		reserved := 0
+		for _, later := range c.positionals[i+1:] {
+			reserved += later.min
+		}
+		n := len(words) - reserved
+		if n < 0 {
+			n = 0
+		}
+		if p.max >= 0 && n > p.max {
+			n = p.max
+		}
+		given := words[:n]
+		words = words[n:]
+		if len(given) > 0 && len(given) < p.min {
+			message := fmt.Sprintf("'%s' expects %s value(s)", p.key, expects(p.min, p.max))
+			return m.fail("missing_value", p.key, message)
+		}
+		if len(given) > 0 {
+			m.values[p.key] = given
+		}
+	}
+
+	m.trailing = words
+	m.unexpected = len(words) > 0 && (dashes < 0 || total-len(words) < dashes)
+	return nil
+}
+
+// leftover reports the first word left over before `--`, once positionals are converted.
+func (m *matched) leftover() error {
+	if !m.unexpected {
+		return nil
+	}
+	word := m.trailing[0]
+	return m.fail("unexpected_argument", word, "unexpected argument '"+word+"'")
+}
+
+func (m *matched) tooMany(key string, min, max int) error {
+	name := "--" + key
+	message := fmt.Sprintf("'%s' expects %s value(s)", name, expects(min, max))
+	return m.fail("too_many_values", name, message)
+}
+
+// Match argv against c, stopping at the first subcommand.
+func match(c *command, path, argv []string) (*matched, error) {
+	m := &matched{path: path, flags: map[string]bool{}, values: map[string][]string{}}
+	var words []string
+	dashes := -1
+	for i := 0; i < len(argv); i++ {
+		arg := argv[i]
+		switch {
+		case dashes >= 0:
+			words = append(words, arg)
+		case arg == "--":
+			dashes = len(words)
+		case strings.HasPrefix(arg, "--"):
+			name, value, inline := strings.Cut(arg[2:], "=")
+			opt := c.long(name)
+			switch {
+			case opt == nil:
+				return nil, m.fail("unknown_option", "--"+name, "unknown option '--"+name+"'")
+			case opt.flag && inline:
+				message := "flag '--" + name + "' does not take a value"
+				return nil, m.fail("flag_with_value", "--"+name, message)
+			case opt.flag:
+				m.setFlag(c, opt)
+			default:
+				var given []string
+				if inline {
+					given = append(given, value)
+				}
+				n, err := m.take(c, opt, given, argv[i+1:])
+				if err != nil {
+					return nil, err
+				}
+				i += n
+			}
+		case c.isOption(arg):
+			n, err := m.short(c, arg[1:], argv[i+1:])
+			if err != nil {
+				return nil, err
+			}
+			i += n
+		default:
+			if sub, ok := c.commands[arg]; ok && len(words) == 0 {
+				m.sub, m.rest = sub, argv[i+1:]
+				return m, m.finish(c, words, dashes)
+			}
+			words = append(words, arg)
+		}
+		if m.action != "" {
+			return m, nil
+		}
+	}
+	return m, m.finish(c, words, dashes)
+}
+"#;
+
+// Exported Go identifier; Go names cannot start with a digit
+fn exported(words: &[String]) -> String {
+    let name = pascal(words);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("Arg{name}")
+    } else {
+        name
+    }
+}
+
+/// Fields of the top-level `Args` struct that are not arguments.
+const RESERVED: [&str; 4] = ["Command", "Action", "Message", "Trailing"];
+
+// Field for an argument or subcommand; in `Args` (`top`), one that would take the name of a
+// reserved field gets an `Arg` suffix
+fn field(name: &str, top: bool) -> String {
+    let field = exported(&[name.to_string()]);
+    if top && RESERVED.contains(&field.as_str()) {
+        format!("{field}Arg")
+    } else {
+        field
+    }
+}
+
+fn struct_name(path: &[String]) -> String {
+    format!("{}Args", exported(path))
+}
+
+// Named after the command's struct, so it is unique whenever the struct names are
+fn spec_var(path: &[String]) -> String {
+    let name = struct_name(path);
+    let mut chars = name.chars();
+    let first = chars.next().unwrap_or_default().to_ascii_lowercase();
+    format!("{first}{}Spec", chars.as_str())
+}
+
+fn go_type(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str | ArgType::Path => "string",
+        ArgType::Int => "int64",
+        ArgType::UInt => "uint64",
+        ArgType::Float => "float64",
+        ArgType::Bool => "bool",
+    }
+}
+
+fn value_lit(v: &Value) -> String {
+    match v {
+        Value::Str(s) => str_lit(s),
+        Value::Int(i) => i.to_string(),
+        Value::Float(x) => format!("{x:?}"),
+        Value::Bool(b) => b.to_string(),
+    }
+}
+
+fn max_lit(n: Nargs) -> String {
+    n.max.map_or("-1".into(), |m| m.to_string())
+}
+
+// A multi-line string as `"line\n" +` continuation lines
+fn text_lit(w: &mut Writer, text: &str) {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    match lines.split_last() {
+        None => w.line("\"\","),
+        Some((last, [])) => w.line(&format!("{},", str_lit(last))),
+        Some((last, init)) => {
+            w.line(&format!("{} +", str_lit(init[0])));
+            w.indent();
+            for line in &init[1..] {
+                w.line(&format!("{} +", str_lit(line)));
+            }
+            w.line(&format!("{},", str_lit(last)));
+            w.dedent();
+        }
+    }
+}
+
+enum Member {
+    Comment(String),
+    Field(String, String),
+    Blank,
+}
+
+// Struct members with the types of consecutive fields aligned, as gofmt does
+fn members(w: &mut Writer, members: &[Member]) {
+    let mut i = 0;
+    while i < members.len() {
+        match &members[i] {
+            Member::Comment(c) => w.line(&format!("// {c}")),
+            Member::Blank => w.line(""),
+            Member::Field(..) => {
+                let run: Vec<(&String, &String)> = members[i..]
+                    .iter()
+                    .map_while(|m| match m {
+                        Member::Field(name, ty) => Some((name, ty)),
+                        _ => None,
+                    })
+                    .collect();
+                let width = run.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+                for (name, ty) in &run {
+                    w.line(&format!("{name:width$} {ty}"));
+                }
+                i += run.len();
+                continue;
+            }
+        }
+        i += 1;
+    }
+}
+
+fn leftover(w: &mut Writer) {
+    w.line("");
+    w.open("if err := m.leftover(); err != nil {");
+    w.line("return err");
+    w.close("}");
+}
+
+// An argument as the generated `parse` method converts it
+struct Arg<'a> {
+    key: String,
+    field: String,
+    ty: ArgType,
+    options: &'a Option<Options>,
+    default: &'a [Value],
+    nargs: Nargs,
+    required: bool,
+}
+
+impl Arg<'_> {
+    fn multiple(&self) -> bool {
+        self.nargs.max != Some(1)
+    }
+
+    fn field_type(&self) -> String {
+        let ty = go_type(self.ty);
+        if self.multiple() {
+            format!("[]{ty}")
+        } else if self.required || !self.default.is_empty() {
+            ty.to_string()
+        } else {
+            format!("*{ty}")
+        }
+    }
+}
+
+// Convert `s` to the value `x` (or keep `s` for strings), check it, and store it in the field
+fn convert(w: &mut Writer, arg: &Arg) {
+    let key = str_lit(&arg.key);
+    let ty = str_lit(&arg.ty.to_string());
+    let parse = match arg.ty {
+        ArgType::Str | ArgType::Path => None,
+        ArgType::Int => Some("strconv.ParseInt(s, 10, 64)"),
+        ArgType::UInt => Some("strconv.ParseUint(s, 10, 64)"),
+        ArgType::Float => Some("parseFloat(s)"),
+        ArgType::Bool => {
+            w.open("if s != \"true\" && s != \"false\" {");
+            w.line(&format!("return m.invalid({key}, {ty}, s)"));
+            w.close("}");
+            w.line("x := s == \"true\"");
+            None
+        }
+    };
+    if let Some(parse) = parse {
+        w.line(&format!("x, err := {parse}"));
+        w.open("if err != nil {");
+        w.line(&format!("return m.invalid({key}, {ty}, s)"));
+        w.close("}");
+    }
+    let numeric = matches!(arg.ty, ArgType::Int | ArgType::UInt | ArgType::Float);
+    let value = if parse.is_none() && arg.ty != ArgType::Bool {
+        "s"
+    } else {
+        "x"
+    };
+
+    match arg.options {
+        Some(Options::Values(vs)) => {
+            let subject = if numeric { "f" } else { value };
+            let tests: Vec<String> = vs
+                .iter()
+                .map(|v| format!("{subject} == {}", value_lit(v)))
+                .collect();
+            let choices: Vec<String> = vs.iter().map(|v| str_lit(&v.to_string())).collect();
+            if numeric {
+                w.open(&format!("if f := float64(x); !({}) {{", tests.join(" || ")));
+            } else {
+                w.open(&format!("if !({}) {{", tests.join(" || ")));
+            }
+            w.line(&format!(
+                "return m.notAllowed({key}, s, {})",
+                choices.join(", ")
+            ));
+            w.close("}");
+        }
+        Some(Options::Range(r)) if numeric => {
+            let mut tests = Vec::new();
+            if let Some(start) = &r.start {
+                tests.push(format!("f >= {}", value_lit(start)));
+            }
+            if let Some(end) = &r.end {
+                let op = if r.inclusive { "<=" } else { "<" };
+                tests.push(format!("f {op} {}", value_lit(end)));
+            }
+            w.open(&format!("if f := float64(x); !({}) {{", tests.join(" && ")));
+            w.line(&format!(
+                "return m.notAllowed({key}, s, {})",
+                str_lit(&r.to_string())
+            ));
+            w.close("}");
+        }
+        _ => {}
+    }
+
+    let field = &arg.field;
+    if arg.multiple() {
+        w.line(&format!("a.{field} = append(a.{field}, {value})"));
+    } else if arg.required || !arg.default.is_empty() {
+        w.line(&format!("a.{field} = {value}"));
+    } else {
+        w.line(&format!("a.{field} = &{value}"));
+    }
+}
+
+struct Generator<'a> {
+    program: &'a Program,
+    out: Vec<String>,
+    // Struct names, which are declared at package level, with the commands they are for
+    structs: Vec<(String, String)>,
+    clashes: Vec<String>,
+}
+
+impl Generator<'_> {
+    fn spec(&mut self, cmd: &Command, path: &[String]) {
+        let mut w = Writer::new("\t");
+        w.open(&format!("var {} = command{{", spec_var(path)));
+
+        let mut options = Vec::new();
+        for f in &cmd.flags {
+            let long: Vec<String> = f.names.long.iter().map(|l| str_lit(l)).collect();
+            options.push(format!(
+                "{{{}, {}, []string{{{}}}, true, {}, 0, 0}},",
+                str_lit(&f.names.primary()),
+                short_lit(f.names.short),
+                long.join(", "),
+                str_lit(f.action.map_or("", |a| a.as_str()))
+            ));
+        }
+        for n in &cmd.named {
+            let long: Vec<String> = n.names.long.iter().map(|l| str_lit(l)).collect();
+            options.push(format!(
+                "{{{}, {}, []string{{{}}}, false, \"\", {}, {}}},",
+                str_lit(&n.names.primary()),
+                short_lit(n.names.short),
+                long.join(", "),
+                n.nargs.min,
+                max_lit(n.nargs)
+            ));
+        }
+        list(&mut w, "[]option", &options);
+
+        let positionals: Vec<String> = cmd
+            .positionals
+            .iter()
+            .map(|p| {
+                format!(
+                    "{{{}, {}, {}}},",
+                    str_lit(&p.name),
+                    p.nargs.min,
+                    max_lit(p.nargs)
+                )
+            })
+            .collect();
+        list(&mut w, "[]positional", &positionals);
+
+        // Values of consecutive entries are aligned, as gofmt does
+        let keys: Vec<(String, String)> = cmd
+            .commands
+            .iter()
+            .flat_map(|sub| {
+                sub.names
+                    .iter()
+                    .map(|name| (format!("{}:", str_lit(name)), str_lit(&sub.names[0])))
+            })
+            .collect();
+        let width = keys.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        let commands: Vec<String> = keys
+            .iter()
+            .map(|(k, v)| format!("{k:width$} {v},"))
+            .collect();
+        list(&mut w, "map[string]string", &commands);
+
+        let has = |action| cmd.flags.iter().any(|f| f.action == Some(action));
+        if has(Action::ShowHelp) {
+            text_lit(&mut w, &help::render(self.program, path, 80));
+        } else {
+            w.line("\"\",");
+        }
+        if has(Action::ShowVersion) {
+            let version = self.program.version.clone().unwrap_or_default();
+            let text = match &self.program.name {
+                Some(name) => format!("{name} {version}\n"),
+                None => format!("{version}\n"),
+            };
+            w.line(&format!("{},", str_lit(&text)));
+        } else {
+            w.line("\"\",");
+        }
+        w.close("}");
+        self.out.push(w.finish());
+    }
+
+    fn command(&mut self, cmd: &Command, path: &[String]) {
+        let name = struct_name(path);
+        self.structs.push((name.clone(), command_origin(path)));
+        let field = |name: &str| field(name, path.is_empty());
+        let args: Vec<Arg> = cmd
+            .positionals
+            .iter()
+            .map(|p| Arg {
+                key: p.name.clone(),
+                field: field(&p.name),
+                ty: p.ty,
+                options: &p.options,
+                default: &p.default,
+                nargs: p.nargs,
+                required: p.required,
+            })
+            .chain(cmd.named.iter().map(|n| Arg {
+                key: n.names.primary(),
+                field: field(&n.names.primary()),
+                ty: n.ty,
+                options: &n.options,
+                default: &n.default,
+                nargs: n.nargs,
+                required: n.required,
+            }))
+            .collect();
+        let descs: Vec<&Option<String>> = cmd
+            .positionals
+            .iter()
+            .map(|p| &p.desc)
+            .chain(cmd.named.iter().map(|n| &n.desc))
+            .collect();
+
+        // The struct
+        let mut body = Vec::new();
+        if path.is_empty() {
+            body.extend([
+                Member::Comment(
+                    "Command is the path of subcommands given, by their first names.".into(),
+                ),
+                Member::Field("Command".into(), "[]string".into()),
+                Member::Comment(
+                    "Action is \"show_help\" or \"show_version\" when a flag with that action was \
+                     given. Matching"
+                        .into(),
+                ),
+                Member::Comment("stops at the flag, and Message holds the text to print.".into()),
+                Member::Field("Action".into(), "string".into()),
+                Member::Field("Message".into(), "string".into()),
+                Member::Comment(
+                    "Trailing holds the words after `--` that no positional took.".into(),
+                ),
+                Member::Field("Trailing".into(), "[]string".into()),
+            ]);
+        }
+        let mut fields = Vec::new();
+        for f in cmd.flags.iter().filter(|f| f.action.is_none()) {
+            if let Some(desc) = &f.desc {
+                fields.push(Member::Comment(desc.clone()));
+            }
+            fields.push(Member::Field(field(&f.names.primary()), "bool".into()));
+        }
+        for (arg, desc) in args.iter().zip(&descs) {
+            if let Some(desc) = desc {
+                fields.push(Member::Comment(desc.to_string()));
+            }
+            fields.push(Member::Field(arg.field.clone(), arg.field_type()));
+        }
+        for sub in &cmd.commands {
+            let mut sub_path = path.to_vec();
+            sub_path.push(sub.names[0].clone());
+            fields.push(Member::Comment(format!(
+                "{} is set when the `{}` command is given.",
+                field(&sub.names[0]),
+                sub.names[0]
+            )));
+            fields.push(Member::Field(
+                field(&sub.names[0]),
+                format!("*{}", struct_name(&sub_path)),
+            ));
+        }
+        if !body.is_empty() && !fields.is_empty() {
+            body.push(Member::Blank);
+        }
+        body.extend(fields);
+
+        let idents: Vec<(String, String)> = cmd
+            .flags
+            .iter()
+            .filter(|f| f.action.is_none())
+            .map(|f| f.names.primary())
+            .chain(args.iter().map(|a| a.key.clone()))
+            .map(|key| (field(&key), format!("argument `{key}`")))
+            .chain(
+                cmd.commands
+                    .iter()
+                    .map(|sub| (field(&sub.names[0]), format!("command `{}`", sub.names[0]))),
+            )
+            .collect();
+        self.clashes.extend(clashes(&format!("`{name}`"), &idents));
+
+        let mut w = Writer::new("\t");
+        let program = self.program.name.as_deref().unwrap_or("the program");
+        if path.is_empty() {
+            w.line(&format!(
+                "// Args holds the arguments matched for {program}."
+            ));
+        } else {
+            w.line(&format!(
+                "// {name} holds the arguments of `{program} {}`.",
+                path.join(" ")
+            ));
+        }
+        w.open(&format!("type {name} struct {{"));
+        members(&mut w, &body);
+        w.close("}");
+        self.out.push(w.finish());
+
+        self.spec(cmd, path);
+
+        // The parse method
+        let mut w = Writer::new("\t");
+        w.open(&format!(
+            "func (a *{name}) parse(root *Args, argv []string) error {{"
+        ));
+        w.line(&format!(
+            "m, err := match(&{}, root.Command, argv)",
+            spec_var(path)
+        ));
+        w.open("if err != nil {");
+        w.line("return err");
+        w.close("}");
+        w.open("if m.action != \"\" {");
+        w.line("root.Action, root.Message = m.action, m.message");
+        w.line("return nil");
+        w.close("}");
+        w.line("root.Trailing = append(root.Trailing, m.trailing...)");
+        for f in cmd.flags.iter().filter(|f| f.action.is_none()) {
+            let name = f.names.primary();
+            w.line(&format!("a.{} = m.flags[{}]", field(&name), str_lit(&name)));
+        }
+
+        for (i, arg) in args.iter().enumerate() {
+            if i == cmd.positionals.len() {
+                leftover(&mut w);
+            }
+            let key = str_lit(&arg.key);
+            w.line("");
+            if let Some(max) = arg.nargs.max
+                && i >= cmd.positionals.len()
+                && max != 1
+            {
+                w.open(&format!("if len(m.values[{key}]) > {max} {{"));
+                w.line(&format!(
+                    "return m.tooMany({key}, {}, {max})",
+                    arg.nargs.min
+                ));
+                w.close("}");
+            }
+            if arg.required {
+                w.open(&format!("if len(m.values[{key}]) == 0 {{"));
+                w.line(&format!("return m.missing({key})"));
+                w.close("}");
+            }
+            let defaults: Vec<String> = arg.default.iter().map(value_lit).collect();
+            match defaults.as_slice() {
+                [] => {}
+                [v] if !arg.multiple() => w.line(&format!("a.{} = {v}", arg.field)),
+                vs => {
+                    w.open(&format!("if len(m.values[{key}]) == 0 {{"));
+                    w.line(&format!(
+                        "a.{} = []{}{{{}}}",
+                        arg.field,
+                        go_type(arg.ty),
+                        vs.join(", ")
+                    ));
+                    w.close("}");
+                }
+            }
+            w.open(&format!("for _, s := range m.values[{key}] {{"));
+            convert(&mut w, arg);
+            w.close("}");
+        }
+        if cmd.named.is_empty() {
+            leftover(&mut w);
+        }
+
+        if !cmd.commands.is_empty() {
+            w.line("");
+            w.line("switch m.sub {");
+            for sub in &cmd.commands {
+                let mut sub_path = path.to_vec();
+                sub_path.push(sub.names[0].clone());
+                let sub_field = field(&sub.names[0]);
+                w.line(&format!("case {}:", str_lit(&sub.names[0])));
+                w.indent();
+                w.line(&format!(
+                    "root.Command = append(root.Command, {})",
+                    str_lit(&sub.names[0])
+                ));
+                w.line(&format!("a.{sub_field} = &{}{{}}", struct_name(&sub_path)));
+                w.line(&format!("return a.{sub_field}.parse(root, m.rest)"));
+                w.dedent();
+            }
+            w.line("}");
+        }
+        w.line("return nil");
+        w.close("}");
+        self.out.push(w.finish());
+
+        for sub in &cmd.commands {
+            let mut sub_path = path.to_vec();
+            sub_path.push(sub.names[0].clone());
+            self.command(sub, &sub_path);
+        }
+    }
+}
+
+// `ty{...},` with one item per line, or `ty{},` when there are none
+fn list(w: &mut Writer, ty: &str, items: &[String]) {
+    if items.is_empty() {
+        w.line(&format!("{ty}{{}},"));
+        return;
+    }
+    w.open(&format!("{ty}{{"));
+    for item in items {
+        w.line(item);
+    }
+    w.close("},");
+}
+
+fn short_lit(short: Option<char>) -> String {
+    match short {
+        Some('\'') => "'\\''".into(),
+        Some('\\') => "'\\\\'".into(),
+        Some(c) => format!("'{c}'"),
+        None => "0".into(),
+    }
+}
+
+/// A Go file in package `cli` with an `Args` struct per command and `Parse(argv)`.
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let mut generator = Generator {
+        program,
+        out: Vec::new(),
+        structs: Vec::new(),
+        clashes: Vec::new(),
+    };
+    generator.command(&program.cmd, &[]);
+    generator
+        .clashes
+        .extend(clashes("the package", &generator.structs));
+    if !generator.clashes.is_empty() {
+        return Err(generator.clashes);
+    }
+
+    let mut out = format!("// Code generated by argolify from `{source}`; DO NOT EDIT.\n\n");
+    out.push_str("package cli\n\n");
+    out.push_str("import (\n\t\"fmt\"\n\t\"strconv\"\n\t\"strings\"\n\t\"unicode/utf8\"\n)\n\n");
+    out.push_str("// Parse matches argv (without the program name) against the spec.\n");
+    out.push_str("func Parse(argv []string) (*Args, error) {\n");
+    out.push_str("\targs := &Args{}\n");
+    out.push_str("\tif err := args.parse(args, argv); err != nil {\n");
+    out.push_str("\t\treturn nil, err\n\t}\n");
+    out.push_str("\treturn args, nil\n}\n");
+    for item in &generator.out {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out.push_str(MATCHER);
+    Ok(out)
+}
@@ -0,0 +1,135 @@
+pub mod bindings;
+pub mod c;
+pub mod completions;
+pub mod go;
+pub mod python;
+pub mod rust;
+pub mod sh;
+pub mod standalone;
+pub mod ts;
+
+use crate::common::json::Json;
+use crate::spec::Program;
+use std::collections::HashMap;
+
+pub const LANGS: [&str; 6] = ["c", "go", "python", "rust", "sh", "ts"];
+
+/// Source for `program` in `lang` (one of [`LANGS`]); `source` is the spec path, for comments.
+/// Fails with one message per pair of names that would become the same identifier.
+pub fn generate(
+    lang: &str,
+    program: &Program,
+    source: &str,
+) -> Option<Result<String, Vec<String>>> {
+    match lang {
+        "c" => Some(c::generate(program, source)),
+        "go" => Some(go::generate(program, source)),
+        "python" => Some(python::generate(program, source)),
+        "rust" => Some(rust::generate(program, source)),
+        "sh" => Some(sh::generate(program, source)),
+        "ts" => Some(ts::generate(program, source)),
+        _ => None,
+    }
+}
+
+/// Report the identifiers in `idents` that more than one spec name maps to, as
+/// `(identifier, what it was made from)` pairs; `scope` says where they are declared.
+pub fn clashes(scope: &str, idents: &[(String, String)]) -> Vec<String> {
+    let mut first: HashMap<&str, &str> = HashMap::new();
+    let mut out = Vec::new();
+    for (ident, origin) in idents {
+        match first.get(ident.as_str()) {
+            Some(prev) => out.push(format!(
+                "{prev} and {origin} both become `{ident}` in {scope}"
+            )),
+            None => {
+                first.insert(ident, origin);
+            }
+        }
+    }
+    out
+}
+
+/// How clash reports name the command at `path`.
+pub fn command_origin(path: &[String]) -> String {
+    if path.is_empty() {
+        "the top level".into()
+    } else {
+        format!("command `{}`", path.join(" "))
+    }
+}
+
+/// PascalCase name made from the alphanumeric runs of `words`, as types are named in the generated
+/// code.
+pub fn pascal(words: &[String]) -> String {
+    words
+        .iter()
+        .flat_map(|w| w.split(|c: char| !c.is_ascii_alphanumeric()))
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Double-quoted string literal with JSON escapes, which Go, Python and TypeScript all accept.
+pub fn str_lit(s: &str) -> String {
+    Json::str(s).to_string()
+}
+
+/// Line-based source writer that tracks the indentation level.
+pub struct Writer {
+    out: String,
+    depth: usize,
+    indent: &'static str,
+}
+
+impl Writer {
+    pub fn new(indent: &'static str) -> Self {
+        Writer {
+            out: String::new(),
+            depth: 0,
+            indent,
+        }
+    }
+
+    pub fn line(&mut self, s: &str) {
+        if !s.is_empty() {
+            self.out.push_str(&self.indent.repeat(self.depth));
+            self.out.push_str(s);
+        }
+        self.out.push('\n');
+    }
+
+    /// Write `s` and indent the lines that follow.
+    pub fn open(&mut self, s: &str) {
+        self.line(s);
+        self.depth += 1;
+    }
+
+    /// Dedent, then write `s`.
+    pub fn close(&mut self, s: &str) {
+        self.depth -= 1;
+        self.line(s);
+    }
+
+    pub fn indent(&mut self) {
+        self.depth += 1;
+    }
+
+    pub fn dedent(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Width of the current indentation.
+    pub fn indent_width(&self) -> usize {
+        self.indent.len() * self.depth
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
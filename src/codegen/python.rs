@@ -0,0 +1,630 @@
+use crate::codegen::{Writer, clashes, command_origin, str_lit};
+use crate::spec::{Action, ArgType, Command, Nargs, Options, Program, Range, Value};
+
+const LINE_WIDTH: usize = 88;
+
+const UINT_HELPER: &str = r#"
+def _uint(s):
+    try:
+        value = int(s)
+    except ValueError:
+        value = -1
+    if value < 0:
+        raise argparse.ArgumentTypeError(f"invalid uint value: {s!r}")
+    return value
+"#;
+
+const FLOAT_HELPER: &str = r#"
+def _float(s):
+    try:
+        value = float(s)
+    except ValueError:
+        value = math.nan
+    if not math.isfinite(value):
+        raise argparse.ArgumentTypeError(f"invalid float value: {s!r}")
+    return value
+"#;
+
+const BOOL_HELPER: &str = r#"
+def _bool(s):
+    if s not in ("true", "false"):
+        raise argparse.ArgumentTypeError(f"invalid bool value: {s!r}")
+    return s == "true"
+"#;
+
+const RANGE_HELPER: &str = r#"
+def _in_range(convert, start, end, inclusive, text):
+    def check(s):
+        value = convert(s)
+        if (start is not None and value < start) or (
+            end is not None and (value > end if inclusive else value >= end)
+        ):
+            raise argparse.ArgumentTypeError(f"{s} is not in range {text}")
+        return value
+
+    return check
+"#;
+
+const SELECT: &str = r#"
+def _is_option(word, shorts):
+    # A dash-prefixed finite number is a value unless a short name claims its first digit
+    if word == "--":
+        return True
+    if len(word) < 2 or word[0] != "-":
+        return False
+    if word[1] in shorts:
+        return True
+    try:
+        return not math.isfinite(float(word[1:]))
+    except ValueError:
+        return True
+
+
+def _select(argv):
+    """The path of the command `argv` selects, walking it the way the argolify runtime does."""
+    path = ""
+    i = 0
+    while i < len(argv):
+        takes, shorts, commands = _COMMANDS[path]
+        word = argv[i]
+        i += 1
+        left = 0
+        if word == "--":
+            break
+        if word.startswith("--"):
+            name, eq, _ = word.partition("=")
+            if name in takes:
+                left = takes[name] - len(eq)
+        elif _is_option(word, shorts):
+            for j, c in enumerate(word[1:], 2):
+                if "-" + c in takes:
+                    left = takes["-" + c] - (j < len(word))
+                    break
+        elif word in commands:
+            path = commands[word]
+            continue
+        else:
+            break
+        while left != 0 and i < len(argv) and not _is_option(argv[i], shorts):
+            left -= 1
+            i += 1
+    return path
+"#;
+
+const TRAILING_NOTE: &str = "\
+#
+# Unlike the argolify runtime, argparse does not keep the words after `--` that no positional
+# argument takes: it exits with a usage error instead.
+";
+
+// argparse formats help strings with `%`
+fn help_lit(s: &str) -> String {
+    str_lit(&s.replace('%', "%%"))
+}
+
+fn value_lit(v: &Value) -> String {
+    match v {
+        Value::Str(s) => str_lit(s),
+        Value::Int(i) => i.to_string(),
+        Value::Float(x) => format!("{x:?}"),
+        Value::Bool(true) => "True".into(),
+        Value::Bool(false) => "False".into(),
+    }
+}
+
+fn opt_value_lit(v: &Option<Value>) -> String {
+    v.as_ref().map_or("None".into(), value_lit)
+}
+
+fn ident(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// argparse `nargs`, and the count bounds argparse cannot express and `parse_args` checks instead.
+fn nargs(n: Nargs, required: bool) -> (Option<String>, Option<(usize, Option<usize>)>) {
+    let min = if required { n.min } else { 0 };
+    match (min, n.max) {
+        (1, Some(1)) => (None, None),
+        (0, Some(1)) => (Some("\"?\"".into()), None),
+        (min, Some(max)) if min == max => (Some(max.to_string()), None),
+        (0, None) if n.min <= 1 => (Some("\"*\"".into()), None),
+        (1, None) => (Some("\"+\"".into()), None),
+        (0, _) => (Some("\"*\"".into()), Some((n.min, n.max))),
+        _ => (Some("\"+\"".into()), Some((n.min, n.max))),
+    }
+}
+
+// What `_select` needs to walk the words given to a command
+struct Level {
+    path: String,
+    // The most values each option switch takes, `-1` for no limit
+    takes: Vec<(String, String)>,
+    shorts: String,
+    // Subcommand names and aliases with the path they select
+    commands: Vec<(String, String)>,
+}
+
+struct Generator {
+    uint: bool,
+    float: bool,
+    bool: bool,
+    range: bool,
+    pathlib: bool,
+    counts: Vec<(String, usize, Option<usize>)>,
+    version: String,
+    levels: Vec<Level>,
+    // Whether a command has both positional arguments and subcommands
+    mixed: bool,
+    // Each subparser variable with the command it is for
+    parsers: Vec<(String, String)>,
+    clashes: Vec<String>,
+}
+
+impl Generator {
+    fn convert(&mut self, ty: ArgType) -> Option<&'static str> {
+        match ty {
+            ArgType::Str => None,
+            ArgType::Int => Some("int"),
+            ArgType::UInt => {
+                self.uint = true;
+                Some("_uint")
+            }
+            ArgType::Float => {
+                self.float = true;
+                Some("_float")
+            }
+            ArgType::Bool => {
+                self.bool = true;
+                Some("_bool")
+            }
+            ArgType::Path => {
+                self.pathlib = true;
+                Some("pathlib.Path")
+            }
+        }
+    }
+
+    fn range_check(&mut self, ty: ArgType, r: &Range) -> String {
+        self.range = true;
+        let convert = self.convert(ty).unwrap_or("str");
+        format!(
+            "_in_range({convert}, {}, {}, {}, {})",
+            opt_value_lit(&r.start),
+            opt_value_lit(&r.end),
+            if r.inclusive { "True" } else { "False" },
+            str_lit(&r.to_string())
+        )
+    }
+
+    fn type_kwargs(&mut self, args: &mut Vec<String>, ty: ArgType, options: &Option<Options>) {
+        match options {
+            Some(Options::Range(r)) => {
+                let check = self.range_check(ty, r);
+                args.push(format!("type={check}"));
+            }
+            Some(Options::Values(vs)) => {
+                if let Some(convert) = self.convert(ty) {
+                    args.push(format!("type={convert}"));
+                }
+                let choices: Vec<String> = vs.iter().map(value_lit).collect();
+                args.push(format!("choices=[{}]", choices.join(", ")));
+            }
+            None => {
+                if let Some(convert) = self.convert(ty) {
+                    args.push(format!("type={convert}"));
+                }
+            }
+        }
+    }
+
+    fn nargs_kwarg(&mut self, args: &mut Vec<String>, dest: &str, n: Nargs, required: bool) {
+        let (nargs, counts) = nargs(n, required);
+        if let Some(nargs) = &nargs {
+            args.push(format!("nargs={nargs}"));
+        }
+        if let Some((min, max)) = counts {
+            self.counts.push((dest.to_string(), min, max));
+        }
+    }
+
+    fn positionals(&mut self, w: &mut Writer, cmd: &Command, var: &str) {
+        for p in &cmd.positionals {
+            let dest = ident(&p.name);
+            let mut args = vec![str_lit(&dest)];
+            if dest != p.name {
+                args.push(format!("metavar={}", str_lit(&p.name)));
+            }
+            self.type_kwargs(&mut args, p.ty, &p.options);
+            self.nargs_kwarg(&mut args, &dest, p.nargs, p.required);
+            default_kwarg(&mut args, &p.default, p.nargs);
+            if let Some(desc) = &p.desc {
+                args.push(format!("help={}", help_lit(desc)));
+            }
+            call(w, &format!("{var}.add_argument"), &args);
+        }
+    }
+
+    fn level(&mut self, cmd: &Command, path: &[String]) {
+        let takes = cmd
+            .named
+            .iter()
+            .flat_map(|n| {
+                let max = n.nargs.max.map_or("-1".into(), |m| m.to_string());
+                n.names
+                    .short
+                    .map(|c| format!("-{c}"))
+                    .into_iter()
+                    .chain(n.names.long.iter().map(|l| format!("--{l}")))
+                    .map(move |s| (s, max.clone()))
+            })
+            .collect();
+        let shorts = cmd
+            .flags
+            .iter()
+            .map(|f| f.names.short)
+            .chain(cmd.named.iter().map(|n| n.names.short))
+            .flatten()
+            .collect();
+        let commands = cmd
+            .commands
+            .iter()
+            .flat_map(|sub| {
+                let mut sub_path = path.to_vec();
+                sub_path.push(sub.names[0].clone());
+                let sub_path = sub_path.join(" ");
+                sub.names.iter().map(move |n| (n.clone(), sub_path.clone()))
+            })
+            .collect();
+        self.levels.push(Level {
+            path: path.join(" "),
+            takes,
+            shorts,
+            commands,
+        });
+    }
+
+    fn command(&mut self, w: &mut Writer, cmd: &Command, var: &str, path: &[String]) {
+        self.level(cmd, path);
+        let prefix: String = path.iter().map(|p| format!("{}_", ident(p))).collect();
+
+        // argparse stores the arguments of a command in one namespace, by dest
+        let mut dests: Vec<(String, String)> = cmd
+            .flags
+            .iter()
+            .filter(|f| f.action.is_none())
+            .map(|f| f.names.primary())
+            .chain(cmd.positionals.iter().map(|p| p.name.clone()))
+            .chain(cmd.named.iter().map(|n| n.names.primary()))
+            .map(|name| (ident(&name), format!("argument `{name}`")))
+            .collect();
+        if !cmd.commands.is_empty() {
+            dests.push((format!("{prefix}command"), "the subcommand name".into()));
+        }
+        let scope = format!("the namespace of {}", command_origin(path));
+        self.clashes.extend(clashes(&scope, &dests));
+
+        for f in &cmd.flags {
+            let mut args = switches(f.names.short, &f.names.long);
+            match f.action {
+                Some(Action::ShowHelp) => args.push("action=\"help\"".into()),
+                Some(Action::ShowVersion) => {
+                    args.push("action=\"version\"".into());
+                    args.push(format!("version={}", str_lit(&self.version)));
+                }
+                None => args.push("action=\"store_true\"".into()),
+            }
+            if let Some(desc) = &f.desc {
+                args.push(format!("help={}", help_lit(desc)));
+            }
+            call(w, &format!("{var}.add_argument"), &args);
+        }
+
+        // argparse would hand the first positional word to the subparsers, so a command with both
+        // only gets its positionals when `_select` finds no subcommand after its options
+        let mixed = !cmd.positionals.is_empty() && !cmd.commands.is_empty();
+        if !mixed {
+            self.positionals(w, cmd, var);
+        }
+
+        for n in &cmd.named {
+            let mut args = switches(n.names.short, &n.names.long);
+            let dest = ident(&n.names.primary());
+            if n.names.long.is_empty() {
+                args.push(format!("dest={}", str_lit(&dest)));
+            }
+            if n.required {
+                args.push("required=True".into());
+            }
+            self.type_kwargs(&mut args, n.ty, &n.options);
+            self.nargs_kwarg(&mut args, &dest, n.nargs, true);
+            default_kwarg(&mut args, &n.default, n.nargs);
+            if let Some(desc) = &n.desc {
+                args.push(format!("help={}", help_lit(desc)));
+            }
+            call(w, &format!("{var}.add_argument"), &args);
+        }
+
+        if cmd.commands.is_empty() {
+            return;
+        }
+
+        if mixed {
+            self.mixed = true;
+            w.open(&format!("if command == {}:", str_lit(&path.join(" "))));
+            self.positionals(w, cmd, var);
+            w.line(&format!("{var}.set_defaults({prefix}command=None)"));
+            w.close("else:");
+            w.indent();
+            let defaults: Vec<String> = cmd
+                .positionals
+                .iter()
+                .map(|p| format!("{}={}", ident(&p.name), default_lit(&p.default, p.nargs)))
+                .collect();
+            call(w, &format!("{var}.set_defaults"), &defaults);
+        }
+        let subparsers = format!("{prefix}subparsers");
+        w.line(&format!(
+            "{subparsers} = {var}.add_subparsers(dest={})",
+            str_lit(&format!("{prefix}command"))
+        ));
+
+        for sub in &cmd.commands {
+            let mut sub_path = path.to_vec();
+            sub_path.push(sub.names[0].clone());
+            let sub_var = format!("{}_parser", ident(&sub_path.join("_")));
+            self.parsers
+                .push((sub_var.clone(), command_origin(&sub_path)));
+
+            let mut args = vec![str_lit(&sub.names[0])];
+            if sub.names.len() > 1 {
+                let aliases: Vec<String> = sub.names[1..].iter().map(|a| str_lit(a)).collect();
+                args.push(format!("aliases=[{}]", aliases.join(", ")));
+            }
+            if let Some(desc) = &sub.desc {
+                args.push(format!("help={}", help_lit(desc)));
+                args.push(format!("description={}", help_lit(desc)));
+            }
+            args.push("add_help=False".into());
+            args.push("allow_abbrev=False".into());
+
+            w.line("");
+            let head = format!("{sub_var} = {subparsers}.add_parser");
+            call(w, &head, &args);
+            if sub.names.len() > 1 {
+                // Report the command by its first name when an alias is used
+                let dest = format!("{prefix}command={}", str_lit(&sub.names[0]));
+                w.line(&format!("{sub_var}.set_defaults({dest})"));
+            }
+            self.command(w, sub, &sub_var, &sub_path);
+        }
+        if mixed {
+            w.dedent();
+        }
+    }
+}
+
+// The value argparse gives an argument it never saw
+fn default_lit(default: &[Value], n: Nargs) -> String {
+    match default {
+        [] if n.max == Some(1) => "None".into(),
+        [v] if n.max == Some(1) => value_lit(v),
+        vs => {
+            let items: Vec<String> = vs.iter().map(value_lit).collect();
+            format!("[{}]", items.join(", "))
+        }
+    }
+}
+
+fn default_kwarg(args: &mut Vec<String>, default: &[Value], n: Nargs) {
+    if !default.is_empty() {
+        args.push(format!("default={}", default_lit(default, n)));
+    }
+}
+
+fn switches(short: Option<char>, long: &[String]) -> Vec<String> {
+    short
+        .map(|s| format!("-{s}"))
+        .into_iter()
+        .chain(long.iter().map(|l| format!("--{l}")))
+        .map(|s| str_lit(&s))
+        .collect()
+}
+
+// Lay out `head(args)` the way black does: on one line if it fits, then with the arguments on their
+// own line, then one argument per line
+fn call(w: &mut Writer, head: &str, args: &[String]) {
+    let joined = args.join(", ");
+    let line = format!("{head}({joined})");
+    if w.indent_width() + line.len() <= LINE_WIDTH {
+        w.line(&line);
+        return;
+    }
+    w.open(&format!("{head}("));
+    if w.indent_width() + joined.len() <= LINE_WIDTH {
+        w.line(&joined);
+    } else {
+        for arg in args {
+            w.line(&format!("{arg},"));
+        }
+    }
+    w.close(")");
+}
+
+// Lay out a dict literal like black: on one line if it fits, else one item per line
+fn dict(w: &mut Writer, items: &[(String, String)], end: &str) {
+    let items: Vec<String> = items
+        .iter()
+        .map(|(k, v)| format!("{}: {v}", str_lit(k)))
+        .collect();
+    let line = format!("{{{}}}{end}", items.join(", "));
+    if w.indent_width() + line.len() <= LINE_WIDTH {
+        w.line(&line);
+        return;
+    }
+    w.open("{");
+    for item in &items {
+        w.line(&format!("{item},"));
+    }
+    w.close(&format!("}}{end}"));
+}
+
+fn commands_table(levels: &[Level]) -> String {
+    let mut w = Writer::new("    ");
+    w.line(
+        "# Per command path: the most values each option takes (-1 for no limit), the short names",
+    );
+    w.line("# its options claim and the paths its subcommand names select");
+    w.open("_COMMANDS = {");
+    for level in levels {
+        w.open(&format!("{}: (", str_lit(&level.path)));
+        dict(&mut w, &level.takes, ",");
+        w.line(&format!("{},", str_lit(&level.shorts)));
+        let commands: Vec<(String, String)> = level
+            .commands
+            .iter()
+            .map(|(name, path)| (name.clone(), str_lit(path)))
+            .collect();
+        dict(&mut w, &commands, ",");
+        w.close("),");
+    }
+    w.close("}");
+    w.finish()
+}
+
+/// An `argparse` module with `build_parser()` and `parse_args(argv=None)`. Fails when spec names
+/// would share a dest or a parser variable.
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let name = program.name.clone();
+    let mut generator = Generator {
+        uint: false,
+        float: false,
+        bool: false,
+        range: false,
+        pathlib: false,
+        counts: Vec::new(),
+        version: match &name {
+            Some(n) => format!("{n} {}", program.version.as_deref().unwrap_or_default()),
+            None => program.version.clone().unwrap_or_default(),
+        },
+        levels: Vec::new(),
+        mixed: false,
+        parsers: Vec::new(),
+        clashes: Vec::new(),
+    };
+
+    let mut parser = Writer::new("    ");
+    parser.indent();
+    let mut args: Vec<String> = name
+        .iter()
+        .map(|n| format!("prog={}", str_lit(n)))
+        .collect();
+    if let Some(desc) = &program.cmd.desc {
+        args.push(format!("description={}", help_lit(desc)));
+    }
+    args.push("add_help=False".into());
+    // The runtime only accepts option names in full
+    args.push("allow_abbrev=False".into());
+    call(&mut parser, "parser = argparse.ArgumentParser", &args);
+    generator.command(&mut parser, &program.cmd, "parser", &[]);
+    parser.line("return parser");
+    generator
+        .clashes
+        .extend(clashes("`build_parser()`", &generator.parsers));
+    if !generator.clashes.is_empty() {
+        return Err(generator.clashes);
+    }
+
+    let mut body = Writer::new("    ");
+    if generator.mixed {
+        body.open("def build_parser(command=\"\"):");
+        body.line(
+            "\"\"\"`command` is the path of the command the words select: one with both positional",
+        );
+        body.line("arguments and subcommands only takes its positionals when it is selected itself.\"\"\"");
+    } else {
+        body.open("def build_parser():");
+    }
+    body.dedent();
+    let mut body = body.finish();
+    body.push_str(&parser.finish());
+
+    let mut w = Writer::new("    ");
+    w.line("");
+    w.line("");
+    w.open("def parse_args(argv=None):");
+    let build = if generator.mixed {
+        w.open("if argv is None:");
+        w.line("argv = sys.argv[1:]");
+        w.dedent();
+        "build_parser(_select(argv))"
+    } else {
+        "build_parser()"
+    };
+    if generator.counts.is_empty() {
+        w.line(&format!("return {build}.parse_args(argv)"));
+    } else {
+        w.line(&format!("parser = {build}"));
+        w.line("args = parser.parse_args(argv)");
+        w.open("for dest, low, high in _COUNTS:");
+        w.line("values = getattr(args, dest, None)");
+        w.open("if values and (len(values) < low or (high is not None and len(values) > high)):");
+        w.line("expected = f\"{low} to {high}\" if high is not None else f\"at least {low}\"");
+        w.line("parser.error(f\"argument {dest}: expected {expected} values\")");
+        w.dedent();
+        w.dedent();
+        w.line("return args");
+    }
+    w.close("");
+    w.line("");
+    w.open("if __name__ == \"__main__\":");
+    w.line("print(vars(parse_args()))");
+    body.push_str(&w.finish());
+
+    let mut out = format!("# Generated by argolify from `{source}`; do not edit.\n");
+    out.push_str(TRAILING_NOTE);
+    out.push_str("\nimport argparse\n");
+    if generator.float || generator.mixed {
+        out.push_str("import math\n");
+    }
+    if generator.pathlib {
+        out.push_str("import pathlib\n");
+    }
+    if generator.mixed {
+        out.push_str("import sys\n");
+    }
+    out.push_str("\n\n");
+
+    if !generator.counts.is_empty() {
+        out.push_str("# (dest, min, max) for value counts argparse cannot check itself\n");
+        out.push_str("_COUNTS = [\n");
+        for (dest, min, max) in &generator.counts {
+            let max = max.map_or("None".into(), |m| m.to_string());
+            out.push_str(&format!("    ({}, {min}, {max}),\n", str_lit(dest)));
+        }
+        out.push_str("]\n\n\n");
+    }
+    if generator.mixed {
+        out.push_str(&commands_table(&generator.levels));
+        out.push_str("\n\n");
+    }
+
+    let helpers = [
+        (generator.uint, UINT_HELPER),
+        (generator.float, FLOAT_HELPER),
+        (generator.bool, BOOL_HELPER),
+        (generator.range, RANGE_HELPER),
+        (generator.mixed, SELECT),
+    ];
+    for (used, helper) in helpers {
+        if used {
+            out.push_str(helper.trim_start());
+            out.push_str("\n\n");
+        }
+    }
+
+    out.push_str(body.trim_end());
+    out.push('\n');
+    Ok(out)
+}
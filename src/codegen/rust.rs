@@ -0,0 +1,446 @@
+use crate::codegen::{Writer, clashes, command_origin, pascal};
+use crate::spec::{Action, ArgType, Command, Names, Nargs, Options, Program, Range, Value};
+
+const LINE_WIDTH: usize = 100;
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+
+const ONE_OF_HELPER: &str = r#"
+fn one_of<T>(allowed: &'static [T]) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync
+where
+    T: std::str::FromStr + PartialEq + std::fmt::Display + Send + Sync + 'static,
+{
+    move |s| {
+        let value: T = s.parse().map_err(|_| format!("invalid value '{s}'"))?;
+        if allowed.contains(&value) {
+            return Ok(value);
+        }
+        let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+        Err(format!("possible values: {}", allowed.join(", ")))
+    }
+}
+"#;
+
+// clap's own f64 parser accepts `inf` and `nan`, which argolify rejects
+const FINITE_HELPER: &str = r#"
+fn finite(s: &str) -> Result<f64, String> {
+    s.parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite())
+        .ok_or_else(|| format!("invalid value '{s}'"))
+}
+"#;
+
+const IN_RANGE_HELPER: &str = r#"
+fn in_range(
+    start: Option<f64>,
+    end: Option<f64>,
+    inclusive: bool,
+    text: &'static str,
+) -> impl Fn(&str) -> Result<f64, String> + Clone + Send + Sync {
+    move |s| {
+        let value = finite(s)?;
+        let above = start.is_none_or(|start| value >= start);
+        let below = end.is_none_or(|end| if inclusive { value <= end } else { value < end });
+        if above && below {
+            Ok(value)
+        } else {
+            Err(format!("{s} is not in range {text}"))
+        }
+    }
+}
+"#;
+
+fn field(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{name}"),
+        // Keywords that cannot be raw identifiers
+        _ if ["crate", "self", "super"].contains(&name.as_str()) => format!("{name}_"),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("r#{name}"),
+        _ => name,
+    }
+}
+
+fn rust_type(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str => "String",
+        ArgType::Int => "i64",
+        ArgType::UInt => "u64",
+        ArgType::Float => "f64",
+        ArgType::Bool => "bool",
+        ArgType::Path => "std::path::PathBuf",
+    }
+}
+
+fn value_lit(v: &Value) -> String {
+    match v {
+        Value::Str(s) => format!("{s:?}"),
+        Value::Int(i) => i.to_string(),
+        Value::Float(x) => format!("{x:?}"),
+        Value::Bool(b) => b.to_string(),
+    }
+}
+
+fn range_lit(r: &Range) -> String {
+    let start = r.start.as_ref().map(value_lit).unwrap_or_default();
+    let end = r.end.as_ref().map(value_lit).unwrap_or_default();
+    let op = if r.inclusive && r.end.is_some() {
+        "..="
+    } else {
+        ".."
+    };
+    format!("{start}{op}{end}")
+}
+
+fn opt_f64(v: &Option<Value>) -> String {
+    match v {
+        Some(Value::Int(i)) => format!("Some({:?})", *i as f64),
+        Some(Value::Float(x)) => format!("Some({x:?})"),
+        _ => "None".into(),
+    }
+}
+
+fn num_args(n: Nargs) -> String {
+    match (n.min, n.max) {
+        (min, Some(max)) if min == max => format!("num_args = {min}"),
+        (min, Some(max)) => format!("num_args = {min}..={max}"),
+        (min, None) => format!("num_args = {min}.."),
+    }
+}
+
+// `#[name(args)]` on one line when it fits, otherwise one argument per line
+fn attr(w: &mut Writer, name: &str, args: &[String]) {
+    let line = format!("#[{name}({})]", args.join(", "));
+    if w.indent_width() + line.len() <= LINE_WIDTH {
+        w.line(&line);
+        return;
+    }
+    w.open(&format!("#[{name}("));
+    for arg in args {
+        w.line(&format!("{arg},"));
+    }
+    w.close(")]");
+}
+
+fn doc(w: &mut Writer, desc: &Option<String>) {
+    if let Some(desc) = desc {
+        for line in desc.lines() {
+            w.line(format!("/// {line}").trim_end());
+        }
+    }
+}
+
+fn switch_args(names: &Names) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(s) = names.short {
+        args.push(format!("short = {s:?}"));
+    }
+    if let Some((long, aliases)) = names.long.split_first() {
+        args.push(format!("long = {long:?}"));
+        if !aliases.is_empty() {
+            let aliases: Vec<String> = aliases.iter().map(|a| format!("{a:?}")).collect();
+            args.push(format!("visible_aliases = [{}]", aliases.join(", ")));
+        }
+    }
+    args
+}
+
+struct Generator<'a> {
+    program: &'a Program,
+    one_of: bool,
+    finite: bool,
+    in_range: bool,
+    items: Vec<String>,
+    // Each struct and enum with what it was made from
+    types: Vec<(String, String)>,
+    clashes: Vec<String>,
+}
+
+impl Generator<'_> {
+    fn value_parser(&mut self, ty: ArgType, options: &Option<Options>) -> Option<String> {
+        let parser = match (options, ty) {
+            (None, ArgType::Float) => {
+                self.finite = true;
+                "finite".into()
+            }
+            (None, _) => return None,
+            (Some(Options::Values(vs)), ArgType::Str) => {
+                let items: Vec<String> =
+                    vs.iter().map(|v| format!("{:?}", v.to_string())).collect();
+                format!("[{}]", items.join(", "))
+            }
+            (Some(Options::Values(vs)), ArgType::Path) => {
+                let items: Vec<String> =
+                    vs.iter().map(|v| format!("{:?}", v.to_string())).collect();
+                format!(
+                    "clap::builder::TypedValueParser::map(\
+                     clap::builder::PossibleValuesParser::new([{}]), std::path::PathBuf::from)",
+                    items.join(", ")
+                )
+            }
+            (Some(Options::Values(vs)), _) => {
+                self.one_of = true;
+                let items: Vec<String> = vs.iter().map(value_lit).collect();
+                format!("one_of::<{}>(&[{}])", rust_type(ty), items.join(", "))
+            }
+            (Some(Options::Range(r)), ArgType::Int | ArgType::UInt) => format!(
+                "clap::value_parser!({}).range({})",
+                rust_type(ty),
+                range_lit(r)
+            ),
+            (Some(Options::Range(r)), _) => {
+                self.finite = true;
+                self.in_range = true;
+                format!(
+                    "in_range({}, {}, {}, {:?})",
+                    opt_f64(&r.start),
+                    opt_f64(&r.end),
+                    r.inclusive,
+                    r.to_string()
+                )
+            }
+        };
+        Some(format!("value_parser = {parser}"))
+    }
+
+    // Attribute arguments and field type for a positional or named argument
+    fn value(
+        &mut self,
+        mut args: Vec<String>,
+        ty: ArgType,
+        options: &Option<Options>,
+        default: &[Value],
+        n: Nargs,
+        required: bool,
+    ) -> (Vec<String>, String) {
+        let inner = rust_type(ty);
+        let multiple = n.max != Some(1);
+        if multiple {
+            args.push(num_args(n));
+        } else if n.min == 0 {
+            args.push("num_args = 0..=1".into());
+        }
+        if required {
+            args.push("required = true".into());
+        }
+        match default {
+            [] => {}
+            [v] if !multiple => args.push(format!("default_value = {:?}", v.to_string())),
+            vs => {
+                let items: Vec<String> =
+                    vs.iter().map(|v| format!("{:?}", v.to_string())).collect();
+                args.push(format!("default_values = [{}]", items.join(", ")));
+            }
+        }
+        if ty == ArgType::Bool {
+            args.push("action = clap::ArgAction::Set".into());
+        }
+        // Otherwise clap reads `-4` as an unknown short option
+        if matches!(ty, ArgType::Int | ArgType::Float) {
+            args.push("allow_negative_numbers = true".into());
+        }
+        args.extend(self.value_parser(ty, options));
+
+        let field_ty = if multiple {
+            format!("Vec<{inner}>")
+        } else if required || !default.is_empty() {
+            inner.to_string()
+        } else {
+            format!("Option<{inner}>")
+        };
+        (args, field_ty)
+    }
+
+    fn command(&mut self, cmd: &Command, path: &[String]) {
+        let name = if path.is_empty() {
+            "Cli".to_string()
+        } else {
+            format!("{}Args", pascal(path))
+        };
+        let enum_name = if path.is_empty() {
+            "Commands".to_string()
+        } else {
+            format!("{}Commands", pascal(path))
+        };
+
+        let mut w = Writer::new("    ");
+        let has_version = cmd
+            .flags
+            .iter()
+            .any(|f| f.action == Some(Action::ShowVersion));
+        let mut command_args = Vec::new();
+        if path.is_empty() {
+            w.line("#[derive(Debug, Parser)]");
+            if let Some(name) = &self.program.name {
+                command_args.push(format!("name = {name:?}"));
+            }
+        } else {
+            w.line("#[derive(Debug, Args)]");
+        }
+        if has_version && let Some(version) = &self.program.version {
+            command_args.push(format!("version = {version:?}"));
+        }
+        command_args.push("disable_help_flag = true".into());
+        if path.is_empty() {
+            command_args.push("disable_version_flag = true".into());
+        }
+        if !cmd.commands.is_empty() {
+            command_args.push("disable_help_subcommand = true".into());
+        }
+        attr(&mut w, "command", &command_args);
+        w.open(&format!("pub struct {name} {{"));
+
+        for f in &cmd.flags {
+            doc(&mut w, &f.desc);
+            let mut args = switch_args(&f.names);
+            let ty = match f.action {
+                Some(Action::ShowHelp) => {
+                    args.push("action = clap::ArgAction::Help".into());
+                    "Option<bool>"
+                }
+                Some(Action::ShowVersion) => {
+                    args.push("action = clap::ArgAction::Version".into());
+                    "Option<bool>"
+                }
+                None => "bool",
+            };
+            attr(&mut w, "arg", &args);
+            w.line(&format!("pub {}: {ty},", field(&f.names.primary())));
+        }
+
+        for p in &cmd.positionals {
+            doc(&mut w, &p.desc);
+            let args = vec![format!("value_name = {:?}", p.name)];
+            let (args, ty) = self.value(args, p.ty, &p.options, &p.default, p.nargs, p.required);
+            attr(&mut w, "arg", &args);
+            w.line(&format!("pub {}: {ty},", field(&p.name)));
+        }
+
+        for n in &cmd.named {
+            doc(&mut w, &n.desc);
+            let args = switch_args(&n.names);
+            let (args, ty) = self.value(args, n.ty, &n.options, &n.default, n.nargs, n.required);
+            attr(&mut w, "arg", &args);
+            w.line(&format!("pub {}: {ty},", field(&n.names.primary())));
+        }
+
+        if !cmd.commands.is_empty() {
+            w.line("#[command(subcommand)]");
+            w.line(&format!("pub command: Option<{enum_name}>,"));
+        }
+        w.close("}");
+        self.items.push(w.finish());
+
+        let mut fields: Vec<(String, String)> = cmd
+            .flags
+            .iter()
+            .map(|f| f.names.primary())
+            .chain(cmd.positionals.iter().map(|p| p.name.clone()))
+            .chain(cmd.named.iter().map(|n| n.names.primary()))
+            .map(|arg| (field(&arg), format!("argument `{arg}`")))
+            .collect();
+        if !cmd.commands.is_empty() {
+            fields.push(("command".into(), "the subcommand field".into()));
+        }
+        self.clashes.extend(clashes(&format!("`{name}`"), &fields));
+        self.types.push((name, command_origin(path)));
+
+        if cmd.commands.is_empty() {
+            return;
+        }
+        self.types.push((
+            enum_name.clone(),
+            format!("the subcommands of {}", command_origin(path)),
+        ));
+
+        let mut w = Writer::new("    ");
+        w.line("#[derive(Debug, Subcommand)]");
+        w.open(&format!("pub enum {enum_name} {{"));
+        let mut subs = Vec::new();
+        let mut variants = Vec::new();
+        for sub in &cmd.commands {
+            let mut sub_path = path.to_vec();
+            sub_path.push(sub.names[0].clone());
+
+            doc(&mut w, &sub.desc);
+            let mut args = vec![format!("name = {:?}", sub.names[0])];
+            if sub.names.len() > 1 {
+                let aliases: Vec<String> =
+                    sub.names[1..].iter().map(|a| format!("{a:?}")).collect();
+                args.push(format!("visible_aliases = [{}]", aliases.join(", ")));
+            }
+            attr(&mut w, "command", &args);
+            let variant = pascal(&sub.names[..1]);
+            w.line(&format!("{variant}({}Args),", pascal(&sub_path)));
+            variants.push((variant, command_origin(&sub_path)));
+            subs.push((sub, sub_path));
+        }
+        w.close("}");
+        self.items.push(w.finish());
+        self.clashes
+            .extend(clashes(&format!("`{enum_name}`"), &variants));
+
+        for (sub, sub_path) in subs {
+            self.command(sub, &sub_path);
+        }
+    }
+}
+
+/// clap derive structs: `Cli` for the top level, an `Args` struct per command and a `Commands`
+/// enum per set of subcommands. Fails when spec names would become the same identifier.
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let mut generator = Generator {
+        program,
+        one_of: false,
+        finite: false,
+        in_range: false,
+        items: Vec::new(),
+        types: Vec::new(),
+        clashes: Vec::new(),
+    };
+    generator.command(&program.cmd, &[]);
+    generator
+        .clashes
+        .extend(clashes("the module", &generator.types));
+    if !generator.clashes.is_empty() {
+        return Err(generator.clashes);
+    }
+
+    let mut uses = vec!["Parser"];
+    if !program.cmd.commands.is_empty() {
+        uses.insert(0, "Args");
+        uses.push("Subcommand");
+    }
+
+    let mut out = format!("// Generated by argolify from `{source}`; do not edit.\n\n");
+    out.push_str(&format!("use clap::{{{}}};\n", uses.join(", ")));
+    for item in &generator.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    if generator.one_of {
+        out.push_str(ONE_OF_HELPER);
+    }
+    if generator.finite {
+        out.push_str(FINITE_HELPER);
+    }
+    if generator.in_range {
+        out.push_str(IN_RANGE_HELPER);
+    }
+    Ok(out)
+}
@@ -0,0 +1,778 @@
+use crate::codegen::{Writer, clashes, command_origin};
+use crate::help;
+use crate::runtime::{Output, fmt_nargs, help_hint, shell_quote, shell_var};
+use crate::spec::{Action, ArgType, Command, Nargs, Options, Program, Value};
+
+const PRELUDE: &str = r#"
+_argol_fail() {
+    printf 'error: %s%s\n' "$1" "$_argol_in" >&2
+    if [ -n "$_argol_hint" ]; then
+        printf '\n%s\n' "$_argol_hint" >&2
+    fi
+    exit 2
+}
+
+# Set `_argol_q` to $1 in single quotes
+_argol_quote() {
+    _argol_q=$(printf '%sx\n' "$1" | sed "s/'/'\\\\''/g")
+    _argol_q="'${_argol_q%x}'"
+}
+
+# Whether $1 is a finite number; literals that overflow to infinity do not count
+_argol_number() {
+    _argol_s=$1 awk 'BEGIN {
+        s = ENVIRON["_argol_s"]
+        x = s < 0 ? -s : +s
+        exit !(s ~ /^[-+]?([0-9]+\.?[0-9]*|\.[0-9]+)([eE][-+]?[0-9]+)?$/ &&
+            x <= 1.7976931348623157e308)
+    }'
+}
+
+# Whether $1 is an option rather than a value: a dash-prefixed word is a value when it is a number
+# and its first character is not one of the short names in $2
+_argol_is_option() {
+    case $1 in
+    --) return 0 ;;
+    -?*) ;;
+    *) return 1 ;;
+    esac
+    _argol_c=${1#-}
+    _argol_c=${_argol_c%"${_argol_c#?}"}
+    case $2 in
+    *"$_argol_c"*) [ -n "$2" ] && return 0 ;;
+    esac
+    ! _argol_number "${1#-}"
+}
+
+_argol_positional() {
+    _argol_quote "$1"
+    _argol_pos="${_argol_pos:+$_argol_pos }$_argol_q"
+    _argol_npos=$((_argol_npos + 1))
+}
+
+# Add up to $1 (no limit if empty) of the following words to `_argol_vals`, stopping at anything
+# that looks like an option; `_argol_n` is how many were taken
+_argol_collect() {
+    _argol_max=$1
+    _argol_shorts=$2
+    shift 2
+    _argol_n=0
+    while [ $# -gt 0 ] && { [ -z "$_argol_max" ] || [ "$_argol_nvals" -lt "$_argol_max" ]; }; do
+        _argol_is_option "$1" "$_argol_shorts" && break
+        _argol_quote "$1"
+        _argol_vals="${_argol_vals:+$_argol_vals }$_argol_q"
+        _argol_nvals=$((_argol_nvals + 1))
+        _argol_n=$((_argol_n + 1))
+        shift
+    done
+}
+
+# Set `_argol_vals` to the first $1 of the following words
+_argol_take() {
+    _argol_vals=
+    _argol_nvals=0
+    _argol_max=$1
+    shift
+    while [ "$_argol_nvals" -lt "$_argol_max" ]; do
+        _argol_quote "$1"
+        _argol_vals="${_argol_vals:+$_argol_vals }$_argol_q"
+        _argol_nvals=$((_argol_nvals + 1))
+        shift
+    done
+}
+"#;
+
+const INT_HELPER: &str = r#"
+_argol_int() {
+    case ${1#[-+]} in
+    '' | *[!0-9]*) return 1 ;;
+    esac
+}
+"#;
+
+const UINT_HELPER: &str = r#"
+_argol_uint() {
+    case ${1#+} in
+    '' | *[!0-9]*) return 1 ;;
+    esac
+}
+"#;
+
+const BOOL_HELPER: &str = r#"
+_argol_bool() {
+    case $1 in
+    true | false) ;;
+    *) return 1 ;;
+    esac
+}
+"#;
+
+const ONE_OF_HELPER: &str = r#"
+# Whether the number $1 equals one of the numbers that follow it
+_argol_one_of() {
+    _argol_s=$1 awk 'BEGIN {
+        for (i = 2; i < ARGC; i++)
+            if (ENVIRON["_argol_s"] + 0 == ARGV[i] + 0)
+                exit 0
+        exit 1
+    }' "$@"
+}
+"#;
+
+const IN_RANGE_HELPER: &str = r#"
+# Whether the number $1 is within $2..$3 (either may be empty), including $3 when $4 is 1
+_argol_in_range() {
+    _argol_s=$1 awk -v start="$2" -v end="$3" -v inclusive="$4" 'BEGIN {
+        x = ENVIRON["_argol_s"] + 0
+        if (start != "" && x < start + 0)
+            exit 1
+        if (end != "" && (inclusive ? x > end + 0 : x >= end + 0))
+            exit 1
+    }'
+}
+"#;
+
+fn ident(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// A `case` pattern matching `s` literally
+fn pattern(s: &str) -> String {
+    let plain = s
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+    if plain && !s.is_empty() {
+        s.to_string()
+    } else {
+        shell_quote(s)
+    }
+}
+
+// Text for use inside double quotes
+fn dq(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if "\\\"$`".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn var(name: &str) -> String {
+    shell_var(Output::SHELL_PREFIX, name)
+}
+
+fn quoted_values(values: &[Value]) -> String {
+    let items: Vec<String> = values.iter().map(|v| shell_quote(&v.to_string())).collect();
+    items.join(" ")
+}
+
+fn number(v: Option<&Value>) -> String {
+    match v {
+        Some(Value::Int(i)) => i.to_string(),
+        Some(Value::Float(x)) => x.to_string(),
+        _ => "''".into(),
+    }
+}
+
+// An argument as the matcher in `finish` sees it
+struct Arg<'a> {
+    name: String,
+    ty: ArgType,
+    options: &'a Option<Options>,
+    default: &'a [Value],
+    nargs: Nargs,
+    required: bool,
+}
+
+struct Generator<'a> {
+    program: &'a Program,
+    int: bool,
+    uint: bool,
+    bool: bool,
+    one_of: bool,
+    in_range: bool,
+    functions: Vec<String>,
+    // Each `_argol_parse*` function with the command it parses
+    parsers: Vec<(String, String)>,
+    clashes: Vec<String>,
+}
+
+impl Generator<'_> {
+    // `_argol_check*` function validating each of its arguments, if `arg` needs one
+    fn check_fn(&mut self, suffix: &str, arg: &Arg) -> Option<String> {
+        let type_check = match arg.ty {
+            ArgType::Str | ArgType::Path => None,
+            ArgType::Int => {
+                self.int = true;
+                Some("_argol_int")
+            }
+            ArgType::UInt => {
+                self.uint = true;
+                Some("_argol_uint")
+            }
+            ArgType::Float => Some("_argol_number"),
+            ArgType::Bool => {
+                self.bool = true;
+                Some("_argol_bool")
+            }
+        };
+        if type_check.is_none() && arg.options.is_none() {
+            return None;
+        }
+
+        let name = format!("_argol_check{suffix}_{}", ident(&arg.name));
+        let subject = dq(&arg.name);
+        let mut w = Writer::new("    ");
+        w.open(&format!("{name}() {{"));
+        w.open("for _argol_val do");
+        // Failures go on a continuation line
+        let cont = format!(" ||\n{:w$}", "", w = w.indent_width() + 4);
+        if let Some(check) = type_check {
+            w.line(&format!(
+                "{check} \"$_argol_val\"{cont}_argol_fail \"invalid value '$_argol_val' for '{subject}': expected {}\"",
+                arg.ty
+            ));
+        }
+        let not_allowed = |choices: String| {
+            format!(
+                "_argol_fail \"invalid value '$_argol_val' for '{subject}' (possible values: {})\"",
+                dq(&choices)
+            )
+        };
+        match arg.options {
+            Some(Options::Values(vs))
+                if !matches!(arg.ty, ArgType::Int | ArgType::UInt | ArgType::Float) =>
+            {
+                let choices: Vec<String> = vs.iter().map(|v| v.to_string()).collect();
+                let patterns: Vec<String> = choices.iter().map(|c| pattern(c)).collect();
+                w.line("case $_argol_val in");
+                w.line(&format!("{}) ;;", patterns.join(" | ")));
+                w.line(&format!("*) {} ;;", not_allowed(choices.join(", "))));
+                w.line("esac");
+            }
+            Some(Options::Values(vs)) => {
+                self.one_of = true;
+                let choices: Vec<String> = vs.iter().map(|v| v.to_string()).collect();
+                let numbers: Vec<String> = vs.iter().map(|v| number(Some(v))).collect();
+                w.line(&format!(
+                    "_argol_one_of \"$_argol_val\" {}{cont}{}",
+                    numbers.join(" "),
+                    not_allowed(choices.join(", "))
+                ));
+            }
+            Some(Options::Range(r)) => {
+                self.in_range = true;
+                w.line(&format!(
+                    "_argol_in_range \"$_argol_val\" {} {} {}{cont}{}",
+                    number(r.start.as_ref()),
+                    number(r.end.as_ref()),
+                    u8::from(r.inclusive),
+                    not_allowed(r.to_string())
+                ));
+            }
+            None => {}
+        }
+        w.close("done");
+        w.close("}");
+        self.functions.push(w.finish());
+        Some(name)
+    }
+
+    // Validate `_argol_vals` and assign the argument's variable, falling back to its default
+    fn resolve(&mut self, w: &mut Writer, suffix: &str, arg: &Arg) {
+        let var = var(&arg.name);
+        let multiple = arg.nargs.max != Some(1);
+        w.open("if [ \"$_argol_nvals\" -gt 0 ]; then");
+        if let Some(check) = self.check_fn(suffix, arg) {
+            w.line(&format!("eval \"{check} $_argol_vals\""));
+        }
+        if multiple {
+            w.line(&format!("{var}=$_argol_vals"));
+        } else {
+            w.line(&format!("eval \"{var}=$_argol_vals\""));
+        }
+        w.close("else");
+        w.indent();
+        if arg.required {
+            w.line(&format!(
+                "_argol_fail \"missing required argument '{}'\"",
+                dq(&arg.name)
+            ));
+        } else {
+            let default = if multiple {
+                shell_quote(&quoted_values(arg.default))
+            } else {
+                arg.default
+                    .first()
+                    .map(|v| shell_quote(&v.to_string()))
+                    .unwrap_or_default()
+            };
+            w.line(&format!("{var}={default}"));
+        }
+        w.close("fi");
+    }
+
+    fn flag_fn(&mut self, cmd: &Command, path: &[String], suffix: &str) {
+        let mut w = Writer::new("    ");
+        w.open(&format!("_argol_flag{suffix}() {{"));
+        w.line("case $1 in");
+        for f in &cmd.flags {
+            let key = ident(&f.names.primary());
+            match f.action {
+                Some(action) => {
+                    let text = match action {
+                        Action::ShowHelp => help::render(self.program, path, 80),
+                        Action::ShowVersion => {
+                            let version = self.program.version.clone().unwrap_or_default();
+                            match &self.program.name {
+                                Some(name) => format!("{name} {version}"),
+                                None => version,
+                            }
+                        }
+                    };
+                    w.line(&format!("{key})"));
+                    w.indent();
+                    w.line(&format!("printf '%s\\n' {}", shell_quote(text.trim_end())));
+                    w.line("exit 0");
+                    w.line(";;");
+                    w.dedent();
+                }
+                None => w.line(&format!("{key}) {}=true ;;", var(&f.names.primary()))),
+            }
+        }
+        w.line("esac");
+        w.close("}");
+        self.functions.push(w.finish());
+    }
+
+    fn finish_fn(&mut self, cmd: &Command, suffix: &str) {
+        let mut w = Writer::new("    ");
+        w.open(&format!("_argol_finish{suffix}() {{"));
+        w.line("eval \"set -- $_argol_pos\"");
+
+        // Hand out positional words in order, leaving enough for the minimum of later positionals
+        for (i, p) in cmd.positionals.iter().enumerate() {
+            let reserved: usize = cmd.positionals[i + 1..].iter().map(|p| p.nargs.min).sum();
+            w.line("");
+            if reserved > 0 {
+                w.line(&format!("_argol_n=$(($# - {reserved}))"));
+                w.line("[ \"$_argol_n\" -ge 0 ] || _argol_n=0");
+            } else {
+                w.line("_argol_n=$#");
+            }
+            if let Some(max) = p.nargs.max {
+                w.line(&format!("[ \"$_argol_n\" -le {max} ] || _argol_n={max}"));
+            }
+            w.line("_argol_take \"$_argol_n\" \"$@\"");
+            w.line("shift \"$_argol_nvals\"");
+            if p.nargs.min > 1 {
+                w.open(&format!(
+                    "if [ \"$_argol_nvals\" -gt 0 ] && [ \"$_argol_nvals\" -lt {} ]; then",
+                    p.nargs.min
+                ));
+                w.line(&format!(
+                    "_argol_fail \"'{}' expects {} value(s)\"",
+                    dq(&p.name),
+                    fmt_nargs(&p.nargs)
+                ));
+                w.close("fi");
+            }
+            let arg = Arg {
+                name: p.name.clone(),
+                ty: p.ty,
+                options: &p.options,
+                default: &p.default,
+                nargs: p.nargs,
+                required: p.required,
+            };
+            self.resolve(&mut w, suffix, &arg);
+        }
+
+        // Leftover words are only allowed after `--`, where they are passed through as trailing
+        w.line("");
+        w.open("if [ $# -gt 0 ]; then");
+        w.open("if [ -z \"$_argol_dashes\" ] || [ $((_argol_npos - $#)) -lt \"$_argol_dashes\" ]; then");
+        w.line("_argol_fail \"unexpected argument '$1'\"");
+        w.close("fi");
+        w.open("for _argol_arg do");
+        w.line("_argol_quote \"$_argol_arg\"");
+        let trailing = var("trailing");
+        w.line(&format!(
+            "{trailing}=\"${{{trailing}:+${trailing} }}$_argol_q\""
+        ));
+        w.close("done");
+        w.close("fi");
+
+        for n in &cmd.named {
+            let name = n.names.primary();
+            let key = ident(&name);
+            w.line("");
+            if let Some(max) = n.nargs.max
+                && max != 1
+            {
+                w.open(&format!("if [ \"$_argol_n_{key}\" -gt {max} ]; then"));
+                w.line(&format!(
+                    "_argol_fail \"'--{}' expects {} value(s)\"",
+                    dq(&name),
+                    fmt_nargs(&n.nargs)
+                ));
+                w.close("fi");
+            }
+            w.line(&format!("_argol_vals=$_argol_v_{key}"));
+            w.line(&format!("_argol_nvals=$_argol_n_{key}"));
+            let arg = Arg {
+                name,
+                ty: n.ty,
+                options: &n.options,
+                default: &n.default,
+                nargs: n.nargs,
+                required: n.required,
+            };
+            self.resolve(&mut w, suffix, &arg);
+        }
+        w.close("}");
+        self.functions.push(w.finish());
+    }
+
+    fn command(&mut self, cmd: &Command, path: &[String]) {
+        let suffix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("_{}", ident(&path.join("_")))
+        };
+        self.parsers
+            .push((format!("_argol_parse{suffix}"), command_origin(path)));
+        let shorts: String = cmd
+            .flags
+            .iter()
+            .filter_map(|f| f.names.short)
+            .chain(cmd.named.iter().filter_map(|n| n.names.short))
+            .collect();
+        let shorts = shell_quote(&shorts);
+
+        let mut w = Writer::new("    ");
+        if path.is_empty() {
+            w.open("argol_parse() {");
+            w.line(&format!("{}=", var("command")));
+            w.line(&format!("{}=", var("trailing")));
+            w.line("_argol_in=");
+        } else {
+            w.open(&format!("_argol_parse{suffix}() {{"));
+            w.line(&format!(
+                "_argol_in={}",
+                shell_quote(&format!(" (in {})", path.join(" ")))
+            ));
+        }
+        let hint = help_hint(self.program, path).unwrap_or_default();
+        w.line(&format!("_argol_hint={}", shell_quote(&hint)));
+        w.line("_argol_sub=");
+        for f in cmd.flags.iter().filter(|f| f.action.is_none()) {
+            w.line(&format!("{}=false", var(&f.names.primary())));
+        }
+        for n in &cmd.named {
+            let key = ident(&n.names.primary());
+            w.line(&format!("_argol_v_{key}="));
+            w.line(&format!("_argol_n_{key}=0"));
+        }
+        w.line("_argol_pos=");
+        w.line("_argol_npos=0");
+        w.line("_argol_dashes=");
+
+        w.open("while [ $# -gt 0 ]; do");
+        w.line("_argol_arg=$1");
+        w.line("shift");
+        w.open("if [ -n \"$_argol_dashes\" ]; then");
+        w.line("_argol_positional \"$_argol_arg\"");
+        w.line("continue");
+        w.close("fi");
+        w.line("_argol_key=");
+        w.line("_argol_vals=");
+        w.line("_argol_nvals=0");
+        w.line("case $_argol_arg in");
+        w.line("--) _argol_dashes=$_argol_npos ;;");
+
+        // Long names, with an inline `=value`
+        w.line("--*)");
+        w.indent();
+        w.line("_argol_name=${_argol_arg#--}");
+        w.line("case $_argol_name in");
+        w.line("*=*)");
+        w.indent();
+        w.line("_argol_quote \"${_argol_name#*=}\"");
+        w.line("_argol_vals=$_argol_q");
+        w.line("_argol_nvals=1");
+        w.line("_argol_name=${_argol_name%%=*}");
+        w.line(";;");
+        w.dedent();
+        w.line("esac");
+        w.line("case $_argol_name in");
+        for f in &cmd.flags {
+            let patterns: Vec<String> = f.names.long.iter().map(|l| pattern(l)).collect();
+            if patterns.is_empty() {
+                continue;
+            }
+            w.line(&format!("{})", patterns.join(" | ")));
+            w.indent();
+            w.open("if [ \"$_argol_nvals\" -gt 0 ]; then");
+            w.line("_argol_fail \"flag '--$_argol_name' does not take a value\"");
+            w.close("fi");
+            w.line(&format!(
+                "_argol_flag{suffix} {}",
+                ident(&f.names.primary())
+            ));
+            w.line(";;");
+            w.dedent();
+        }
+        for n in &cmd.named {
+            let patterns: Vec<String> = n.names.long.iter().map(|l| pattern(l)).collect();
+            if !patterns.is_empty() {
+                let key = ident(&n.names.primary());
+                w.line(&format!("{}) _argol_key={key} ;;", patterns.join(" | ")));
+            }
+        }
+        w.line("*) _argol_fail \"unknown option '--$_argol_name'\" ;;");
+        w.line("esac");
+        w.line(";;");
+        w.dedent();
+
+        // Clusters of short names, where a named argument takes the rest as its value
+        w.line("-?*)");
+        w.indent();
+        w.open(&format!(
+            "if ! _argol_is_option \"$_argol_arg\" {shorts}; then"
+        ));
+        w.line("_argol_positional \"$_argol_arg\"");
+        w.line("continue");
+        w.close("fi");
+        w.line("_argol_rest=${_argol_arg#-}");
+        w.open("while [ -n \"$_argol_rest\" ]; do");
+        w.line("_argol_c=${_argol_rest%\"${_argol_rest#?}\"}");
+        w.line("_argol_rest=${_argol_rest#?}");
+        w.line("case $_argol_c in");
+        for f in &cmd.flags {
+            if let Some(s) = f.names.short {
+                let key = ident(&f.names.primary());
+                w.line(&format!(
+                    "{}) _argol_flag{suffix} {key} ;;",
+                    pattern(&s.to_string())
+                ));
+            }
+        }
+        for n in &cmd.named {
+            if let Some(s) = n.names.short {
+                let key = ident(&n.names.primary());
+                w.line(&format!("{})", pattern(&s.to_string())));
+                w.indent();
+                w.line(&format!("_argol_key={key}"));
+                w.line("break");
+                w.line(";;");
+                w.dedent();
+            }
+        }
+        w.line("*) _argol_fail \"unknown option '-$_argol_c'\" ;;");
+        w.line("esac");
+        w.close("done");
+        w.line("_argol_rest=${_argol_rest#=}");
+        w.open("if [ -n \"$_argol_rest\" ]; then");
+        w.line("_argol_quote \"$_argol_rest\"");
+        w.line("_argol_vals=$_argol_q");
+        w.line("_argol_nvals=1");
+        w.close("fi");
+        w.line(";;");
+        w.dedent();
+
+        // Subcommands are only recognised before the first positional
+        if cmd.commands.is_empty() {
+            w.line("*) _argol_positional \"$_argol_arg\" ;;");
+        } else {
+            w.line("*)");
+            w.indent();
+            w.open("if [ \"$_argol_npos\" -eq 0 ]; then");
+            w.line("case $_argol_arg in");
+            for sub in &cmd.commands {
+                let patterns: Vec<String> = sub.names.iter().map(|n| pattern(n)).collect();
+                w.line(&format!("{})", patterns.join(" | ")));
+                w.indent();
+                w.line(&format!("_argol_sub={}", ident(&sub.names[0])));
+                w.line("break");
+                w.line(";;");
+                w.dedent();
+            }
+            w.line("esac");
+            w.close("fi");
+            w.line("_argol_positional \"$_argol_arg\"");
+            w.line(";;");
+            w.dedent();
+        }
+        w.line("esac");
+
+        if !cmd.named.is_empty() {
+            w.line("case $_argol_key in");
+            for n in &cmd.named {
+                let name = n.names.primary();
+                let key = ident(&name);
+                let max = n.nargs.max.map_or("''".into(), |m| m.to_string());
+                w.line(&format!("{key})"));
+                w.indent();
+                w.line(&format!("_argol_collect {max} {shorts} \"$@\""));
+                w.line("shift \"$_argol_n\"");
+                if n.nargs.min > 0 {
+                    w.open(&format!(
+                        "if [ \"$_argol_nvals\" -lt {} ]; then",
+                        n.nargs.min
+                    ));
+                    w.line(&format!(
+                        "_argol_fail \"'--{}' expects {} value(s)\"",
+                        dq(&name),
+                        fmt_nargs(&n.nargs)
+                    ));
+                    w.close("fi");
+                }
+                if n.nargs.max == Some(1) {
+                    w.line(&format!("_argol_v_{key}=$_argol_vals"));
+                    w.line(&format!("_argol_n_{key}=$_argol_nvals"));
+                } else {
+                    w.line(&format!(
+                        "_argol_v_{key}=\"${{_argol_v_{key}:+$_argol_v_{key} }}$_argol_vals\""
+                    ));
+                    w.line(&format!(
+                        "_argol_n_{key}=$((_argol_n_{key} + _argol_nvals))"
+                    ));
+                }
+                w.line(";;");
+                w.dedent();
+            }
+            w.line("esac");
+        }
+        w.close("done");
+        w.line(&format!("_argol_finish{suffix}"));
+
+        if !cmd.commands.is_empty() {
+            w.line("case $_argol_sub in");
+            for sub in &cmd.commands {
+                let mut sub_path = path.to_vec();
+                sub_path.push(sub.names[0].clone());
+                w.line(&format!("{})", ident(&sub.names[0])));
+                w.indent();
+                w.line(&format!(
+                    "{}={}",
+                    var("command"),
+                    shell_quote(&sub_path.join(" "))
+                ));
+                w.line(&format!(
+                    "_argol_parse_{} \"$@\"",
+                    ident(&sub_path.join("_"))
+                ));
+                w.line(";;");
+                w.dedent();
+            }
+            w.line("esac");
+        }
+        w.close("}");
+        self.functions.push(w.finish());
+
+        if !cmd.flags.is_empty() {
+            self.flag_fn(cmd, path, &suffix);
+        }
+        self.finish_fn(cmd, &suffix);
+
+        for sub in &cmd.commands {
+            let mut sub_path = path.to_vec();
+            sub_path.push(sub.names[0].clone());
+            self.command(sub, &sub_path);
+        }
+    }
+}
+
+// Names that map to the same variable, where a command also sets its parents' variables and
+// shadows the ones it redeclares
+fn var_clashes(cmd: &Command, mut names: Vec<String>, out: &mut Vec<String>) {
+    let own: Vec<String> = cmd
+        .flags
+        .iter()
+        .filter(|f| f.action.is_none())
+        .map(|f| f.names.primary())
+        .chain(cmd.positionals.iter().map(|p| p.name.clone()))
+        .chain(cmd.named.iter().map(|n| n.names.primary()))
+        .collect();
+    names.retain(|n| !own.contains(n));
+    names.extend(own);
+
+    let vars: Vec<(String, String)> = ["command", "trailing"]
+        .iter()
+        .map(|v| (var(v), format!("the `{v}` variable")))
+        .chain(
+            names
+                .iter()
+                .map(|name| (var(name), format!("argument `{name}`"))),
+        )
+        .collect();
+    for clash in clashes("the script's variables", &vars) {
+        if !out.contains(&clash) {
+            out.push(clash);
+        }
+    }
+    for sub in &cmd.commands {
+        var_clashes(sub, names.clone(), out);
+    }
+}
+
+/// A POSIX shell file defining `argol_parse`, which matches its arguments against `program` and
+/// assigns the results to `ARGOL_*` variables. Fails when spec names would set the same variable
+/// or share a function.
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let mut generator = Generator {
+        program,
+        int: false,
+        uint: false,
+        bool: false,
+        one_of: false,
+        in_range: false,
+        functions: Vec::new(),
+        parsers: Vec::new(),
+        clashes: Vec::new(),
+    };
+    generator.command(&program.cmd, &[]);
+    var_clashes(&program.cmd, Vec::new(), &mut generator.clashes);
+    generator
+        .clashes
+        .extend(clashes("the script's functions", &generator.parsers));
+    if !generator.clashes.is_empty() {
+        return Err(generator.clashes);
+    }
+
+    let mut out = format!("# Generated by argolify from `{source}`; do not edit.\n");
+    out.push_str(
+        "#
+# Source this file and call `argol_parse \"$@\"`. Each argument is assigned to an ARGOL_* variable
+# named after it, and ARGOL_COMMAND holds the subcommand path. Arguments that take several values,
+# and ARGOL_TRAILING, hold quoted lists to expand with `eval \"set -- $ARGOL_NAME\"`.
+#
+# Usage errors exit with status 2, and help and version flags print their text and exit with 0.
+",
+    );
+
+    out.push_str(PRELUDE);
+    let helpers = [
+        (generator.int, INT_HELPER),
+        (generator.uint, UINT_HELPER),
+        (generator.bool, BOOL_HELPER),
+        (generator.one_of, ONE_OF_HELPER),
+        (generator.in_range, IN_RANGE_HELPER),
+    ];
+    for (used, helper) in helpers {
+        if used {
+            out.push_str(helper);
+        }
+    }
+    for function in &generator.functions {
+        out.push('\n');
+        out.push_str(function);
+    }
+    Ok(out)
+}
@@ -0,0 +1,258 @@
+use crate::codegen::Writer;
+use crate::runtime::Output;
+use crate::spec::{Command, Names, Nargs, Options, Program, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command as Process, ExitStatus};
+
+// The generated project compiles these files unchanged, so they must only depend on std and each
+// other
+const SPEC_RS: &str = include_str!("../spec.rs");
+const RUNTIME_RS: &str = include_str!("../runtime.rs");
+const HELP_RS: &str = include_str!("../help.rs");
+const JSON_RS: &str = include_str!("../common/json.rs");
+
+// First line of every generated `src/main.rs`, used to recognise a directory argolify wrote before
+const HEADER: &str = "// Generated by argolify from";
+
+const MAIN_RS: &str = r#"
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let program = program();
+    let result = runtime::match_args(&program, &args);
+    std::process::exit(runtime::report(&program, &result, &output()));
+}
+"#;
+
+/// Turn an arbitrary file name into a valid Cargo package name.
+pub fn package_name(s: &str) -> String {
+    let name: String = s
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches('-');
+    match name.chars().next() {
+        None => "argol-args".into(),
+        Some(c) if c.is_ascii_digit() => format!("argol-{name}"),
+        Some(_) => name.into(),
+    }
+}
+
+fn str_lit(s: &str) -> String {
+    format!("{s:?}.into()")
+}
+
+fn opt_str(s: &Option<String>) -> String {
+    s.as_deref()
+        .map_or("None".into(), |s| format!("Some({})", str_lit(s)))
+}
+
+fn value(v: &Value) -> String {
+    match v {
+        Value::Str(s) => format!("Value::Str({})", str_lit(s)),
+        Value::Int(i) => format!("Value::Int({i})"),
+        Value::Float(x) => format!("Value::Float({x:?})"),
+        Value::Bool(b) => format!("Value::Bool({b})"),
+    }
+}
+
+fn values(vs: &[Value]) -> String {
+    let items: Vec<String> = vs.iter().map(value).collect();
+    format!("vec![{}]", items.join(", "))
+}
+
+fn names(n: &Names) -> String {
+    let long: Vec<String> = n.long.iter().map(|l| str_lit(l)).collect();
+    format!(
+        "Names {{ short: {:?}, long: vec![{}] }}",
+        n.short,
+        long.join(", ")
+    )
+}
+
+fn options(o: &Option<Options>) -> String {
+    let opt_value = |v: &Option<Value>| {
+        v.as_ref()
+            .map_or("None".into(), |v| format!("Some({})", value(v)))
+    };
+    match o {
+        None => "None".into(),
+        Some(Options::Values(vs)) => format!("Some(Options::Values({}))", values(vs)),
+        Some(Options::Range(r)) => format!(
+            "Some(Options::Range(Range {{ start: {}, end: {}, inclusive: {} }}))",
+            opt_value(&r.start),
+            opt_value(&r.end),
+            r.inclusive
+        ),
+    }
+}
+
+fn nargs(n: &Nargs) -> String {
+    format!("Nargs {{ min: {}, max: {:?} }}", n.min, n.max)
+}
+
+fn command(w: &mut Writer, cmd: &Command) {
+    let cmd_names: Vec<String> = cmd.names.iter().map(|n| str_lit(n)).collect();
+    w.line(&format!("names: vec![{}],", cmd_names.join(", ")));
+    w.line(&format!("desc: {},", opt_str(&cmd.desc)));
+
+    if cmd.flags.is_empty() {
+        w.line("flags: vec![],");
+    } else {
+        w.open("flags: vec![");
+        for f in &cmd.flags {
+            w.open("Flag {");
+            w.line(&format!("names: {},", names(&f.names)));
+            w.line(&format!("desc: {},", opt_str(&f.desc)));
+            let action = f
+                .action
+                .map_or("None".into(), |a| format!("Some(Action::{a:?})"));
+            w.line(&format!("action: {action},"));
+            w.close("},");
+        }
+        w.close("],");
+    }
+
+    if cmd.positionals.is_empty() {
+        w.line("positionals: vec![],");
+    } else {
+        w.open("positionals: vec![");
+        for p in &cmd.positionals {
+            w.open("Positional {");
+            w.line(&format!("name: {},", str_lit(&p.name)));
+            w.line(&format!("desc: {},", opt_str(&p.desc)));
+            w.line(&format!("required: {},", p.required));
+            w.line(&format!("default: {},", values(&p.default)));
+            w.line(&format!("ty: ArgType::{:?},", p.ty));
+            w.line(&format!("options: {},", options(&p.options)));
+            w.line(&format!("nargs: {},", nargs(&p.nargs)));
+            w.close("},");
+        }
+        w.close("],");
+    }
+
+    if cmd.named.is_empty() {
+        w.line("named: vec![],");
+    } else {
+        w.open("named: vec![");
+        for n in &cmd.named {
+            w.open("Named {");
+            w.line(&format!("names: {},", names(&n.names)));
+            w.line(&format!("desc: {},", opt_str(&n.desc)));
+            w.line(&format!("required: {},", n.required));
+            w.line(&format!("default: {},", values(&n.default)));
+            w.line(&format!("ty: ArgType::{:?},", n.ty));
+            w.line(&format!("options: {},", options(&n.options)));
+            w.line(&format!("nargs: {},", nargs(&n.nargs)));
+            w.close("},");
+        }
+        w.close("],");
+    }
+
+    if cmd.commands.is_empty() {
+        w.line("commands: vec![],");
+    } else {
+        w.open("commands: vec![");
+        for c in &cmd.commands {
+            w.open("Command {");
+            command(w, c);
+            w.close("},");
+        }
+        w.close("],");
+    }
+}
+
+/// Rust source for a `program()` function that rebuilds `program`.
+pub fn program_fn(program: &Program) -> String {
+    let mut w = Writer::new("    ");
+    w.open("fn program() -> Program {");
+    w.open("Program {");
+    w.line(&format!("name: {},", opt_str(&program.name)));
+    w.line(&format!("version: {},", opt_str(&program.version)));
+    w.open("cmd: Command {");
+    command(&mut w, &program.cmd);
+    w.close("},");
+    w.close("}");
+    w.close("}");
+    w.finish()
+}
+
+fn output_fn(output: &Output) -> String {
+    let expr = match output {
+        Output::Text => "runtime::Output::Text".into(),
+        Output::Json => "runtime::Output::Json".into(),
+        Output::Shell(prefix) => format!("runtime::Output::Shell({})", str_lit(prefix)),
+    };
+    format!("fn output() -> runtime::Output {{\n    {expr}\n}}\n\n")
+}
+
+fn main_rs(program: &Program, source: &str, output: &Output) -> String {
+    format!(
+        "{HEADER} `{source}`; do not edit.\n\n\
+         #![allow(dead_code)]\n\n\
+         mod common {{\n    pub mod json;\n}}\n\
+         mod help;\n\
+         mod runtime;\n\
+         mod spec;\n\n\
+         use spec::*;\n\n\
+         {}{}{MAIN_RS}",
+        output_fn(output),
+        program_fn(program)
+    )
+}
+
+fn cargo_toml(name: &str) -> String {
+    format!(
+        "[package]\n\
+         name = \"{name}\"\n\
+         version = \"0.1.0\"\n\
+         edition = \"2024\"\n\n\
+         [dependencies]\n\n\
+         [workspace]\n"
+    )
+}
+
+/// Whether `emit` may write into `dir`: it is missing, empty or holds an earlier argolify project.
+pub fn can_overwrite(dir: &Path) -> bool {
+    let Ok(mut entries) = fs::read_dir(dir) else {
+        return !dir.exists();
+    };
+    entries.next().is_none()
+        || fs::read_to_string(dir.join("src").join("main.rs")).is_ok_and(|s| s.starts_with(HEADER))
+}
+
+/// Write a dependency-free Cargo project for `program` into `dir`.
+pub fn emit(
+    program: &Program,
+    source: &str,
+    dir: &Path,
+    name: &str,
+    output: &Output,
+) -> io::Result<()> {
+    let src = dir.join("src");
+    fs::create_dir_all(src.join("common"))?;
+    fs::write(dir.join("Cargo.toml"), cargo_toml(name))?;
+    fs::write(dir.join(".gitignore"), "/target\n")?;
+    fs::write(src.join("main.rs"), main_rs(program, source, output))?;
+    fs::write(src.join("spec.rs"), SPEC_RS)?;
+    fs::write(src.join("runtime.rs"), RUNTIME_RS)?;
+    fs::write(src.join("help.rs"), HELP_RS)?;
+    fs::write(src.join("common").join("json.rs"), JSON_RS)?;
+    Ok(())
+}
+
+/// Build the project in `dir` with the local toolchain, without touching the network.
+pub fn build(dir: &Path) -> io::Result<ExitStatus> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    Process::new(cargo)
+        .args(["build", "--release", "--offline", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .status()
+}
@@ -0,0 +1,995 @@
+use crate::codegen::{Writer, clashes, command_origin, pascal, str_lit};
+use crate::help;
+use crate::spec::{Action, ArgType, Command, Nargs, Options, Program, Value};
+
+const LINE_WIDTH: usize = 80;
+
+const HEAD: &str = r#"
+export type Action = "show_help" | "show_version";
+
+/** Returned instead of the arguments when a flag like `--help` is given. */
+export interface ActionRequest {
+  action: Action;
+  /** Path of subcommands given, by their first names. */
+  command: string[];
+  /** Text to print: help for the command, or the program version. */
+  message: string;
+}
+
+export type ErrorKind =
+  | "unknown_option"
+  | "unexpected_argument"
+  | "flag_with_value"
+  | "missing_value"
+  | "too_many_values"
+  | "missing_required"
+  | "invalid_value"
+  | "not_allowed";
+
+/**
+ * A usage error. `kind` and `subject` are those of the error object in
+ * argolify's JSON output.
+ */
+export class ParseError extends Error {
+  readonly kind: ErrorKind;
+  readonly subject: string;
+  readonly command: string[];
+
+  constructor(
+    kind: ErrorKind,
+    subject: string,
+    command: string[],
+    message: string,
+  ) {
+    super(
+      command.length > 0 ? `${message} (in ${command.join(" ")})` : message,
+    );
+    this.name = "ParseError";
+    this.kind = kind;
+    this.subject = subject;
+    this.command = command;
+  }
+}
+
+/**
+ * Match `argv` (without the program name, e.g. `process.argv.slice(2)`)
+ * against the spec. Throws a `ParseError` on usage errors.
+ */
+export function parse(argv: string[]): Args | ActionRequest {
+  const state: State = { command: [], trailing: [], request: null };
+  const args = buildArgs(state, argv);
+  return state.request ?? (args as Args);
+}
+"#;
+
+const SPEC: &str = r#"
+interface Option {
+  key: string;
+  short: string | null;
+  long: string[];
+  flag: boolean;
+  action: Action | null;
+  min: number;
+  /** `null` when there is no limit. */
+  max: number | null;
+}
+
+interface Positional {
+  key: string;
+  min: number;
+  max: number | null;
+}
+
+interface Command {
+  options: Option[];
+  positionals: Positional[];
+  /** First name of each subcommand, by every name. */
+  commands: Map<string, string>;
+  help: string;
+  version: string;
+}
+
+function flag(
+  key: string,
+  short: string | null,
+  long: string[],
+  action: Action | null = null,
+): Option {
+  return { key, short, long, flag: true, action, min: 0, max: 0 };
+}
+"#;
+
+const MATCHER: &str = r#"
+interface State {
+  command: string[];
+  trailing: string[];
+  request: ActionRequest | null;
+}
+
+/** The words given to one command, before they are converted. */
+interface Matched {
+  cmd: Command;
+  path: string[];
+  flags: Set<string>;
+  values: Map<string, string[]>;
+  trailing: string[];
+  /** First word left over before `--`, reported by `leftover`. */
+  unexpected: string | null;
+  sub: string | null;
+  rest: string[];
+}
+
+function fail(
+  m: Matched,
+  kind: ErrorKind,
+  subject: string,
+  message: string,
+): never {
+  throw new ParseError(kind, subject, m.path, message);
+}
+
+function expects(min: number, max: number | null): string {
+  if (min === max) {
+    return `${min}`;
+  }
+  if (max === null) {
+    return min === 0 ? "any number of" : `at least ${min}`;
+  }
+  return `${min} to ${max}`;
+}
+
+const NUMBER = /^[-+]?(\d+\.?\d*|\.\d+)(e[-+]?\d+)?$/i;
+
+/**
+ * Whether s is a finite number; literals that overflow to Infinity do not
+ * count.
+ */
+function isNumber(s: string): boolean {
+  return NUMBER.test(s) && Number.isFinite(Number(s));
+}
+
+/**
+ * A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short
+ * name claims it.
+ */
+function isOption(cmd: Command, arg: string): boolean {
+  if (arg === "--") {
+    return true;
+  }
+  if (arg.length < 2 || !arg.startsWith("-")) {
+    return false;
+  }
+  const first = Array.from(arg.slice(1))[0];
+  const claimed = cmd.options.some((o) => o.short === first);
+  return claimed || !isNumber(arg.slice(1));
+}
+
+/**
+ * Record a flag, and the request when it has an action. Returns whether it had
+ * one.
+ */
+function setFlag(cmd: Command, state: State, m: Matched, opt: Option): boolean {
+  m.flags.add(opt.key);
+  if (opt.action === null) {
+    return false;
+  }
+  const message = opt.action === "show_help" ? cmd.help : cmd.version;
+  state.request = { action: opt.action, command: [...m.path], message };
+  return true;
+}
+
+/**
+ * Collect the values of a named argument: `given` inline ones, then following
+ * words up to the maximum, stopping at anything that looks like an option.
+ * Returns how many words were used.
+ */
+function take(
+  cmd: Command,
+  m: Matched,
+  opt: Option,
+  given: string[],
+  rest: string[],
+): number {
+  let n = 0;
+  while (
+    (opt.max === null || given.length < opt.max) &&
+    n < rest.length &&
+    !isOption(cmd, rest[n])
+  ) {
+    given.push(rest[n]);
+    n++;
+  }
+  if (given.length < opt.min) {
+    const name = `--${opt.key}`;
+    const message = `'${name}' expects ${expects(opt.min, opt.max)} value(s)`;
+    fail(m, "missing_value", name, message);
+  }
+  const previous = opt.max === 1 ? [] : (m.values.get(opt.key) ?? []);
+  m.values.set(opt.key, [...previous, ...given]);
+  return n;
+}
+
+/**
+ * Hand out positional words in order, leaving enough for the minimum of later
+ * positionals. Leftover words are only allowed after `--`, where they are
+ * passed through as trailing; see `leftover`.
+ */
+function finish(cmd: Command, m: Matched, words: string[], dashes: number) {
+  let rest = words;
+  cmd.positionals.forEach((p, i) => {
+    const later = cmd.positionals.slice(i + 1);
+    const reserved = later.reduce((sum, q) => sum + q.min, 0);
+    let n = Math.max(rest.length - reserved, 0);
+    if (p.max !== null) {
+      n = Math.min(n, p.max);
+    }
+    const given = rest.slice(0, n);
+    rest = rest.slice(n);
+    if (given.length > 0 && given.length < p.min) {
+      const message = `'${p.key}' expects ${expects(p.min, p.max)} value(s)`;
+      fail(m, "missing_value", p.key, message);
+    }
+    if (given.length > 0) {
+      m.values.set(p.key, given);
+    }
+  });
+
+  if (rest.length > 0 && (dashes < 0 || words.length - rest.length < dashes)) {
+    m.unexpected = rest[0];
+  }
+  m.trailing = rest;
+}
+
+/** Fail on words left over before `--`, after positionals are converted. */
+function leftover(m: Matched) {
+  if (m.unexpected !== null) {
+    const word = m.unexpected;
+    fail(m, "unexpected_argument", word, `unexpected argument '${word}'`);
+  }
+}
+
+/** Match `argv` against `cmd`, stopping at a subcommand or an action. */
+function match(cmd: Command, state: State, argv: string[]): Matched | null {
+  const m: Matched = {
+    cmd,
+    path: [...state.command],
+    flags: new Set(),
+    values: new Map(),
+    trailing: [],
+    unexpected: null,
+    sub: null,
+    rest: [],
+  };
+  const words: string[] = [];
+  let dashes = -1;
+  for (let i = 0; i < argv.length; i++) {
+    const arg = argv[i];
+    if (dashes >= 0) {
+      words.push(arg);
+    } else if (arg === "--") {
+      dashes = words.length;
+    } else if (arg.startsWith("--")) {
+      const eq = arg.indexOf("=");
+      const name = eq < 0 ? arg.slice(2) : arg.slice(2, eq);
+      const given = eq < 0 ? [] : [arg.slice(eq + 1)];
+      const opt = cmd.options.find((o) => o.long.includes(name));
+      if (opt === undefined) {
+        fail(m, "unknown_option", `--${name}`, `unknown option '--${name}'`);
+      }
+      if (opt.flag && given.length > 0) {
+        const message = `flag '--${name}' does not take a value`;
+        fail(m, "flag_with_value", `--${name}`, message);
+      }
+      if (opt.flag && setFlag(cmd, state, m, opt)) {
+        return null;
+      }
+      if (!opt.flag) {
+        i += take(cmd, m, opt, given, argv.slice(i + 1));
+      }
+    } else if (isOption(cmd, arg)) {
+      const cluster = Array.from(arg.slice(1));
+      for (let j = 0; j < cluster.length; j++) {
+        const opt = cmd.options.find((o) => o.short === cluster[j]);
+        if (opt === undefined) {
+          const name = `-${cluster[j]}`;
+          fail(m, "unknown_option", name, `unknown option '${name}'`);
+        }
+        if (opt.flag) {
+          if (setFlag(cmd, state, m, opt)) {
+            return null;
+          }
+          continue;
+        }
+        const value = cluster.slice(j + 1).join("").replace(/^=/, "");
+        const given = value === "" ? [] : [value];
+        i += take(cmd, m, opt, given, argv.slice(i + 1));
+        break;
+      }
+    } else if (words.length === 0 && cmd.commands.has(arg)) {
+      m.sub = cmd.commands.get(arg) ?? null;
+      m.rest = argv.slice(i + 1);
+      break;
+    } else {
+      words.push(arg);
+    }
+  }
+  finish(cmd, m, words, dashes);
+  return m;
+}
+
+"#;
+
+const VALUES: &str = r#"
+interface Type<T> {
+  name: string;
+  parse(s: string): T | undefined;
+}
+
+/** A set of allowed values, and how they are listed in errors. */
+interface Allowed<T> {
+  test(x: T): boolean;
+  choices: string[];
+}
+
+/** Convert and check the values given for `key`. */
+function values<T>(
+  m: Matched,
+  key: string,
+  type: Type<T>,
+  allowed?: Allowed<T>,
+): T[] {
+  return (m.values.get(key) ?? []).map((s) => {
+    const x = type.parse(s);
+    const invalid = `invalid value '${s}' for '${key}'`;
+    if (x === undefined) {
+      fail(m, "invalid_value", key, `${invalid}: expected ${type.name}`);
+    }
+    if (allowed !== undefined && !allowed.test(x)) {
+      const choices = allowed.choices.join(", ");
+      fail(m, "not_allowed", key, `${invalid} (possible values: ${choices})`);
+    }
+    return x;
+  });
+}
+"#;
+
+const NAMED_HELPER: &str = r#"
+function named(
+  key: string,
+  short: string | null,
+  long: string[],
+  min: number,
+  max: number | null,
+): Option {
+  return { key, short, long, flag: false, action: null, min, max };
+}
+"#;
+
+const POSITIONAL_HELPER: &str = r#"
+function positional(key: string, min: number, max: number | null): Positional {
+  return { key, min, max };
+}
+"#;
+
+const OPTION_VALUES_HELPER: &str = r#"
+/** Like `values`, for a named argument: checks what is left, then how many. */
+function optionValues<T>(
+  m: Matched,
+  key: string,
+  type: Type<T>,
+  allowed?: Allowed<T>,
+): T[] {
+  leftover(m);
+  const opt = m.cmd.options.find((o) => o.key === key && !o.flag);
+  const given = m.values.get(key) ?? [];
+  if (opt !== undefined && opt.max !== null && given.length > opt.max) {
+    const name = `--${key}`;
+    const message = `'${name}' expects ${expects(opt.min, opt.max)} value(s)`;
+    fail(m, "too_many_values", name, message);
+  }
+  return values(m, key, type, allowed);
+}
+"#;
+
+const REQUIRED_HELPER: &str = r#"
+function required<T>(m: Matched, key: string, given: T[]): T[] {
+  if (given.length === 0) {
+    fail(m, "missing_required", key, `missing required argument '${key}'`);
+  }
+  return given;
+}
+"#;
+
+const OR_DEFAULT_HELPER: &str = r#"
+function orDefault<T>(given: T[], fallback: T[]): T[] {
+  return given.length > 0 ? given : fallback;
+}
+"#;
+
+const ONE_OF_HELPER: &str = r#"
+function oneOf<T>(items: T[]): Allowed<T> {
+  return { test: (x) => items.includes(x), choices: items.map(String) };
+}
+"#;
+
+const IN_RANGE_HELPER: &str = r#"
+function inRange(
+  start: number | null,
+  end: number | null,
+  inclusive: boolean,
+  text: string,
+): Allowed<number> {
+  return {
+    test: (x) =>
+      (start === null || x >= start) &&
+      (end === null || (inclusive ? x <= end : x < end)),
+    choices: [text],
+  };
+}
+"#;
+
+const TYPES: [(ArgType, &str); 6] = [
+    (
+        ArgType::Str,
+        r#"const str: Type<string> = { name: "str", parse: (s) => s };"#,
+    ),
+    (
+        ArgType::Int,
+        r#"const int: Type<number> = {
+  name: "int",
+  // Larger whole numbers would silently lose precision
+  parse: (s) => {
+    const x = /^[-+]?\d+$/.test(s) ? Number(s) : NaN;
+    return Number.isSafeInteger(x) ? x : undefined;
+  },
+};"#,
+    ),
+    (
+        ArgType::UInt,
+        r#"const uint: Type<number> = {
+  name: "uint",
+  parse: (s) => {
+    const x = /^\+?\d+$/.test(s) ? Number(s) : NaN;
+    return Number.isSafeInteger(x) ? x : undefined;
+  },
+};"#,
+    ),
+    (
+        ArgType::Float,
+        r#"const float: Type<number> = {
+  name: "float",
+  parse: (s) => (isNumber(s) ? Number(s) : undefined),
+};"#,
+    ),
+    (
+        ArgType::Bool,
+        r#"const bool: Type<boolean> = {
+  name: "bool",
+  parse: (s) => (s === "true" ? true : s === "false" ? false : undefined),
+};"#,
+    ),
+    (
+        ArgType::Path,
+        r#"const path: Type<string> = { name: "path", parse: (s) => s };"#,
+    ),
+];
+
+fn camel(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Properties of `Args` that are not arguments; `message` is `ActionRequest`'s, which `Args`
+/// must stay distinguishable from.
+const RESERVED: [&str; 4] = ["command", "action", "message", "trailing"];
+
+// camelCase property name, quoted when it is not an identifier; in `Args` (`top`), one that
+// would take the name of a reserved property gets an `Arg` suffix
+fn property(name: &str, top: bool) -> String {
+    let camel = camel(&pascal(&[name.to_string()]));
+    if camel.is_empty() || camel.starts_with(|c: char| c.is_ascii_digit()) {
+        str_lit(name)
+    } else if top && RESERVED.contains(&camel.as_str()) {
+        format!("{camel}Arg")
+    } else {
+        camel
+    }
+}
+
+fn interface_name(path: &[String]) -> String {
+    format!("{}Args", pascal(path))
+}
+
+// Named after the command's interface, so it is unique whenever the interface names are
+fn spec_var(path: &[String]) -> String {
+    format!("{}Spec", camel(&interface_name(path)))
+}
+
+fn ts_type(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str | ArgType::Path => "string",
+        ArgType::Int | ArgType::UInt | ArgType::Float => "number",
+        ArgType::Bool => "boolean",
+    }
+}
+
+fn type_var(ty: ArgType) -> &'static str {
+    match ty {
+        ArgType::Str => "str",
+        ArgType::Int => "int",
+        ArgType::UInt => "uint",
+        ArgType::Float => "float",
+        ArgType::Bool => "bool",
+        ArgType::Path => "path",
+    }
+}
+
+fn value_lit(v: &Value) -> String {
+    match v {
+        Value::Str(s) => str_lit(s),
+        Value::Int(i) => i.to_string(),
+        Value::Float(x) => x.to_string(),
+        Value::Bool(b) => b.to_string(),
+    }
+}
+
+fn opt_lit(s: Option<String>) -> String {
+    s.map_or("null".into(), |s| str_lit(&s))
+}
+
+fn max_lit(n: Nargs) -> String {
+    n.max.map_or("null".into(), |m| m.to_string())
+}
+
+fn doc(w: &mut Writer, desc: &Option<String>) {
+    if let Some(desc) = desc {
+        w.line(&format!("/** {} */", desc.replace("*/", "*\\/")));
+    }
+}
+
+// `head[items]tail` on one line if it fits, otherwise one item per line with trailing commas
+fn list(w: &mut Writer, head: &str, open: &str, close: &str, items: &[String], tail: &str) {
+    let line = format!("{head}{open}{}{close}{tail}", items.join(", "));
+    if items.is_empty() || w.indent_width() + line.len() <= LINE_WIDTH {
+        w.line(&line);
+        return;
+    }
+    w.open(&format!("{head}{open}"));
+    for item in items {
+        w.line(&format!("{item},"));
+    }
+    w.close(&format!("{close}{tail}"));
+}
+
+// `key: value,`, with the value on its own line when it does not fit
+fn prop(w: &mut Writer, key: &str, value: &str) {
+    let line = format!("{key}: {value},");
+    if w.indent_width() + line.len() <= LINE_WIDTH {
+        w.line(&line);
+    } else {
+        w.line(&format!("{key}:"));
+        w.indent();
+        w.line(&format!("{value},"));
+        w.dedent();
+    }
+}
+
+// An argument as the generated `build*` function converts it
+struct Arg<'a> {
+    key: String,
+    desc: &'a Option<String>,
+    ty: ArgType,
+    options: &'a Option<Options>,
+    default: &'a [Value],
+    nargs: Nargs,
+    required: bool,
+    named: bool,
+}
+
+impl Arg<'_> {
+    fn multiple(&self) -> bool {
+        self.nargs.max != Some(1)
+    }
+
+    // Property and type for the interface
+    fn member(&self, top: bool) -> String {
+        let ty = ts_type(self.ty);
+        let name = property(&self.key, top);
+        if self.multiple() {
+            format!("{name}: {ty}[];")
+        } else if self.required || !self.default.is_empty() {
+            format!("{name}: {ty};")
+        } else {
+            format!("{name}?: {ty};")
+        }
+    }
+}
+
+struct Generator<'a> {
+    program: &'a Program,
+    types: Vec<ArgType>,
+    named: bool,
+    positional: bool,
+    required: bool,
+    or_default: bool,
+    one_of: bool,
+    in_range: bool,
+    out: Vec<String>,
+    // Interface names, which are declared at module level, with the commands they are for
+    interfaces: Vec<(String, String)>,
+    clashes: Vec<String>,
+}
+
+impl Generator<'_> {
+    // Expression for the value of `arg`'s property
+    fn value(&mut self, arg: &Arg) -> String {
+        if !self.types.contains(&arg.ty) {
+            self.types.push(arg.ty);
+        }
+        let mut call = vec![
+            "m".to_string(),
+            str_lit(&arg.key),
+            type_var(arg.ty).to_string(),
+        ];
+        let numeric = matches!(arg.ty, ArgType::Int | ArgType::UInt | ArgType::Float);
+        match arg.options {
+            Some(Options::Values(vs)) => {
+                self.one_of = true;
+                let items: Vec<String> = vs.iter().map(value_lit).collect();
+                call.push(format!("oneOf([{}])", items.join(", ")));
+            }
+            Some(Options::Range(r)) if numeric => {
+                self.in_range = true;
+                let bound = |v: &Option<Value>| v.as_ref().map_or("null".into(), value_lit);
+                call.push(format!(
+                    "inRange({}, {}, {}, {})",
+                    bound(&r.start),
+                    bound(&r.end),
+                    r.inclusive,
+                    str_lit(&r.to_string())
+                ));
+            }
+            _ => {}
+        }
+        let function = if arg.named { "optionValues" } else { "values" };
+        let mut value = format!("{function}({})", call.join(", "));
+        if arg.required {
+            self.required = true;
+            value = format!("required(m, {}, {value})", str_lit(&arg.key));
+        }
+
+        let defaults: Vec<String> = arg.default.iter().map(value_lit).collect();
+        if arg.multiple() {
+            if !defaults.is_empty() {
+                self.or_default = true;
+                value = format!("orDefault({value}, [{}])", defaults.join(", "));
+            }
+            value
+        } else if let [default] = defaults.as_slice() {
+            format!("{value}[0] ?? {default}")
+        } else {
+            format!("{value}[0]")
+        }
+    }
+
+    fn spec(&mut self, cmd: &Command, path: &[String]) {
+        let mut w = Writer::new("  ");
+        w.open(&format!("const {}: Command = {{", spec_var(path)));
+
+        let mut options = Vec::new();
+        for f in &cmd.flags {
+            let long: Vec<String> = f.names.long.iter().map(|l| str_lit(l)).collect();
+            let mut args = vec![
+                str_lit(&f.names.primary()),
+                opt_lit(f.names.short.map(String::from)),
+                format!("[{}]", long.join(", ")),
+            ];
+            if let Some(action) = f.action {
+                args.push(str_lit(action.as_str()));
+            }
+            options.push(format!("flag({})", args.join(", ")));
+        }
+        self.named |= !cmd.named.is_empty();
+        for n in &cmd.named {
+            let long: Vec<String> = n.names.long.iter().map(|l| str_lit(l)).collect();
+            options.push(format!(
+                "named({}, {}, [{}], {}, {})",
+                str_lit(&n.names.primary()),
+                opt_lit(n.names.short.map(String::from)),
+                long.join(", "),
+                n.nargs.min,
+                max_lit(n.nargs)
+            ));
+        }
+        list(&mut w, "options: ", "[", "]", &options, ",");
+
+        let positionals: Vec<String> = cmd
+            .positionals
+            .iter()
+            .map(|p| {
+                format!(
+                    "positional({}, {}, {})",
+                    str_lit(&p.name),
+                    p.nargs.min,
+                    max_lit(p.nargs)
+                )
+            })
+            .collect();
+        self.positional |= !positionals.is_empty();
+        list(&mut w, "positionals: ", "[", "]", &positionals, ",");
+
+        let commands: Vec<String> = cmd
+            .commands
+            .iter()
+            .flat_map(|sub| {
+                sub.names
+                    .iter()
+                    .map(|name| format!("[{}, {}]", str_lit(name), str_lit(&sub.names[0])))
+            })
+            .collect();
+        if commands.is_empty() {
+            w.line("commands: new Map(),");
+        } else {
+            list(&mut w, "commands: new Map(", "[", "])", &commands, ",");
+        }
+
+        let has = |action| cmd.flags.iter().any(|f| f.action == Some(action));
+        if has(Action::ShowHelp) {
+            let text = help::render(self.program, path, 80);
+            let lines: Vec<String> = text.split_inclusive('\n').map(str_lit).collect();
+            if lines.len() == 1 {
+                prop(&mut w, "help", &lines[0]);
+            } else {
+                w.line("help:");
+                w.indent();
+                let (last, init) = lines.split_last().unwrap();
+                for line in init {
+                    w.line(&format!("{line} +"));
+                }
+                w.line(&format!("{last},"));
+                w.dedent();
+            }
+        } else {
+            w.line("help: \"\",");
+        }
+        if has(Action::ShowVersion) {
+            let version = self.program.version.clone().unwrap_or_default();
+            let text = match &self.program.name {
+                Some(name) => format!("{name} {version}\n"),
+                None => format!("{version}\n"),
+            };
+            prop(&mut w, "version", &str_lit(&text));
+        } else {
+            w.line("version: \"\",");
+        }
+        w.close("};");
+        self.out.push(w.finish());
+    }
+
+    fn command(&mut self, cmd: &Command, path: &[String]) {
+        let name = interface_name(path);
+        self.interfaces.push((name.clone(), command_origin(path)));
+        let property = |name: &str| property(name, path.is_empty());
+        let args: Vec<Arg> = cmd
+            .positionals
+            .iter()
+            .map(|p| Arg {
+                key: p.name.clone(),
+                desc: &p.desc,
+                ty: p.ty,
+                options: &p.options,
+                default: &p.default,
+                nargs: p.nargs,
+                required: p.required,
+                named: false,
+            })
+            .chain(cmd.named.iter().map(|n| Arg {
+                key: n.names.primary(),
+                desc: &n.desc,
+                ty: n.ty,
+                options: &n.options,
+                default: &n.default,
+                nargs: n.nargs,
+                required: n.required,
+                named: true,
+            }))
+            .collect();
+        let flags: Vec<_> = cmd.flags.iter().filter(|f| f.action.is_none()).collect();
+        let subs: Vec<(&Command, Vec<String>)> = cmd
+            .commands
+            .iter()
+            .map(|sub| {
+                let mut sub_path = path.to_vec();
+                sub_path.push(sub.names[0].clone());
+                (sub, sub_path)
+            })
+            .collect();
+
+        let idents: Vec<(String, String)> = flags
+            .iter()
+            .map(|f| f.names.primary())
+            .chain(args.iter().map(|a| a.key.clone()))
+            .map(|key| (property(&key), format!("argument `{key}`")))
+            .chain(subs.iter().map(|(sub, _)| {
+                (
+                    property(&sub.names[0]),
+                    format!("command `{}`", sub.names[0]),
+                )
+            }))
+            .collect();
+        self.clashes.extend(clashes(&format!("`{name}`"), &idents));
+
+        // The interface
+        let mut w = Writer::new("  ");
+        let program = self.program.name.as_deref().unwrap_or("the program");
+        if path.is_empty() {
+            w.line(&format!("/** Arguments matched for {program}. */"));
+        } else {
+            w.line(&format!(
+                "/** Arguments of `{program} {}`. */",
+                path.join(" ")
+            ));
+        }
+        w.open(&format!("export interface {name} {{"));
+        if path.is_empty() {
+            w.line("/** Path of subcommands given, by their first names. */");
+            w.line("command: string[];");
+            w.line("action: null;");
+            w.line("/** Words after `--` that no positional took. */");
+            w.line("trailing: string[];");
+        }
+        for f in &flags {
+            doc(&mut w, &f.desc);
+            w.line(&format!("{}: boolean;", property(&f.names.primary())));
+        }
+        for arg in &args {
+            doc(&mut w, arg.desc);
+            w.line(&arg.member(path.is_empty()));
+        }
+        for (sub, sub_path) in &subs {
+            w.line(&format!(
+                "/** Set when the `{}` command is given. */",
+                sub.names[0]
+            ));
+            w.line(&format!(
+                "{}?: {};",
+                property(&sub.names[0]),
+                interface_name(sub_path)
+            ));
+        }
+        w.close("}");
+        self.out.push(w.finish());
+
+        self.spec(cmd, path);
+
+        // The build function
+        let mut w = Writer::new("  ");
+        let params = ["state: State".to_string(), "argv: string[]".to_string()];
+        let head = format!("function build{name}");
+        let tail = format!(": {name} | undefined {{");
+        list(&mut w, &head, "(", ")", &params, &tail);
+        w.indent();
+        w.line(&format!(
+            "const m = match({}, state, argv);",
+            spec_var(path)
+        ));
+        w.open("if (m === null) {");
+        w.line("return undefined;");
+        w.close("}");
+        w.line("state.trailing.push(...m.trailing);");
+        if path.is_empty() || !flags.is_empty() || !args.is_empty() {
+            w.open(&format!("const args: {name} = {{"));
+            if path.is_empty() {
+                w.line("command: state.command,");
+                w.line("action: null,");
+                w.line("trailing: state.trailing,");
+            }
+            for f in &flags {
+                let key = f.names.primary();
+                let value = format!("m.flags.has({})", str_lit(&key));
+                prop(&mut w, &property(&key), &value);
+            }
+            for arg in &args {
+                let value = self.value(arg);
+                prop(&mut w, &property(&arg.key), &value);
+            }
+            w.close("};");
+        } else {
+            w.line(&format!("const args: {name} = {{}};"));
+        }
+        if cmd.named.is_empty() {
+            w.line("leftover(m);");
+        }
+
+        if !subs.is_empty() {
+            w.open("switch (m.sub) {");
+            for (sub, sub_path) in &subs {
+                w.line(&format!("case {}:", str_lit(&sub.names[0])));
+                w.indent();
+                w.line(&format!("state.command.push({});", str_lit(&sub.names[0])));
+                w.line(&format!(
+                    "args.{} = build{}(state, m.rest);",
+                    property(&sub.names[0]),
+                    interface_name(sub_path)
+                ));
+                w.line("break;");
+                w.dedent();
+            }
+            w.close("}");
+        }
+        w.line("return args;");
+        w.close("}");
+        self.out.push(w.finish());
+
+        for (sub, sub_path) in &subs {
+            self.command(sub, sub_path);
+        }
+    }
+}
+
+/// A TypeScript module exporting an interface per command and `parse(argv)`.
+pub fn generate(program: &Program, source: &str) -> Result<String, Vec<String>> {
+    let mut generator = Generator {
+        program,
+        types: Vec::new(),
+        named: false,
+        positional: false,
+        required: false,
+        or_default: false,
+        one_of: false,
+        in_range: false,
+        out: Vec::new(),
+        interfaces: Vec::new(),
+        clashes: Vec::new(),
+    };
+    generator.command(&program.cmd, &[]);
+    generator
+        .clashes
+        .extend(clashes("the module", &generator.interfaces));
+    if !generator.clashes.is_empty() {
+        return Err(generator.clashes);
+    }
+
+    let mut out = format!("// Generated by argolify from `{source}`; do not edit.\n");
+    out.push_str(HEAD);
+    for item in &generator.out {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out.push_str(SPEC);
+    if generator.named {
+        out.push_str(NAMED_HELPER);
+    }
+    if generator.positional {
+        out.push_str(POSITIONAL_HELPER);
+    }
+    out.push_str(MATCHER);
+    if !generator.types.is_empty() {
+        out.push_str(VALUES);
+    }
+    if generator.named {
+        out.push_str(OPTION_VALUES_HELPER);
+    }
+    for (ty, def) in TYPES {
+        if generator.types.contains(&ty) {
+            out.push('\n');
+            out.push_str(def);
+            out.push('\n');
+        }
+    }
+    let helpers = [
+        (generator.required, REQUIRED_HELPER),
+        (generator.or_default, OR_DEFAULT_HELPER),
+        (generator.one_of, ONE_OF_HELPER),
+        (generator.in_range, IN_RANGE_HELPER),
+    ];
+    for (used, helper) in helpers {
+        if used {
+            out.push_str(helper);
+        }
+    }
+    Ok(out)
+}
@@ -1,5 +1,13 @@
+use crate::common::json::Json;
+use crate::common::lints::{Level, Lint};
+use crate::common::tokens::{
+    ACTIONS, BOOLEANS, DIRECTIVES, KEYWORDS, Span, TYPES, TokenKind, allowed_keys,
+    fmt_assignment_kinds, is_known_key,
+};
+use crate::common::util::suggest;
+use crate::spec::{ArgType, Nargs, Options};
 use colored::Colorize;
-use std::{collections::HashSet, fmt};
+use std::collections::HashSet;
 
 fn fmt_loc(line: usize, col: usize, path: &str) -> String {
     let loc = format!("({line}:{col})").yellow().bold();
@@ -8,43 +16,247 @@ fn fmt_loc(line: usize, col: usize, path: &str) -> String {
     format!("{loc} {path}")
 }
 
-fn fmt_msg(prefix: &str, msg: String, loc: &Loc, is_tip: bool) -> String {
-    let prefix = if is_tip {
-        prefix.cyan().bold()
+fn fmt_msg(
+    source: &str,
+    prefix: &str,
+    code: &str,
+    msg: String,
+    loc: &Loc,
+    notes: &[(&Loc, &str)],
+    help: Option<String>,
+) -> String {
+    let is_warning = prefix == Severity::Warning.as_str();
+    let prefix = format!("[{prefix} {code}]");
+    let prefix = if is_warning {
+        prefix.yellow().bold()
     } else {
         prefix.red().bold()
     };
-    let arrow = if is_tip { "⤷".cyan() } else { "⤷".red() };
+    let arrow = if is_warning {
+        "⤷".yellow()
+    } else {
+        "⤷".red()
+    };
+    let snippet = fmt_snippet(source, loc, notes, is_warning);
+    let width = notes
+        .iter()
+        .map(|(l, _)| l.line)
+        .fold(loc.line, usize::max)
+        .to_string()
+        .len();
+    let help = match help {
+        Some(h) => format!(
+            "\n{} {} {} {h}",
+            " ".repeat(width),
+            "=".blue().bold(),
+            "tip:".cyan().bold()
+        ),
+        None => String::new(),
+    };
     let loc = fmt_loc(loc.line, loc.col, &loc.path);
 
-    format!("{prefix} {loc}\n {arrow} {msg}")
+    format!("{prefix} {loc}\n {arrow} {msg}{snippet}{help}")
 }
 
-fn fmt_semantic_err(msg: String, loc: &Loc) -> String {
-    fmt_msg("[semantic error]", msg, loc, false)
+// Render the line(s) of `source` a diagnostic refers to, underlining the primary span with `^` and
+// any related spans (e.g. an earlier definition) with `-`
+fn fmt_snippet(source: &str, loc: &Loc, notes: &[(&Loc, &str)], is_warning: bool) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut labels: Vec<(&Loc, &str, bool)> = vec![(loc, "", true)];
+    labels.extend(notes.iter().map(|(l, note)| (*l, *note, false)));
+    labels.retain(|(l, _, _)| l.line >= 1 && l.line <= lines.len());
+    if labels.is_empty() {
+        return String::new();
+    }
+    labels.sort_by_key(|(l, _, primary)| (l.line, !primary));
+
+    let width = labels
+        .iter()
+        .map(|(l, _, _)| l.line)
+        .max()
+        .unwrap()
+        .to_string()
+        .len();
+    let gutter = format!("{} |", " ".repeat(width)).blue().bold();
+    let mut out = format!("\n{gutter}");
+    let mut prev_line = 0;
+
+    for (l, note, primary) in labels {
+        if l.line != prev_line {
+            let src = lines[l.line - 1].replace('\t', " ");
+            let num = format!("{:>width$} |", l.line).blue().bold();
+            out.push_str(&format!("\n{num} {src}"));
+            prev_line = l.line;
+        }
+
+        let pad = " ".repeat(l.col.saturating_sub(1));
+        let marks = if primary {
+            let marks = "^".repeat(l.len.max(1));
+            if is_warning {
+                marks.yellow().bold()
+            } else {
+                marks.red().bold()
+            }
+        } else {
+            "-".repeat(l.len.max(1)).blue().bold()
+        };
+        let note = if note.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", note.blue())
+        };
+        out.push_str(&format!("\n{gutter} {pad}{marks}{note}"));
+    }
+
+    out
 }
 
-fn fmt_syntax_err(msg: String, loc: &Loc) -> String {
-    fmt_msg("[syntax error]", msg, loc, false)
+// Bare words a value could have been meant as, narrowed by the key when it only takes one kind
+fn value_candidates(key: &str) -> &'static [&'static str] {
+    match key {
+        "type" => &TYPES,
+        "action" => &ACTIONS,
+        "required" => &BOOLEANS,
+        _ => &[
+            "string",
+            "int",
+            "uint",
+            "float",
+            "bool",
+            "path",
+            "show_help",
+            "show_version",
+            "true",
+            "false",
+        ],
+    }
 }
 
-fn fmt_semantic_tip(msg: String, loc: &Loc) -> String {
-    fmt_msg("[semantic tip]", msg, loc, true)
+fn loc_fields(loc: &Loc) -> [(&'static str, Json); 4] {
+    [
+        ("path", Json::str(&loc.path)),
+        ("line", Json::uint(loc.line)),
+        ("col", Json::uint(loc.col)),
+        (
+            "span",
+            Json::object([
+                ("start", Json::uint(loc.span.start)),
+                ("end", Json::uint(loc.span.end)),
+            ]),
+        ),
+    ]
 }
 
-pub fn print_semantic_tip(loc: &Loc, s: String) {
-    println!("{}", fmt_semantic_tip(s, loc))
+fn diagnostic_json(
+    severity: Severity,
+    code: &str,
+    msg: &str,
+    loc: &Loc,
+    notes: &[(&Loc, &str)],
+    help: Option<String>,
+) -> Json {
+    let notes = notes
+        .iter()
+        .map(|(l, note)| {
+            let mut fields = vec![("message".to_string(), Json::str(*note))];
+            fields.extend(loc_fields(l).map(|(k, v)| (k.to_string(), v)));
+            Json::Object(fields)
+        })
+        .collect();
+
+    let mut fields = vec![
+        ("severity".to_string(), Json::str(severity.as_str())),
+        ("code".to_string(), Json::str(code)),
+        ("message".to_string(), Json::str(msg)),
+    ];
+    fields.extend(loc_fields(loc).map(|(k, v)| (k.to_string(), v)));
+    fields.push(("notes".to_string(), Json::Array(notes)));
+    fields.push(("help".to_string(), Json::opt_str(help.as_deref())));
+    Json::Object(fields)
 }
 
 pub fn print_err(s: &str) {
     eprintln!("{} {}", "[error]".red().bold(), s);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Loc {
     pub path: String,
     pub line: usize,
     pub col: usize,
+    pub len: usize,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub lint: Lint,
+    pub level: Level,
+    pub loc: Loc,
+    pub msg: String,
+}
+
+impl Warning {
+    pub fn new(lint: Lint, loc: Loc, msg: String) -> Self {
+        Warning {
+            lint,
+            level: lint.default_level(),
+            loc,
+            msg,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self.level {
+            Level::Deny => Severity::Error,
+            Level::Allow | Level::Warn => Severity::Warning,
+        }
+    }
+
+    fn help(&self) -> String {
+        let pragma = format!("# argol: allow({})", self.lint.name());
+        format!("silence with {}", pragma.bright_black())
+    }
+
+    pub fn to_json(&self) -> Json {
+        diagnostic_json(
+            self.severity(),
+            self.lint.code(),
+            &self.msg,
+            &self.loc,
+            &[],
+            Some(self.help()),
+        )
+    }
+
+    /// The human-readable report, quoting the lines of `source` it points at.
+    pub fn render(&self, source: &str) -> String {
+        fmt_msg(
+            source,
+            self.severity().as_str(),
+            self.lint.code(),
+            self.msg.clone(),
+            &self.loc,
+            &[],
+            Some(self.help()),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -63,34 +275,257 @@ pub enum SyntaxError {
     NoFloatExp(Loc, String),
     InvalidNumberSign(Loc, String),
     InvalidStandaloneSymbol(Loc, String),
+    UnknownKeyword(Loc, String),
+    UnknownValue(Loc, String, String),
 }
 
 #[derive(Debug)]
 pub enum SemanticError {
-    ExtraShortName(Loc, HashSet<String>),
-    DuplicateArgNames(Loc, HashSet<String>),
+    ExtraShortName(Loc, Box<Loc>, HashSet<String>),
+    DuplicateArgNames(Loc, Box<Loc>, HashSet<String>),
     CannotNest(Loc, String),
+    DuplicateKey(Loc, Box<Loc>, String),
+    InvalidKey(Loc, String, String),
+    MultipleValues(Loc, String),
+    InvalidValueKind(Loc, String, Vec<TokenKind>, TokenKind),
+    InvalidNargs(Loc, String),
+    NumberOutOfRange(Loc, String),
+    TypeMismatch(Loc, ArgType, String),
+    NegativeUnsigned(Loc, String),
+    MissingVersion(Loc),
+    DuplicateSiblingName(Loc, Box<Loc>, String),
+    DefaultNotAllowed(Loc, String, Options),
+    DefaultCount(Loc, usize, Nargs),
 }
 
 #[derive(Debug)]
 pub enum ParseError {
-    Syntax(SyntaxError),
-    Semantic(SemanticError),
+    Syntax(Box<SyntaxError>),
+    Semantic(Box<SemanticError>),
+}
+
+impl ParseError {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Self::Syntax(err) => err.loc(),
+            Self::Semantic(err) => err.loc(),
+        }
+    }
+
+    pub fn loc_mut(&mut self) -> &mut Loc {
+        match self {
+            Self::Syntax(err) => err.loc_mut(),
+            Self::Semantic(err) => err.loc_mut(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Syntax(err) => err.code(),
+            Self::Semantic(err) => err.code(),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::Syntax(err) => err.message(),
+            Self::Semantic(err) => err.parts().0,
+        }
+    }
+
+    pub fn notes(&self) -> Vec<(&Loc, &str)> {
+        match self {
+            Self::Syntax(_) => Vec::new(),
+            Self::Semantic(err) => err.parts().1,
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        let (word, candidates): (&str, &[&str]) = match self {
+            Self::Syntax(err) => match &**err {
+                SyntaxError::InvalidDirective(_, s) => (s, &DIRECTIVES),
+                SyntaxError::UnknownKeyword(_, s) => (s, &KEYWORDS),
+                SyntaxError::UnknownValue(_, key, s) => (s, value_candidates(key)),
+                _ => return None,
+            },
+            Self::Semantic(err) => match &**err {
+                SemanticError::InvalidKey(_, kw, key) if !is_known_key(key) => {
+                    (key, allowed_keys(kw))
+                }
+                SemanticError::MissingVersion(_) => {
+                    let directive = "!version = \"...\";".blue();
+                    return Some(format!("add {directive} at the top level"));
+                }
+                _ => return None,
+            },
+        };
+
+        suggest(word, candidates).map(|s| format!("did you mean {}?", s.green()))
+    }
+
+    pub fn to_json(&self) -> Json {
+        let notes = self.notes();
+        diagnostic_json(
+            Severity::Error,
+            self.code(),
+            &self.message(),
+            self.loc(),
+            &notes,
+            self.help(),
+        )
+    }
+}
+
+impl SyntaxError {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Self::InvalidChar(loc, _)
+            | Self::InvalidDirective(loc, _)
+            | Self::InvalidRange(loc, _)
+            | Self::InvalidStrEsc(loc, _)
+            | Self::InvalidIdent(loc, _)
+            | Self::ExtraDecimalPoint(loc, _)
+            | Self::InvalidFloatExpChars(_, loc, _)
+            | Self::InvalidNumChars(_, _, loc, _)
+            | Self::UnterminatedStr(loc, _)
+            | Self::UnexpectedToken(loc, _)
+            | Self::NoToken(loc, _)
+            | Self::NoFloatExp(loc, _)
+            | Self::InvalidNumberSign(loc, _)
+            | Self::InvalidStandaloneSymbol(loc, _)
+            | Self::UnknownKeyword(loc, _)
+            | Self::UnknownValue(loc, _, _) => loc,
+        }
+    }
+
+    pub fn loc_mut(&mut self) -> &mut Loc {
+        match self {
+            Self::InvalidChar(loc, _)
+            | Self::InvalidDirective(loc, _)
+            | Self::InvalidRange(loc, _)
+            | Self::InvalidStrEsc(loc, _)
+            | Self::InvalidIdent(loc, _)
+            | Self::ExtraDecimalPoint(loc, _)
+            | Self::InvalidFloatExpChars(_, loc, _)
+            | Self::InvalidNumChars(_, _, loc, _)
+            | Self::UnterminatedStr(loc, _)
+            | Self::UnexpectedToken(loc, _)
+            | Self::NoToken(loc, _)
+            | Self::NoFloatExp(loc, _)
+            | Self::InvalidNumberSign(loc, _)
+            | Self::InvalidStandaloneSymbol(loc, _)
+            | Self::UnknownKeyword(loc, _)
+            | Self::UnknownValue(loc, _, _) => loc,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidChar(..) => "A0101",
+            Self::InvalidDirective(..) => "A0102",
+            Self::InvalidRange(..) => "A0103",
+            Self::InvalidStrEsc(..) => "A0104",
+            Self::InvalidIdent(..) => "A0105",
+            Self::ExtraDecimalPoint(..) => "A0106",
+            Self::InvalidFloatExpChars(..) => "A0107",
+            Self::InvalidNumChars(..) => "A0108",
+            Self::UnterminatedStr(..) => "A0109",
+            Self::UnexpectedToken(..) => "A0110",
+            Self::NoToken(..) => "A0111",
+            Self::NoFloatExp(..) => "A0112",
+            Self::InvalidNumberSign(..) => "A0113",
+            Self::InvalidStandaloneSymbol(..) => "A0114",
+            Self::UnknownKeyword(..) => "A0115",
+            Self::UnknownValue(..) => "A0116",
+        }
+    }
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl SemanticError {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Self::ExtraShortName(loc, _, _)
+            | Self::DuplicateArgNames(loc, _, _)
+            | Self::CannotNest(loc, _)
+            | Self::DuplicateKey(loc, _, _)
+            | Self::InvalidKey(loc, _, _)
+            | Self::MultipleValues(loc, _)
+            | Self::InvalidValueKind(loc, _, _, _)
+            | Self::InvalidNargs(loc, _)
+            | Self::NumberOutOfRange(loc, _)
+            | Self::TypeMismatch(loc, _, _)
+            | Self::NegativeUnsigned(loc, _)
+            | Self::MissingVersion(loc)
+            | Self::DuplicateSiblingName(loc, _, _)
+            | Self::DefaultNotAllowed(loc, _, _)
+            | Self::DefaultCount(loc, _, _) => loc,
+        }
+    }
+
+    pub fn loc_mut(&mut self) -> &mut Loc {
+        match self {
+            Self::ExtraShortName(loc, _, _)
+            | Self::DuplicateArgNames(loc, _, _)
+            | Self::CannotNest(loc, _)
+            | Self::DuplicateKey(loc, _, _)
+            | Self::InvalidKey(loc, _, _)
+            | Self::MultipleValues(loc, _)
+            | Self::InvalidValueKind(loc, _, _, _)
+            | Self::InvalidNargs(loc, _)
+            | Self::NumberOutOfRange(loc, _)
+            | Self::TypeMismatch(loc, _, _)
+            | Self::NegativeUnsigned(loc, _)
+            | Self::MissingVersion(loc)
+            | Self::DuplicateSiblingName(loc, _, _)
+            | Self::DefaultNotAllowed(loc, _, _)
+            | Self::DefaultCount(loc, _, _) => loc,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
         match self {
-            Self::Semantic(err) => write!(f, "{err}"),
-            Self::Syntax(err) => write!(f, "{err}"),
+            Self::ExtraShortName(..) => "A0201",
+            Self::DuplicateArgNames(..) => "A0202",
+            Self::CannotNest(..) => "A0203",
+            Self::DuplicateKey(..) => "A0204",
+            Self::InvalidKey(..) => "A0205",
+            Self::MultipleValues(..) => "A0206",
+            Self::InvalidValueKind(..) => "A0207",
+            Self::InvalidNargs(..) => "A0208",
+            Self::NumberOutOfRange(..) => "A0209",
+            Self::TypeMismatch(..) => "A0210",
+            Self::NegativeUnsigned(..) => "A0211",
+            Self::MissingVersion(..) => "A0212",
+            Self::DuplicateSiblingName(..) => "A0213",
+            Self::DefaultNotAllowed(..) => "A0214",
+            Self::DefaultCount(..) => "A0215",
         }
     }
 }
 
-impl fmt::Display for SemanticError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl ParseError {
+    /// The human-readable report, quoting the lines of `source` it points at.
+    pub fn render(&self, source: &str) -> String {
+        let prefix = match self {
+            Self::Syntax(_) => "syntax error",
+            Self::Semantic(_) => "semantic error",
+        };
+        fmt_msg(
+            source,
+            prefix,
+            self.code(),
+            self.message(),
+            self.loc(),
+            &self.notes(),
+            self.help(),
+        )
+    }
+}
+
+impl SemanticError {
+    fn parts(&self) -> (String, Vec<(&Loc, &str)>) {
         match self {
-            Self::ExtraShortName(loc, hs) => {
+            Self::ExtraShortName(_, first, hs) => {
                 let colored_strs: Vec<String> = hs.iter().map(|s| s.blue().to_string()).collect();
                 let joined_msg = colored_strs.join(" / ");
                 let msg = format!(
@@ -99,11 +534,10 @@ impl fmt::Display for SemanticError {
                     "->".bold(),
                     joined_msg
                 );
-                let fmted = fmt_semantic_err(msg, loc);
-                write!(f, "{fmted}")
+                (msg, vec![(first, "first short name")])
             }
 
-            Self::DuplicateArgNames(loc, hs) => {
+            Self::DuplicateArgNames(_, first, hs) => {
                 let colored_str: Vec<String> = hs.iter().map(|s| s.blue().to_string()).collect();
                 let joined_msg = colored_str.join(" / ");
                 let msg = format!(
@@ -112,132 +546,249 @@ impl fmt::Display for SemanticError {
                     "->".bold(),
                     joined_msg
                 );
-                let fmted = fmt_semantic_err(msg, loc);
-                write!(f, "{fmted}")
+                (msg, vec![(first, "first used here")])
             }
 
-            Self::CannotNest(loc, s) => {
+            Self::CannotNest(_, s) => {
                 let msg = format!(
                     "{} blocks cannot contain nested blocks (only {})",
                     s.bright_magenta(),
                     "command".bright_magenta()
                 );
 
-                let fmted = fmt_semantic_err(msg, loc);
-                write!(f, "{fmted}")
+                (msg, Vec::new())
             }
-        }
-    }
-}
 
-impl fmt::Display for SyntaxError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let (msg, loc) = match self {
-            Self::InvalidChar(loc, ch) => (
-                format!("Illegal character {} {}", "->".bold(), ch.to_string().red()),
-                loc,
-            ),
+            Self::DuplicateKey(_, first, key) => {
+                let msg = format!("Duplicate assignment {} {}", "->".bold(), key.blue());
+                (msg, vec![(first, "first assigned here")])
+            }
 
-            Self::InvalidDirective(loc, s) => (
-                format!("Invalid directive {} {}", "->".bold(), s.blue()),
-                loc,
-            ),
+            Self::InvalidKey(_, kw, key) => {
+                let allowed: Vec<String> = allowed_keys(kw)
+                    .iter()
+                    .map(|k| k.blue().to_string())
+                    .collect();
+                let block = if kw == "root" {
+                    "at top level".to_string()
+                } else {
+                    format!("in {} block", kw.bright_magenta())
+                };
+                let msg = if is_known_key(key) {
+                    format!(
+                        "Key {} is not allowed {block} (allowed: {})",
+                        key.blue(),
+                        allowed.join(", ")
+                    )
+                } else {
+                    format!(
+                        "Unknown key {block} {} {} (allowed: {})",
+                        "->".bold(),
+                        key.blue(),
+                        allowed.join(", ")
+                    )
+                };
+                (msg, Vec::new())
+            }
 
-            Self::InvalidRange(loc, s) => (
-                format!("Invalid range syntax {} {}", "->".bold(), s.yellow()),
-                loc,
-            ),
+            Self::MultipleValues(_, key) => {
+                let msg = format!(
+                    "Assignment only accepts a single value {} {}",
+                    "->".bold(),
+                    key.blue()
+                );
+                (msg, Vec::new())
+            }
 
-            Self::InvalidStrEsc(loc, ch) => (
-                format!(
-                    "Invalid string escape sequence {} {}",
+            Self::InvalidValueKind(_, key, expected, got) => {
+                let expected: Vec<String> = expected.iter().map(|k| k.to_string()).collect();
+                let msg = format!(
+                    "Expected {} for {}, but got {}",
+                    expected.join(" / "),
+                    key.blue(),
+                    got
+                );
+                (msg, Vec::new())
+            }
+
+            Self::InvalidNargs(_, s) => {
+                let msg = format!(
+                    "Invalid {} (expected a non-empty count or range of counts) {} {}",
+                    "nargs".blue(),
                     "->".bold(),
-                    format!("\\{ch}").red()
-                ),
-                loc,
-            ),
+                    s.yellow()
+                );
+                (msg, Vec::new())
+            }
+
+            Self::NumberOutOfRange(_, s) => {
+                let msg = format!("Number literal out of range {} {}", "->".bold(), s.yellow());
+                (msg, Vec::new())
+            }
 
-            Self::InvalidIdent(loc, s) => (
-                format!(
-                    "Identifier cannot start or end with {} {} {}",
-                    "-".red(),
+            Self::TypeMismatch(_, ty, s) => {
+                let msg = format!(
+                    "Value is not compatible with type {} {} {}",
+                    ty.to_string().bright_cyan(),
                     "->".bold(),
-                    s.blue()
-                ),
-                loc,
-            ),
+                    s.yellow()
+                );
+                (msg, Vec::new())
+            }
 
-            Self::ExtraDecimalPoint(loc, s) => (
-                format!(
-                    "Float literal cannot contain > 1 decimal point {} {}",
+            Self::NegativeUnsigned(_, s) => {
+                let msg = format!(
+                    "Negative value for type {} {} {}",
+                    "uint".bright_cyan(),
                     "->".bold(),
                     s.yellow()
-                ),
-                loc,
-            ),
+                );
+                (msg, Vec::new())
+            }
 
-            Self::NoFloatExp(loc, s) => (
-                format!("Missing float exponent {} {}", "->".bold(), s.yellow()),
-                loc,
-            ),
+            Self::MissingVersion(_) => {
+                let msg = format!(
+                    "Action {} requires a {} directive",
+                    "show_version".yellow(),
+                    "!version".blue()
+                );
+                (msg, Vec::new())
+            }
 
-            Self::InvalidFloatExpChars(chars, loc, s) => (
-                format!(
-                    "Float exponent contains illegal character(s): {} {} {}",
-                    chars
-                        .chars()
-                        .map(|c| c.to_string().bright_red().to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
+            Self::DuplicateSiblingName(_, first, name) => {
+                let msg = format!(
+                    "Name is already used by another block in this command {} {}",
                     "->".bold(),
-                    s.yellow(),
-                ),
-                loc,
-            ),
+                    name.blue()
+                );
+                (msg, vec![(first, "first used here")])
+            }
 
-            Self::InvalidNumChars(float_or_int, chars, loc, s) => (
-                format!(
-                    "{} contains illegal character(s): {} {} {}",
-                    float_or_int,
-                    chars
-                        .chars()
-                        .map(|c| c.to_string().bright_red().to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
+            Self::DefaultNotAllowed(_, s, options) => {
+                let allowed = match options {
+                    Options::Values(vs) => vs.iter().map(|v| v.to_string()).collect(),
+                    Options::Range(r) => vec![r.to_string()],
+                };
+                let msg = format!(
+                    "Default value is not allowed by {} (allowed: {}) {} {}",
+                    "options".blue(),
+                    allowed.join(", "),
                     "->".bold(),
                     s.yellow()
-                ),
-                loc,
+                );
+                (msg, Vec::new())
+            }
+
+            Self::DefaultCount(_, n, nargs) => {
+                let expected = match nargs.max {
+                    Some(max) if max == nargs.min => format!("exactly {max}"),
+                    Some(max) => format!("{} to {max}", nargs.min),
+                    None => format!("at least {}", nargs.min),
+                };
+                let msg = format!(
+                    "{} takes {expected} value(s), but {} has {}",
+                    "nargs".blue(),
+                    "default".blue(),
+                    n.to_string().yellow()
+                );
+                (msg, Vec::new())
+            }
+        }
+    }
+}
+
+impl SyntaxError {
+    fn message(&self) -> String {
+        match self {
+            Self::InvalidChar(_, ch) => {
+                format!("Illegal character {} {}", "->".bold(), ch.to_string().red())
+            }
+
+            Self::InvalidDirective(_, s) => {
+                format!("Invalid directive {} {}", "->".bold(), s.blue())
+            }
+
+            Self::InvalidRange(_, s) => {
+                format!("Invalid range syntax {} {}", "->".bold(), s.yellow())
+            }
+
+            Self::InvalidStrEsc(_, ch) => format!(
+                "Invalid string escape sequence {} {}",
+                "->".bold(),
+                format!("\\{ch}").red()
             ),
 
-            Self::UnterminatedStr(loc, s) => (
-                format!(
-                    "Unterminated string literal {} {}",
-                    "->".bold(),
-                    format!("\"{s}").green()
-                ),
-                loc,
+            Self::InvalidIdent(_, s) => format!(
+                "Identifier cannot start or end with {} {} {}",
+                "-".red(),
+                "->".bold(),
+                s.blue()
+            ),
+
+            Self::ExtraDecimalPoint(_, s) => format!(
+                "Float literal cannot contain > 1 decimal point {} {}",
+                "->".bold(),
+                s.yellow()
             ),
 
-            Self::UnexpectedToken(loc, s) => (s.into(), loc),
+            Self::NoFloatExp(_, s) => {
+                format!("Missing float exponent {} {}", "->".bold(), s.yellow())
+            }
 
-            Self::NoToken(loc, s) => (s.into(), loc),
+            Self::InvalidFloatExpChars(chars, _, s) => format!(
+                "Float exponent contains illegal character(s): {} {} {}",
+                chars
+                    .chars()
+                    .map(|c| c.to_string().bright_red().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                "->".bold(),
+                s.yellow(),
+            ),
 
-            Self::InvalidNumberSign(loc, s) => (
-                format!("Multiple signs found {} {}", "->".bold(), s.yellow()),
-                loc,
+            Self::InvalidNumChars(float_or_int, chars, _, s) => format!(
+                "{} contains illegal character(s): {} {} {}",
+                float_or_int,
+                chars
+                    .chars()
+                    .map(|c| c.to_string().bright_red().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                "->".bold(),
+                s.yellow()
             ),
 
-            Self::InvalidStandaloneSymbol(loc, s) => (
-                format!(
-                    "Invalid standalone symbol(s) {} {}",
-                    "->".bold(),
-                    s.yellow()
-                ),
-                loc,
+            Self::UnterminatedStr(_, s) => format!(
+                "Unterminated string literal {} {}",
+                "->".bold(),
+                format!("\"{s}").green()
             ),
-        };
 
-        write!(f, "{}", fmt_syntax_err(msg, loc))
+            Self::UnexpectedToken(_, s) => s.clone(),
+
+            Self::NoToken(_, s) => s.clone(),
+
+            Self::InvalidNumberSign(_, s) => {
+                format!("Multiple signs found {} {}", "->".bold(), s.yellow())
+            }
+
+            Self::InvalidStandaloneSymbol(_, s) => format!(
+                "Invalid standalone symbol(s) {} {}",
+                "->".bold(),
+                s.yellow()
+            ),
+
+            Self::UnknownKeyword(_, s) => {
+                format!("Unknown keyword {} {}", "->".bold(), s.bright_magenta())
+            }
+
+            Self::UnknownValue(_, key, s) => format!(
+                "Unknown value for {} {} {} {}",
+                key.blue(),
+                "->".bold(),
+                s.yellow(),
+                fmt_assignment_kinds()
+            ),
+        }
     }
 }
@@ -0,0 +1,100 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<const N: usize>(fields: [(&str, Json); N]) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    pub fn str(s: impl Into<String>) -> Json {
+        Json::Str(s.into())
+    }
+
+    pub fn uint(n: usize) -> Json {
+        Json::Int(n as i64)
+    }
+
+    pub fn opt_str(s: Option<&str>) -> Json {
+        s.map_or(Json::Null, Json::str)
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+// Compact by default; `{:#}` pretty-prints with two-space indentation
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pretty = f.alternate();
+        let indent = f.width().unwrap_or(0);
+        let (nl, pad, inner, sep) = if pretty {
+            ("\n", " ".repeat(indent), " ".repeat(indent + 2), ": ")
+        } else {
+            ("", String::new(), String::new(), ":")
+        };
+
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Int(i) => write!(f, "{i}"),
+            Json::Float(x) if x.is_finite() => write!(f, "{x:?}"),
+            Json::Float(_) => write!(f, "null"),
+            Json::Str(s) => write_str(f, s),
+            Json::Array(items) if items.is_empty() => write!(f, "[]"),
+            Json::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Json::Array(items) => {
+                write!(f, "[{nl}")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",{nl}")?;
+                    }
+                    if pretty {
+                        write!(f, "{inner}{item:#w$}", w = indent + 2)?;
+                    } else {
+                        write!(f, "{item}")?;
+                    }
+                }
+                write!(f, "{nl}{pad}]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{{nl}")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",{nl}")?;
+                    }
+                    write!(f, "{inner}")?;
+                    write_str(f, k)?;
+                    if pretty {
+                        write!(f, "{sep}{v:#w$}", w = indent + 2)?;
+                    } else {
+                        write!(f, "{sep}{v}")?;
+                    }
+                }
+                write!(f, "{nl}{pad}}}")
+            }
+        }
+    }
+}
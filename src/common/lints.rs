@@ -0,0 +1,117 @@
+use crate::common::errors::{Loc, Warning};
+use crate::common::tokens::Span;
+use crate::common::util::suggest;
+use colored::Colorize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    RedundantComma,
+    EmptyBlock,
+    UnknownPragma,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 3] = [Lint::RedundantComma, Lint::EmptyBlock, Lint::UnknownPragma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::RedundantComma => "redundant-comma",
+            Lint::EmptyBlock => "empty-block",
+            Lint::UnknownPragma => "unknown-pragma",
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::RedundantComma => "A0301",
+            Lint::EmptyBlock => "A0302",
+            Lint::UnknownPragma => "A0303",
+        }
+    }
+
+    pub fn default_level(&self) -> Level {
+        match self {
+            Lint::RedundantComma | Lint::EmptyBlock | Lint::UnknownPragma => Level::Warn,
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|l| l.name() == s)
+    }
+}
+
+// Parse a `# argol: allow(a, b)` line, returning each lint name with its byte offset into the line
+fn parse_pragma(line: &str) -> Option<Vec<(&str, usize)>> {
+    let comment = line.trim_start().strip_prefix('#')?;
+    let body = comment.trim_start().strip_prefix("argol:")?.trim_start();
+    let list = body.strip_prefix("allow(")?.trim_end().strip_suffix(')')?;
+
+    let mut offset = line.find("allow(")? + "allow(".len();
+    let mut names = Vec::new();
+    for part in list.split(',') {
+        let name = part.trim();
+        if !name.is_empty() {
+            names.push((name, offset + part.find(name).unwrap()));
+        }
+        offset += part.len() + 1;
+    }
+
+    Some(names)
+}
+
+/// Collect `# argol: allow(...)` pragmas. A pragma applies to the next line that is neither blank
+/// nor a comment, keyed here by that line's number. Unknown lint names are reported as warnings.
+pub fn pragmas(content: &str, path: &str) -> (HashMap<usize, Vec<Lint>>, Vec<Warning>) {
+    let mut allowed: HashMap<usize, Vec<Lint>> = HashMap::new();
+    let mut warnings = Vec::new();
+    let mut pending: Vec<Lint> = Vec::new();
+    let mut line_start = 0;
+
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            for (name, offset) in parse_pragma(line).unwrap_or_default() {
+                if let Some(lint) = Lint::from_name(name) {
+                    pending.push(lint);
+                    continue;
+                }
+
+                let loc = Loc {
+                    path: path.to_string(),
+                    line: i + 1,
+                    col: line[..offset].chars().count() + 1,
+                    len: name.chars().count(),
+                    span: Span {
+                        start: line_start + offset,
+                        end: line_start + offset + name.len(),
+                    },
+                };
+                let names: Vec<&str> = Lint::ALL.iter().map(|l| l.name()).collect();
+                let msg = match suggest(name, &names) {
+                    Some(s) => format!(
+                        "Unknown lint in pragma {} {} (did you mean {}?)",
+                        "->".bold(),
+                        name.yellow(),
+                        s.green()
+                    ),
+                    None => format!("Unknown lint in pragma {} {}", "->".bold(), name.yellow()),
+                };
+                warnings.push(Warning::new(Lint::UnknownPragma, loc, msg));
+            }
+        } else if !trimmed.is_empty() && !pending.is_empty() {
+            allowed.insert(i + 1, std::mem::take(&mut pending));
+        }
+
+        line_start += line.len();
+    }
+
+    (allowed, warnings)
+}
@@ -1,3 +1,5 @@
 pub mod errors;
+pub mod json;
+pub mod lints;
 pub mod tokens;
 pub mod util;
@@ -103,8 +103,9 @@ impl TokenKind {
             ),
             TokenKind::Wildcard => format!("{:20} {}", "wildcard:", "*".yellow()),
             TokenKind::Type => format!(
-                "{:20} {} / {} / {} / {} / {}",
+                "{:20} {} / {} / {} / {} / {} / {}",
                 "type:",
+                "string".bright_cyan(),
                 "int".bright_cyan(),
                 "float".bright_cyan(),
                 "uint".bright_cyan(),
@@ -117,12 +118,21 @@ impl TokenKind {
     }
 }
 
+/// Byte range `start..end` into the source file.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
     pub lexeme: String,
     pub line: usize,
     pub col: usize,
+    pub end_col: usize,
+    pub span: Span,
 }
 
 impl Display for Token {
@@ -149,10 +159,7 @@ impl Display for Token {
 
 impl Token {
     pub fn len(&self) -> usize {
-        match self.kind {
-            TokenKind::String => self.lexeme.len() + 2,
-            _ => self.lexeme.len(),
-        }
+        self.end_col + 1 - self.col
     }
 }
 
@@ -181,3 +188,27 @@ pub fn fmt_assignment_kinds() -> String {
 pub fn is_assignment_kind(kind: &TokenKind) -> bool {
     ASSIGNMENT_KINDS.contains(kind)
 }
+
+pub const DIRECTIVES: [&str; 2] = ["!program", "!version"];
+pub const KEYWORDS: [&str; 4] = ["command", "flag", "pos", "named"];
+pub const TYPES: [&str; 6] = ["string", "int", "uint", "float", "bool", "path"];
+pub const ACTIONS: [&str; 2] = ["show_help", "show_version"];
+pub const BOOLEANS: [&str; 2] = ["true", "false"];
+
+const ARG_KEYS: [&str; 6] = ["desc", "required", "default", "type", "options", "nargs"];
+
+pub fn allowed_keys(keyword: &str) -> &'static [&'static str] {
+    match keyword {
+        "root" => &DIRECTIVES,
+        "command" => &["desc"],
+        "flag" => &["desc", "action"],
+        "pos" | "named" => &ARG_KEYS,
+        _ => &[],
+    }
+}
+
+pub fn is_known_key(key: &str) -> bool {
+    ["root", "command", "flag", "pos", "named"]
+        .iter()
+        .any(|kw| allowed_keys(kw).contains(&key))
+}
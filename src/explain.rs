@@ -0,0 +1,316 @@
+use colored::Colorize;
+
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub text: &'static str,
+    pub bad: &'static str,
+    pub good: &'static str,
+}
+
+pub const EXPLANATIONS: [Explanation; 34] = [
+    Explanation {
+        code: "A0101",
+        title: "Illegal character",
+        text: "The character is not part of the argol syntax. Outside of string literals and \
+               comments only identifiers, numbers, ranges and the symbols `= , ; / { } *` are \
+               allowed.",
+        bad: "flag verbose | v {\n    desc = \"Verbose output\";\n}",
+        good: "flag verbose / v {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0102",
+        title: "Invalid directive",
+        text: "Words starting with `!` are directives, and only `!program` and `!version` \
+               exist.",
+        bad: "!verison = \"1.0.0\";",
+        good: "!version = \"1.0.0\";",
+    },
+    Explanation {
+        code: "A0103",
+        title: "Invalid range syntax",
+        text: "A range is written `start..end` (exclusive) or `start..=end` (inclusive), where \
+               either bound may be left out. Any other combination of dots is rejected.",
+        bad: "named level {\n    type = int;\n    options = 1...5;\n}",
+        good: "named level {\n    type = int;\n    options = 1..=5;\n}",
+    },
+    Explanation {
+        code: "A0104",
+        title: "Invalid string escape sequence",
+        text: "Only `\\\\`, `\\\"`, `\\t`, `\\b` and `\\e` may follow a backslash inside a \
+               string literal.",
+        bad: "flag v {\n    desc = \"C:\\path\";\n}",
+        good: "flag v {\n    desc = \"C:\\\\path\";\n}",
+    },
+    Explanation {
+        code: "A0105",
+        title: "Identifier starts or ends with `-`",
+        text: "Dashes may separate the words of an identifier, but an identifier cannot begin \
+               or end with one.",
+        bad: "flag verbose- {\n    desc = \"Verbose output\";\n}",
+        good: "flag verbose {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0106",
+        title: "Extra decimal point",
+        text: "A float literal can contain at most one decimal point.",
+        bad: "named ratio {\n    type = float;\n    default = 0.5.1;\n}",
+        good: "named ratio {\n    type = float;\n    default = 0.51;\n}",
+    },
+    Explanation {
+        code: "A0107",
+        title: "Illegal characters in float exponent",
+        text: "The exponent of a float literal must be an integer, optionally signed.",
+        bad: "named ratio {\n    type = float;\n    default = 1.5e2x;\n}",
+        good: "named ratio {\n    type = float;\n    default = 1.5e2;\n}",
+    },
+    Explanation {
+        code: "A0108",
+        title: "Illegal characters in number",
+        text: "Number literals may only contain digits, `_` separators and, for floats, a \
+               decimal point and exponent.",
+        bad: "named jobs {\n    type = int;\n    default = 4x;\n}",
+        good: "named jobs {\n    type = int;\n    default = 4;\n}",
+    },
+    Explanation {
+        code: "A0109",
+        title: "Unterminated string literal",
+        text: "String literals must be closed with `\"` on the same line they start on.",
+        bad: "!program = \"app;",
+        good: "!program = \"app\";",
+    },
+    Explanation {
+        code: "A0110",
+        title: "Unexpected token",
+        text: "The token cannot appear at this position. The file is a sequence of `!directive \
+               = \"value\";` assignments and `keyword name / alias { ... }` blocks, and blocks \
+               contain `key = value, ...;` assignments.",
+        bad: "flag v {\n    desc \"Verbose output\";\n}",
+        good: "flag v {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0111",
+        title: "Missing token",
+        text: "The file ended, or a statement was cut short, before a required token such as \
+               `;` or `}` was found.",
+        bad: "flag v {\n    desc = \"Verbose output\";",
+        good: "flag v {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0112",
+        title: "Missing float exponent",
+        text: "An `e` in a float literal must be followed by an exponent.",
+        bad: "named ratio {\n    type = float;\n    default = 1.5e;\n}",
+        good: "named ratio {\n    type = float;\n    default = 1.5e3;\n}",
+    },
+    Explanation {
+        code: "A0113",
+        title: "Multiple signs",
+        text: "A number literal can have at most one leading `+` or `-`.",
+        bad: "named offset {\n    type = int;\n    default = --3;\n}",
+        good: "named offset {\n    type = int;\n    default = -3;\n}",
+    },
+    Explanation {
+        code: "A0114",
+        title: "Invalid standalone symbol",
+        text: "A sign or range operator was found without the number it applies to.",
+        bad: "named offset {\n    type = int;\n    default = -;\n}",
+        good: "named offset {\n    type = int;\n    default = -1;\n}",
+    },
+    Explanation {
+        code: "A0115",
+        title: "Unknown keyword",
+        text: "Blocks must start with one of the keywords `command`, `flag`, `pos` or `named`.",
+        bad: "comand build {\n    desc = \"Build the project\";\n}",
+        good: "command build {\n    desc = \"Build the project\";\n}",
+    },
+    Explanation {
+        code: "A0116",
+        title: "Unknown value",
+        text: "Bare words are only accepted as values when they are a type (`string`, `int`, \
+               `uint`, `float`, `bool`, `path`), an action (`show_help`, `show_version`) or a \
+               boolean (`true`, `false`). Other text must be quoted as a string.",
+        bad: "named level {\n    type = integer;\n}",
+        good: "named level {\n    type = int;\n}",
+    },
+    Explanation {
+        code: "A0201",
+        title: "More than one short name",
+        text: "An argument can have many long names but only one single-character short name.",
+        bad: "flag v/V/verbose {\n    desc = \"Verbose output\";\n}",
+        good: "flag v/verbose {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0202",
+        title: "Duplicate argument names",
+        text: "Each name in a block header must be unique.",
+        bad: "named d/deps/deps {\n    desc = \"Dependencies\";\n}",
+        good: "named d/deps/dependencies {\n    desc = \"Dependencies\";\n}",
+    },
+    Explanation {
+        code: "A0203",
+        title: "Block cannot be nested",
+        text: "Only `command` blocks may contain other blocks. `flag`, `pos` and `named` blocks \
+               can only contain assignments.",
+        bad: "flag v {\n    flag q {\n        desc = \"Quiet\";\n    }\n}",
+        good: "flag v {\n    desc = \"Verbose\";\n}\n\nflag q {\n    desc = \"Quiet\";\n}",
+    },
+    Explanation {
+        code: "A0204",
+        title: "Duplicate assignment",
+        text: "A key can only be assigned once per block.",
+        bad: "flag v {\n    desc = \"Verbose\";\n    desc = \"Verbose output\";\n}",
+        good: "flag v {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0205",
+        title: "Invalid key",
+        text: "Each block only accepts certain keys: the top level takes `!program` and \
+               `!version`, `command` takes `desc`, `flag` takes `desc` and `action`, and `pos` \
+               and `named` take `desc`, `required`, `default`, `type`, `options` and `nargs`.",
+        bad: "flag v {\n    type = bool;\n}",
+        good: "flag v {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0206",
+        title: "Multiple values for a single-value key",
+        text: "Only `default` and `options` accept comma-separated lists. All other keys take \
+               exactly one value.",
+        bad: "named level {\n    type = int, float;\n}",
+        good: "named level {\n    type = float;\n}",
+    },
+    Explanation {
+        code: "A0207",
+        title: "Invalid value kind",
+        text: "The value is not of a kind this key accepts, e.g. `desc` takes a string, \
+               `required` a boolean, `type` a type and `action` an action.",
+        bad: "pos name {\n    required = \"yes\";\n}",
+        good: "pos name {\n    required = true;\n}",
+    },
+    Explanation {
+        code: "A0208",
+        title: "Invalid nargs",
+        text: "`nargs` takes a non-negative count, a range of counts or `*`. The range must \
+               contain at least one count.",
+        bad: "named files {\n    nargs = 3..1;\n}",
+        good: "named files {\n    nargs = 1..=3;\n}",
+    },
+    Explanation {
+        code: "A0209",
+        title: "Number out of range",
+        text: "Integers must fit in a signed 64-bit integer and floats in a 64-bit float.",
+        bad: "named size {\n    type = int;\n    default = 99999999999999999999;\n}",
+        good: "named size {\n    type = int;\n    default = 9999999999;\n}",
+    },
+    Explanation {
+        code: "A0210",
+        title: "Type mismatch",
+        text: "`default` and `options` values must match the argument's `type`, which is \
+               `string` when not given. Integers are accepted for `float`, and ranges are only \
+               allowed for numeric types.",
+        bad: "named jobs {\n    default = 4;\n}",
+        good: "named jobs {\n    type = int;\n    default = 4;\n}",
+    },
+    Explanation {
+        code: "A0211",
+        title: "Negative unsigned value",
+        text: "Values for a `uint` argument cannot be negative.",
+        bad: "named jobs {\n    type = uint;\n    default = -1;\n}",
+        good: "named jobs {\n    type = uint;\n    default = 1;\n}",
+    },
+    Explanation {
+        code: "A0212",
+        title: "`show_version` without `!version`",
+        text: "A flag with `action = show_version` prints `<!program> <!version>`, so the spec \
+               must set the `!version` directive.",
+        bad: "!program = \"app\";\n\nflag V/version {\n    action = show_version;\n}",
+        good: "!program = \"app\";\n!version = \"1.0.0\";\n\nflag V/version {\n    action = \
+               show_version;\n}",
+    },
+    Explanation {
+        code: "A0213",
+        title: "Name used twice in a command",
+        text: "The arguments of a command share one set of names, covering the long and short \
+               names of `flag` and `named` blocks and the names of `pos` blocks, and its \
+               subcommands share another. A name can only appear once in each set.",
+        bad: "flag v/verbose {\n    desc = \"Verbose output\";\n}\n\nnamed v/level {\n    \
+              type = int;\n}",
+        good: "flag v/verbose {\n    desc = \"Verbose output\";\n}\n\nnamed l/level {\n    \
+               type = int;\n}",
+    },
+    Explanation {
+        code: "A0214",
+        title: "Default not among the options",
+        text: "Each `default` value must be one the argument accepts on the command line, so \
+               when `options` is given it must be one of the listed values or lie in the range.",
+        bad: "named level {\n    type = int;\n    default = 9;\n    options = 1..=5;\n}",
+        good: "named level {\n    type = int;\n    default = 3;\n    options = 1..=5;\n}",
+    },
+    Explanation {
+        code: "A0215",
+        title: "Wrong number of default values",
+        text: "`default` must give as many values as `nargs` takes, which is exactly one when \
+               `nargs` is not set.",
+        bad: "named tags {\n    default = \"a\", \"b\";\n}",
+        good: "named tags {\n    default = \"a\", \"b\";\n    nargs = 1..;\n}",
+    },
+    Explanation {
+        code: "A0301",
+        title: "Redundant comma",
+        text: "A comma before the first value of an assignment has no effect. This is the \
+               `redundant-comma` lint, which warns by default and can be silenced with \
+               `# argol: allow(redundant-comma)` on the line before.",
+        bad: "named color {\n    type = int;\n    options = , 3, 4;\n}",
+        good: "named color {\n    type = int;\n    options = 3, 4;\n}",
+    },
+    Explanation {
+        code: "A0302",
+        title: "Empty block",
+        text: "A block without any assignments or nested blocks declares an argument with no \
+               description, which is usually unintended. This is the `empty-block` lint, which \
+               warns by default and can be silenced with `# argol: allow(empty-block)` on the \
+               line before.",
+        bad: "flag verbose {}",
+        good: "flag verbose {\n    desc = \"Verbose output\";\n}",
+    },
+    Explanation {
+        code: "A0303",
+        title: "Unknown lint in pragma",
+        text: "`# argol: allow(...)` only accepts the names of existing lints: \
+               `redundant-comma`, `empty-block` and `unknown-pragma`.",
+        bad: "# argol: allow(empty-blocks)\nflag verbose {}",
+        good: "# argol: allow(empty-block)\nflag verbose {}",
+    },
+];
+
+pub fn find(code: &str) -> Option<&'static Explanation> {
+    EXPLANATIONS
+        .iter()
+        .find(|e| e.code.eq_ignore_ascii_case(code))
+}
+
+fn fmt_example(src: &str) -> String {
+    src.lines()
+        .map(|l| format!("    {l}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn print_explanation(e: &Explanation) {
+    println!(
+        "{} {}\n",
+        format!("[{}]", e.code).yellow().bold(),
+        e.title.bold()
+    );
+    println!("{}\n", e.text);
+    println!("{}\n", "Erroneous example:".red().bold());
+    println!("{}\n", fmt_example(e.bad));
+    println!("{}\n", "Corrected example:".green().bold());
+    println!("{}", fmt_example(e.good));
+}
+
+pub fn print_codes() {
+    for e in &EXPLANATIONS {
+        println!("{} {}", e.code.yellow().bold(), e.title);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::spec::{Command, Named, Names, Nargs, Options, Positional, Program, Value};
+
+const MIN_DESC_WIDTH: usize = 20;
+const MAX_NAME_WIDTH: usize = 30;
+
+/// Terminal width from `$COLUMNS`, falling back to 80.
+pub fn width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c: &usize| c > 0)
+        .unwrap_or(80)
+}
+
+fn join_values(values: &[Value]) -> String {
+    let values: Vec<String> = values.iter().map(Value::to_string).collect();
+    values.join(", ")
+}
+
+// `desc [default: x] [possible values: a, b]`
+fn describe(desc: &Option<String>, default: &[Value], options: &Option<Options>) -> String {
+    let mut parts: Vec<String> = desc.iter().cloned().collect();
+    if !default.is_empty() {
+        parts.push(format!("[default: {}]", join_values(default)));
+    }
+    match options {
+        Some(Options::Values(vs)) => parts.push(format!("[possible values: {}]", join_values(vs))),
+        Some(Options::Range(r)) => parts.push(format!("[possible values: {r}]")),
+        None => {}
+    }
+    parts.join(" ")
+}
+
+// `<V>`, `[<V>]`, `<V>...` or `[<V>...]` depending on how many values are taken
+fn placeholder(value: &str, nargs: Nargs) -> String {
+    match (nargs.min, nargs.max) {
+        (_, Some(0)) => String::new(),
+        (0, Some(1)) => format!("[{value}]"),
+        (_, Some(1)) => value.to_string(),
+        (0, _) => format!("[{value}...]"),
+        _ => format!("{value}..."),
+    }
+}
+
+fn named_value(n: &Named) -> String {
+    let value = format!("<{}>", n.names.primary().to_uppercase());
+    placeholder(&value, n.nargs)
+}
+
+fn positional_usage(p: &Positional) -> String {
+    let name = if p.required {
+        format!("<{}>", p.name)
+    } else {
+        format!("[{}]", p.name)
+    };
+    if p.nargs.max == Some(1) {
+        name
+    } else {
+        format!("{name}...")
+    }
+}
+
+fn switches(names: &Names) -> String {
+    let long = names.long.iter().map(|l| format!("--{l}"));
+    match names.short {
+        Some(s) => std::iter::once(format!("-{s}"))
+            .chain(long)
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => format!("    {}", long.collect::<Vec<_>>().join(", ")),
+    }
+}
+
+/// Split `text` into lines of at most `width` characters, breaking at spaces where possible.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// Two-column list of names and descriptions. Descriptions are wrapped to the width left over, and
+// start on the next line when the name is too long or too little room is left
+fn section(out: &mut String, title: &str, rows: &[(String, String)], width: usize) {
+    if rows.is_empty() {
+        return;
+    }
+    out.push_str(&format!("\n{title}:\n"));
+
+    let longest = rows
+        .iter()
+        .map(|(n, _)| n.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut col = 2 + longest.min(MAX_NAME_WIDTH) + 2;
+    if width.saturating_sub(col) < MIN_DESC_WIDTH {
+        col = 10;
+    }
+    let desc_width = width.saturating_sub(col).max(MIN_DESC_WIDTH);
+
+    for (name, desc) in rows {
+        let lines = wrap(desc, desc_width);
+        let name_len = 2 + name.chars().count();
+        let mut lines = lines.iter();
+        if name_len + 2 > col {
+            out.push_str(&format!("  {name}\n"));
+        } else {
+            let first = lines.next().map_or("", String::as_str);
+            let line = format!("  {name:<w$}{first}", w = col - 2);
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        for line in lines {
+            out.push_str(&format!("{:col$}{line}\n", ""));
+        }
+    }
+}
+
+/// Help for the command at `path` (a list of subcommand names, empty for the top level).
+pub fn render(program: &Program, path: &[String], width: usize) -> String {
+    let mut cmd = &program.cmd;
+    let mut usage = vec![program.name.clone().unwrap_or_else(|| "program".into())];
+    for name in path {
+        match cmd.commands.iter().find(|c| c.names.contains(name)) {
+            Some(sub) => {
+                cmd = sub;
+                usage.push(sub.names[0].clone());
+            }
+            None => break,
+        }
+    }
+
+    let mut out = String::new();
+    if let Some(desc) = &cmd.desc {
+        for line in wrap(desc, width) {
+            out.push_str(&format!("{line}\n"));
+        }
+        out.push('\n');
+    }
+
+    out.push_str(&format!("Usage: {}", usage_line(cmd, &usage)));
+    out.push('\n');
+
+    let arguments: Vec<(String, String)> = cmd
+        .positionals
+        .iter()
+        .map(|p| {
+            let desc = describe(&p.desc, &p.default, &p.options);
+            (positional_usage(p), desc)
+        })
+        .collect();
+    section(&mut out, "Arguments", &arguments, width);
+
+    let flags = cmd
+        .flags
+        .iter()
+        .map(|f| (switches(&f.names), f.desc.clone().unwrap_or_default()));
+    let named = cmd.named.iter().map(|n| {
+        let name = format!("{} {}", switches(&n.names), named_value(n));
+        (name, describe(&n.desc, &n.default, &n.options))
+    });
+    let options: Vec<(String, String)> = flags.chain(named).collect();
+    section(&mut out, "Options", &options, width);
+
+    let commands: Vec<(String, String)> = cmd
+        .commands
+        .iter()
+        .map(|c| {
+            let mut desc = c.desc.clone().unwrap_or_default();
+            if c.names.len() > 1 {
+                desc = format!("{desc} [aliases: {}]", c.names[1..].join(", "));
+            }
+            (c.names[0].clone(), desc.trim().to_string())
+        })
+        .collect();
+    section(&mut out, "Commands", &commands, width);
+
+    out
+}
+
+fn usage_line(cmd: &Command, path: &[String]) -> String {
+    let mut parts: Vec<String> = path.to_vec();
+    let optional = cmd.flags.len() + cmd.named.iter().filter(|n| !n.required).count();
+    if optional > 0 {
+        parts.push("[OPTIONS]".into());
+    }
+    for n in cmd.named.iter().filter(|n| n.required) {
+        let switch = match (n.names.long.first(), n.names.short) {
+            (Some(l), _) => format!("--{l}"),
+            (None, s) => format!("-{}", s.unwrap_or_default()),
+        };
+        parts.push(format!("{switch} {}", named_value(n)));
+    }
+    parts.extend(cmd.positionals.iter().map(positional_usage));
+    if !cmd.commands.is_empty() {
+        parts.push("[COMMAND]".into());
+    }
+    parts.join(" ")
+}
@@ -1,26 +1,63 @@
 mod ast;
+mod codegen;
 mod common;
+mod explain;
+mod help;
 mod parser;
+mod runtime;
+mod semantic;
+mod spec;
 mod tokenizer;
 
-use crate::common::errors::print_err;
+use crate::codegen::{bindings, completions, standalone};
+use crate::common::errors::{Loc, print_err};
+use crate::common::json::Json;
+use crate::common::lints::Level;
+use crate::runtime::Output;
+use crate::spec::Program;
 use colored::Colorize;
 use parser::parse;
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        print_err("Exactly 1 positional argument required (file to parse)");
-        exit(1);
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+fn parse_message_format(s: &str) -> MessageFormat {
+    match s {
+        "human" => MessageFormat::Human,
+        "json" => MessageFormat::Json,
+        _ => {
+            print_err(&format!(
+                "Invalid message format: {} (expected human or json)",
+                s.yellow()
+            ));
+            exit(1);
+        }
+    }
+}
+
+struct Options {
+    format: MessageFormat,
+    deny_warnings: bool,
+    // Only the bare check prints JSON diagnostics on stdout; subcommands keep it for their output
+    diagnostics_on_stdout: bool,
+}
+
+// Parse a spec file and report its diagnostics, exiting unless it parsed cleanly
+fn load(file: &str, opts: &Options) -> Program {
+    if opts.format == MessageFormat::Json {
+        colored::control::set_override(false);
     }
 
-    let fpath = Path::new(&args[1]);
+    let fpath = Path::new(file);
     let mut err = false;
     if !fpath.exists() {
-        print_err(&format!("File does not exist: {}", args[1].yellow()));
+        print_err(&format!("File does not exist: {}", file.yellow()));
         err = true;
     }
 
@@ -33,9 +70,422 @@ fn main() {
         exit(2);
     }
 
-    println!("\n{} `{}` ...\n", "Parsing".bright_green().bold(), args[1]);
+    let source = match std::fs::read_to_string(fpath) {
+        Ok(source) => source,
+        Err(e) => {
+            print_err(&format!("Could not read `{file}`: {e}"));
+            exit(2);
+        }
+    };
+    let (res, mut warnings) = parse(fpath, &source);
+    if opts.deny_warnings {
+        for w in &mut warnings {
+            w.level = Level::Deny;
+        }
+    }
+
+    let render = |loc: &Loc, human: String, json: Json| {
+        let out = match opts.format {
+            MessageFormat::Human => format!("{human}\n"),
+            MessageFormat::Json => json.to_string(),
+        };
+        ((loc.line, loc.col), out)
+    };
+    let errors = res.as_ref().err().map_or(&[][..], Vec::as_slice);
+    let mut diags: Vec<_> = warnings
+        .iter()
+        .map(|w| render(&w.loc, w.render(&source), w.to_json()))
+        .chain(
+            errors
+                .iter()
+                .map(|e| render(e.loc(), e.render(&source), e.to_json())),
+        )
+        .collect();
+    diags.sort_by_key(|(pos, _)| *pos);
+
+    for (_, d) in &diags {
+        match opts.format {
+            MessageFormat::Json if opts.diagnostics_on_stdout => println!("{d}"),
+            _ => eprintln!("{d}"),
+        }
+    }
+
+    let denied = warnings.iter().filter(|w| w.level == Level::Deny).count();
+    let n = errors.len() + denied;
+    match res {
+        Ok(program) if n == 0 => program,
+        _ => {
+            if opts.format == MessageFormat::Human {
+                print_err(&format!(
+                    "Could not parse `{}` due to {n} error{}",
+                    file,
+                    if n == 1 { "" } else { "s" }
+                ));
+            }
+            exit(1);
+        }
+    }
+}
+
+fn explain(args: &[String]) {
+    match args {
+        [] => explain::print_codes(),
+        [code] => match explain::find(code) {
+            Some(e) => explain::print_explanation(e),
+            None => {
+                print_err(&format!("Unknown error code: {}", code.yellow()));
+                exit(1);
+            }
+        },
+        _ => {
+            print_err("Expected at most 1 error code to explain");
+            exit(1);
+        }
+    }
+}
+
+// Split argolify's own options from positional words
+fn parse_options(args: impl IntoIterator<Item = String>) -> (Options, Vec<String>) {
+    let mut opts = Options {
+        format: MessageFormat::Human,
+        deny_warnings: false,
+        diagnostics_on_stdout: false,
+    };
+    let mut words: Vec<String> = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if let Some(v) = arg.strip_prefix("--message-format=") {
+            opts.format = parse_message_format(v);
+        } else if arg == "--message-format" {
+            let Some(v) = args.next() else {
+                print_err("Missing value for --message-format");
+                exit(1);
+            };
+            opts.format = parse_message_format(&v);
+        } else if arg == "--deny-warnings" {
+            opts.deny_warnings = true;
+        } else if arg.starts_with("--") {
+            print_err(&format!("Unknown option: {}", arg.yellow()));
+            exit(1);
+        } else {
+            words.push(arg);
+        }
+    }
+    (opts, words)
+}
+
+// Match the words after `--` against a spec and print the values, exiting 2 on a usage error
+fn run(args: Vec<String>) {
+    let (mut own, argv) = match args.iter().position(|a| a == "--") {
+        Some(i) => (args[..i].to_vec(), args[i + 1..].to_vec()),
+        None => (args, Vec::new()),
+    };
+    let output = take_output(&mut own, Output::Text);
+    let (opts, files) = parse_options(own);
+    if files.len() != 1 {
+        print_err(&format!(
+            "Expected a single spec file: {}",
+            "argolify run <file> [--output <format>] [--prefix <prefix>] -- <args...>".yellow()
+        ));
+        exit(1);
+    }
+
+    let program = load(&files[0], &opts);
+    check_shell_names(&program, &output);
+    let result = runtime::match_args(&program, &argv);
+    exit(runtime::report(&program, &result, &output));
+}
+
+// Remove an option and its value (`-o <value>`, `--out <value>` or `--out=<value>`) from `args`
+fn take_value(args: &mut Vec<String>, names: &[&str]) -> Option<String> {
+    let inline = |a: &str| {
+        names
+            .iter()
+            .find_map(|n| a.strip_prefix(n)?.strip_prefix('='))
+            .map(str::to_string)
+    };
+    let i = args
+        .iter()
+        .position(|a| names.contains(&a.as_str()) || inline(a).is_some())?;
+    let arg = args.remove(i);
+    if let Some(v) = inline(&arg) {
+        return Some(v);
+    }
+    if i >= args.len() {
+        print_err(&format!("Missing value for {}", arg.yellow()));
+        exit(1);
+    }
+    Some(args.remove(i))
+}
+
+// Remove `--output <format>` and, for shell output, `--prefix <prefix>` from `args`
+fn take_output(args: &mut Vec<String>, default: Output) -> Output {
+    let output = match take_value(args, &["--output"]) {
+        None => default,
+        Some(name) => Output::from_name(&name).unwrap_or_else(|| {
+            print_err(&format!(
+                "Invalid output format: {} (expected {})",
+                name.yellow(),
+                Output::NAMES.join(", ")
+            ));
+            exit(1);
+        }),
+    };
+
+    let Some(prefix) = take_value(args, &["--prefix"]) else {
+        return output;
+    };
+    if !matches!(output, Output::Shell(_)) {
+        print_err(&format!(
+            "{} only applies to {}",
+            "--prefix".yellow(),
+            "--output shell".yellow()
+        ));
+        exit(1);
+    }
+    if !runtime::is_shell_prefix(&prefix) {
+        print_err(&format!(
+            "Invalid prefix: {} (expected one or more letters, digits and `_`, not starting with a \
+             digit)",
+            prefix.yellow()
+        ));
+        exit(1);
+    }
+    Output::Shell(prefix)
+}
+
+// Exit when shell output would assign one variable for two names
+fn check_shell_names(program: &Program, output: &Output) {
+    let Output::Shell(prefix) = output else {
+        return;
+    };
+    let clashes = runtime::shell_clashes(program, prefix);
+    for clash in &clashes {
+        print_err(&format!("Cannot print shell variables: {clash}"));
+    }
+    if !clashes.is_empty() {
+        exit(1);
+    }
+}
+
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let len = args.len();
+    args.retain(|a| a != name);
+    args.len() != len
+}
+
+// Emit a standalone Rust project for a spec and build it with cargo
+fn build(mut args: Vec<String>) {
+    let out = take_value(&mut args, &["-o", "--out"]);
+    let output = take_output(&mut args, Output::Json);
+    let no_build = take_flag(&mut args, "--no-build");
+    let force = take_flag(&mut args, "--force");
+    let (opts, files) = parse_options(args);
+    if files.len() != 1 {
+        print_err(&format!(
+            "Expected a single spec file: {}",
+            "argolify build <file> [-o <dir>] [--output <format>] [--prefix <prefix>] [--no-build] \
+             [--force]"
+                .yellow()
+        ));
+        exit(1);
+    }
+
+    let program = load(&files[0], &opts);
+    check_shell_names(&program, &output);
+    let dir = PathBuf::from(out.unwrap_or_else(|| {
+        let stem = Path::new(&files[0]).file_stem().unwrap_or_default();
+        match &program.name {
+            Some(name) => format!("{name}-args"),
+            None => format!("{}-args", stem.to_string_lossy()),
+        }
+    }));
+    let name = standalone::package_name(&dir.file_name().unwrap_or_default().to_string_lossy());
+
+    if !force && !standalone::can_overwrite(&dir) {
+        print_err(&format!(
+            "Refusing to overwrite `{}`: it is not empty and was not generated by argolify (use {} \
+             to write anyway)",
+            dir.display(),
+            "--force".yellow()
+        ));
+        exit(1);
+    }
+    if let Err(e) = standalone::emit(&program, &files[0], &dir, &name, &output) {
+        print_err(&format!("Could not write `{}`: {e}", dir.display()));
+        exit(1);
+    }
+    println!(
+        "{} `{}` ({})",
+        "Generated".bright_green().bold(),
+        dir.display(),
+        name
+    );
+    if no_build {
+        return;
+    }
+
+    match standalone::build(&dir) {
+        Ok(status) if status.success() => {
+            let exe = dir
+                .join("target")
+                .join("release")
+                .join(format!("{name}{}", env::consts::EXE_SUFFIX));
+            println!("{} `{}`", "Built".bright_green().bold(), exe.display());
+        }
+        Ok(_) => {
+            print_err(&format!("Could not build `{}`", dir.display()));
+            exit(1);
+        }
+        Err(e) => {
+            print_err(&format!("Could not run cargo: {e}"));
+            exit(1);
+        }
+    }
+}
+
+// The generated source, or exit after reporting each pair of names that would clash in it
+fn unclashed(what: &str, source: Result<String, Vec<String>>) -> String {
+    source.unwrap_or_else(|clashes| {
+        for clash in clashes {
+            print_err(&format!("Cannot generate {what}: {clash}"));
+        }
+        exit(1);
+    })
+}
+
+// Print generated source, or write it to `out` when given
+fn write_output(out: Option<String>, source: &str) {
+    match out {
+        None => print!("{source}"),
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, source) {
+                print_err(&format!("Could not write `{path}`: {e}"));
+                exit(1);
+            }
+        }
+    }
+}
+
+// Generate parser source for a spec in another language
+fn generate(mut args: Vec<String>) {
+    let out = take_value(&mut args, &["-o", "--out"]);
+    let (opts, words) = parse_options(args);
+    let [lang, file] = words.as_slice() else {
+        print_err(&format!(
+            "Expected a language and a spec file: {}",
+            "argolify gen <lang> <file> [-o <path>]".yellow()
+        ));
+        exit(1);
+    };
+    if !codegen::LANGS.contains(&lang.as_str()) {
+        print_err(&format!(
+            "Unknown language: {} (expected {})",
+            lang.yellow(),
+            codegen::LANGS.join(", ")
+        ));
+        exit(1);
+    }
+
+    let program = load(file, &opts);
+    let source = codegen::generate(lang, &program, file).unwrap();
+    write_output(out, &unclashed(lang, source));
+}
+
+// Generate types for reading the JSON output of a spec in another language
+fn bindings(mut args: Vec<String>) {
+    let out = take_value(&mut args, &["-o", "--out"]);
+    let lang = take_value(&mut args, &["--lang"]);
+    let (opts, files) = parse_options(args);
+    let (Some(lang), [file]) = (lang, files.as_slice()) else {
+        print_err(&format!(
+            "Expected a language and a spec file: {}",
+            "argolify bindings --lang <lang> <file> [-o <path>]".yellow()
+        ));
+        exit(1);
+    };
+    if !bindings::LANGS.contains(&lang.as_str()) {
+        print_err(&format!(
+            "Unknown language: {} (expected {})",
+            lang.yellow(),
+            bindings::LANGS.join(", ")
+        ));
+        exit(1);
+    }
+
+    let program = load(file, &opts);
+    let source = bindings::generate(&lang, &program, file).unwrap();
+    write_output(out, &unclashed(&format!("{lang} bindings"), source));
+}
+
+// Generate a shell completion script for a spec
+fn complete(mut args: Vec<String>) {
+    let out = take_value(&mut args, &["-o", "--out"]);
+    let (opts, words) = parse_options(args);
+    let [shell, file] = words.as_slice() else {
+        print_err(&format!(
+            "Expected a shell and a spec file: {}",
+            "argolify completions <shell> <file> [-o <path>]".yellow()
+        ));
+        exit(1);
+    };
+    if !completions::SHELLS.contains(&shell.as_str()) {
+        print_err(&format!(
+            "Unknown shell: {} (expected {})",
+            shell.yellow(),
+            completions::SHELLS.join(", ")
+        ));
+        exit(1);
+    }
+
+    let program = load(file, &opts);
+    let script = completions::generate(shell, &program, file).unwrap();
+    write_output(out, &script);
+}
+
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("explain") => {
+            explain(&args.skip(1).collect::<Vec<_>>());
+            return;
+        }
+        Some("run") => {
+            run(args.skip(1).collect());
+            return;
+        }
+        Some("build") => {
+            build(args.skip(1).collect());
+            return;
+        }
+        Some("gen") => {
+            generate(args.skip(1).collect());
+            return;
+        }
+        Some("bindings") => {
+            bindings(args.skip(1).collect());
+            return;
+        }
+        Some("completions") => {
+            complete(args.skip(1).collect());
+            return;
+        }
+        _ => {}
+    }
+
+    let (mut opts, files) = parse_options(args);
+    opts.diagnostics_on_stdout = true;
+    if files.len() != 1 {
+        print_err("Exactly 1 positional argument required (file to parse)");
+        exit(1);
+    }
+
+    if opts.format == MessageFormat::Human {
+        println!("\n{} `{}` ...\n", "Parsing".bright_green().bold(), files[0]);
+    }
 
-    if let Err(e) = parse(fpath) {
-        eprintln!("{e}");
+    let program = load(&files[0], &opts);
+    if opts.format == MessageFormat::Human {
+        println!("{program:#?}");
     }
 }
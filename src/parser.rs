@@ -1,14 +1,14 @@
 use crate::{
     ast::{Assignment, Block},
-    common::errors::{
-        Loc, ParseError, ParseError::*, SemanticError, SyntaxError, print_semantic_tip,
-    },
-    common::tokens::{Token, TokenKind, fmt_assignment_kinds, is_assignment_kind},
+    common::errors::{Loc, ParseError, ParseError::*, SemanticError, SyntaxError, Warning},
+    common::lints::{Level, Lint, pragmas},
+    common::tokens::{Span, Token, TokenKind, fmt_assignment_kinds, is_assignment_kind},
+    semantic::analyze,
+    spec::Program,
     tokenizer::tokenize,
 };
 use colored::Colorize;
-use std::collections::HashSet;
-use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::iter::Peekable;
 use std::path::Path;
 use std::slice::Iter;
@@ -18,6 +18,8 @@ pub struct Parser<'a> {
     pub root: Block,
     pub tokens: Peekable<Iter<'a, Token>>,
     pub path: String,
+    pub errors: Vec<ParseError>,
+    pub warnings: Vec<Warning>,
 }
 
 impl<'a> Parser<'a> {
@@ -27,23 +29,47 @@ impl<'a> Parser<'a> {
             keyword: "root".into(),
             identifiers: Vec::new(),
             assignments: Vec::new(),
+            invalid_keys: Vec::new(),
             blocks: Vec::new(),
+            line: 0,
+            span: Span::default(),
         };
 
         Parser {
             root,
             tokens,
             path: path.to_string_lossy().to_string(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
-    fn parse(&mut self) -> Result<(), ParseError> {
+    fn parse(&mut self) {
         let mut has_tok = false;
         while let Some(&tok) = self.tokens.peek() {
             has_tok = true;
             match tok.kind {
-                TokenKind::Directive => self.parse_assignment(tok, true, None)?,
-                TokenKind::Keyword => self.parse_block(tok, None)?,
+                TokenKind::Directive => {
+                    if let Err(e) = self.parse_assignment(tok, true, None) {
+                        self.errors.push(e);
+                        self.skip_assignment();
+                    }
+                }
+                TokenKind::Keyword => {
+                    if let Err(e) = self.parse_block(tok, None) {
+                        self.errors.push(e);
+                        self.skip_block();
+                    }
+                }
+                TokenKind::Identifier if self.is_block_like() => {
+                    let loc = self.get_loc(tok);
+                    self.errors
+                        .push(Syntax(Box::new(SyntaxError::UnknownKeyword(
+                            loc,
+                            tok.lexeme.clone(),
+                        ))));
+                    self.skip_block();
+                }
                 _ => {
                     let loc = self.get_loc(tok);
                     let msg = format!(
@@ -53,25 +79,29 @@ impl<'a> Parser<'a> {
                         tok.kind,
                         tok,
                     );
-                    return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                    self.errors
+                        .push(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
+                    self.tokens.next();
+                    self.skip_assignment();
                 }
             }
         }
 
-        if has_tok {
-            Ok(())
-        } else {
+        if !has_tok {
             let loc = Loc {
                 path: self.path.to_string(),
                 line: 0,
                 col: 0,
+                len: 0,
+                span: Span::default(),
             };
             let msg: String = format!(
                 "Expected {} or {} but got no token",
                 TokenKind::Directive,
                 TokenKind::Keyword
             );
-            Err(Syntax(SyntaxError::NoToken(loc, msg)))
+            self.errors
+                .push(Syntax(Box::new(SyntaxError::NoToken(loc, msg))));
         }
     }
 
@@ -86,10 +116,13 @@ impl<'a> Parser<'a> {
         )?;
 
         let mut exp_alt = true;
+        let mut opened = false;
         let mut short_names: HashSet<String> = HashSet::new();
-        let mut unique_names: HashSet<String> = HashSet::new();
+        let mut unique_names: HashMap<String, Loc> = HashMap::new();
         let mut duplicate_names: HashSet<String> = HashSet::new();
-        let mut error_loc: Option<Loc> = None;
+        let mut first_short: Option<Loc> = None;
+        let mut short_err: Option<(Loc, Loc)> = None;
+        let mut dup_err: Option<(Loc, Loc)> = None;
 
         while let Some(&tok) = self.tokens.peek() {
             if exp_alt && tok.kind != TokenKind::Identifier {
@@ -101,7 +134,7 @@ impl<'a> Parser<'a> {
                     tok
                 );
                 let loc = self.get_loc(tok);
-                return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
             }
 
             if exp_alt {
@@ -109,22 +142,24 @@ impl<'a> Parser<'a> {
                 self.tokens.next();
 
                 if tok.lexeme.len() == 1 && short_names.len() == 1 {
-                    error_loc = Some(self.get_loc(tok));
+                    short_err = Some((self.get_loc(tok), first_short.clone().unwrap()));
                     short_names.insert(tok.lexeme.clone());
                 } else if tok.lexeme.len() == 1 {
+                    first_short = Some(self.get_loc(tok));
                     short_names.insert(tok.lexeme.clone());
-                } else if unique_names.contains(&tok.lexeme) && duplicate_names.is_empty() {
-                    error_loc = Some(self.get_loc(tok));
-                    duplicate_names.insert(tok.lexeme.clone());
-                } else if unique_names.contains(&tok.lexeme) {
+                } else if let Some(first) = unique_names.get(&tok.lexeme) {
+                    if duplicate_names.is_empty() {
+                        dup_err = Some((self.get_loc(tok), first.clone()));
+                    }
                     duplicate_names.insert(tok.lexeme.clone());
                 } else {
-                    unique_names.insert(tok.lexeme.clone());
+                    unique_names.insert(tok.lexeme.clone(), self.get_loc(tok));
                 }
 
-                block.identifiers.push(tok.lexeme.clone());
+                block.identifiers.push(tok.clone());
             } else if tok.kind == TokenKind::OpenBrace {
                 self.tokens.next();
+                opened = true;
                 break;
             } else if tok.kind == TokenKind::Alt {
                 exp_alt = true;
@@ -139,21 +174,35 @@ impl<'a> Parser<'a> {
                 );
 
                 let loc = self.get_loc(tok);
-                return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
             }
         }
 
-        if short_names.len() > 1 {
-            Err(Semantic(SemanticError::ExtraShortName(
-                error_loc.unwrap(),
-                short_names,
-            )))
-        } else if !duplicate_names.is_empty() {
-            Err(Semantic(SemanticError::DuplicateArgNames(
-                error_loc.unwrap(),
-                duplicate_names,
-            )))
+        if let Some((loc, first)) = short_err {
+            self.errors
+                .push(Semantic(Box::new(SemanticError::ExtraShortName(
+                    loc,
+                    Box::new(first),
+                    short_names,
+                ))));
+        }
+
+        if let Some((loc, first)) = dup_err {
+            self.errors
+                .push(Semantic(Box::new(SemanticError::DuplicateArgNames(
+                    loc,
+                    Box::new(first),
+                    duplicate_names,
+                ))));
+        }
+
+        if opened {
+            Ok(())
         } else {
+            self.expect_next(
+                TokenKind::OpenBrace,
+                format!("Missing {} to open block", TokenKind::OpenBrace),
+            )?;
             Ok(())
         }
     }
@@ -167,34 +216,62 @@ impl<'a> Parser<'a> {
             keyword: kw_tok.lexeme.clone(),
             identifiers: Vec::new(),
             assignments: Vec::new(),
+            invalid_keys: Vec::new(),
             blocks: Vec::new(),
+            line: kw_tok.line,
+            span: kw_tok.span,
         };
 
         self.parse_block_header(&mut block)?;
 
-        let mut empty_block = false;
+        let mut empty_block = true;
+        let mut closed = false;
 
         while let Some(&tok) = self.tokens.peek() {
             match tok.kind {
                 TokenKind::CloseBrace => {
                     if empty_block {
-                        let loc = self.get_loc(tok);
-                        print_semantic_tip(&loc, "Redundant argument: empty block".into());
+                        let loc = self.get_loc(kw_tok);
+                        let msg = "Redundant argument: empty block".into();
+                        self.warnings.push(Warning::new(Lint::EmptyBlock, loc, msg));
                     }
 
                     self.tokens.next();
+                    block.span.end = tok.span.end;
+                    closed = true;
                     break;
                 }
+                TokenKind::Identifier if self.is_block_like() => {
+                    let loc = self.get_loc(tok);
+                    self.errors
+                        .push(Syntax(Box::new(SyntaxError::UnknownKeyword(
+                            loc,
+                            tok.lexeme.clone(),
+                        ))));
+                    self.skip_block();
+                    empty_block = false;
+                }
                 TokenKind::Identifier => {
-                    self.parse_assignment(tok, false, Some(&mut block))?;
+                    if let Err(e) = self.parse_assignment(tok, false, Some(&mut block)) {
+                        block.invalid_keys.push(tok.lexeme.clone());
+                        self.errors.push(e);
+                        self.skip_assignment();
+                    }
                     empty_block = false;
                 }
                 TokenKind::Keyword => {
                     let loc = self.get_loc(tok);
                     if kw_tok.lexeme != "command" {
-                        return Err(Semantic(SemanticError::CannotNest(loc, tok.lexeme.clone())));
+                        self.errors
+                            .push(Semantic(Box::new(SemanticError::CannotNest(
+                                loc,
+                                tok.lexeme.clone(),
+                            ))));
+                    }
+                    if let Err(e) = self.parse_block(tok, Some(&mut block)) {
+                        self.errors.push(e);
+                        self.skip_block();
                     }
-                    self.parse_block(tok, Some(&mut block))?;
                     empty_block = false;
                 }
                 _ => {
@@ -215,11 +292,35 @@ impl<'a> Parser<'a> {
                         )
                     };
                     let loc = self.get_loc(tok);
-                    return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                    self.errors
+                        .push(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
+                    self.tokens.next();
+                    self.skip_assignment();
                 }
             }
         }
 
+        if !closed {
+            let last_end = block.assignments.iter().map(|a| a.span.end);
+            let child_end = block.blocks.iter().map(|b| b.span.end);
+            block.span.end = last_end.chain(child_end).fold(block.span.end, usize::max);
+
+            let loc = Loc {
+                path: self.path.to_string(),
+                line: kw_tok.line,
+                col: kw_tok.col,
+                len: kw_tok.len(),
+                span: kw_tok.span,
+            };
+            let msg = format!(
+                "Missing {} to end {} block; got no token",
+                TokenKind::CloseBrace,
+                block.keyword.bright_magenta()
+            );
+            self.errors
+                .push(Syntax(Box::new(SyntaxError::NoToken(loc, msg))));
+        }
+
         if let Some(p) = parent {
             p.blocks.push(block);
         } else {
@@ -247,7 +348,7 @@ impl<'a> Parser<'a> {
         )?;
         self.tokens.next();
 
-        let mut str_vals: Vec<String> = Vec::new();
+        let mut vals: Vec<Token> = Vec::new();
         let mut val_kind: Option<TokenKind> = None;
         let mut assignment: Option<Assignment> = None;
 
@@ -262,16 +363,21 @@ impl<'a> Parser<'a> {
                     tok.kind,
                     tok
                 );
-                return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
             } else if is_dir && is_str {
-                self.expect_semicolon()?;
+                let sc_tok = self.expect_semicolon()?;
                 self.tokens.next();
 
                 assignment = Some(Assignment {
                     key: ident_tok.lexeme.clone(),
-                    value: tok.lexeme.clone(),
-                    ident_kind,
+                    values: vec![tok.clone()],
                     kind: TokenKind::String,
+                    line: ident_tok.line,
+                    col: ident_tok.col,
+                    span: Span {
+                        start: ident_tok.span.start,
+                        end: sc_tok.span.end,
+                    },
                 });
                 break;
             }
@@ -289,20 +395,25 @@ impl<'a> Parser<'a> {
                         "Expected {} value after comma, but got {}: {}",
                         vkind, tok.kind, tok
                     );
-                    return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                    return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
                 } else {
                     let msg = format!("Expected value after comma, but got {}: {}", tok.kind, tok);
-                    return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                    return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
                 };
             }
 
             let is_sc = tok.kind == TokenKind::SemiColon;
-            if is_sc && !str_vals.is_empty() {
+            if is_sc && !vals.is_empty() {
                 assignment = Some(Assignment {
                     key: ident_tok.lexeme.clone(),
-                    value: str_vals.join(","),
+                    values: vals,
                     kind: val_kind.unwrap(),
-                    ident_kind,
+                    line: ident_tok.line,
+                    col: ident_tok.col,
+                    span: Span {
+                        start: ident_tok.span.start,
+                        end: tok.span.end,
+                    },
                 });
                 self.tokens.next();
                 break;
@@ -318,22 +429,36 @@ impl<'a> Parser<'a> {
                             "Expected {} value after comma, but got {}: {}",
                             vkind, tok.kind, tok
                         );
-                        return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                        return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
                     } else {
                         let mut loc = self.get_loc(&prev_tok);
-                        loc.col += prev_tok.len() - 1;
+                        loc.col += prev_tok.len();
+                        loc.len = 1;
+                        loc.span = Span {
+                            start: prev_tok.span.end,
+                            end: prev_tok.span.end,
+                        };
                         let msg = format!(
                             "Expected comma or semicolon after {} value, but got {}",
                             vkind, tok.kind
                         );
 
-                        return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                        return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
                     };
                 }
             } else {
                 val_kind = Some(tok.kind);
             }
 
+            if tok.kind == TokenKind::Identifier {
+                let loc = self.get_loc(tok);
+                return Err(Syntax(Box::new(SyntaxError::UnknownValue(
+                    loc,
+                    ident_tok.lexeme.clone(),
+                    tok.lexeme.clone(),
+                ))));
+            }
+
             if !is_assignment_kind(&tok.kind) {
                 let loc = self.get_loc(tok);
                 let msg = format!(
@@ -344,35 +469,104 @@ impl<'a> Parser<'a> {
                     fmt_assignment_kinds()
                 );
 
-                return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
             }
 
-            if prev_tok.kind == TokenKind::Comma && str_vals.is_empty() {
+            if prev_tok.kind == TokenKind::Comma && vals.is_empty() {
                 let loc = self.get_loc(&prev_tok);
                 let msg = "Redundant comma before value in assignment".to_string();
-                print_semantic_tip(&loc, msg);
+                self.warnings
+                    .push(Warning::new(Lint::RedundantComma, loc, msg));
             }
 
             prev_tok = tok.clone();
-            str_vals.push(tok.lexeme.clone());
+            vals.push(tok.clone());
             self.tokens.next();
         }
 
+        let Some(assignment) = assignment else {
+            let mut loc = self.get_loc(&prev_tok);
+            loc.col += prev_tok.len();
+            loc.len = 1;
+            loc.span = Span {
+                start: prev_tok.span.end,
+                end: prev_tok.span.end,
+            };
+            let msg = format!(
+                "Missing {} to end assignment; got no token",
+                TokenKind::SemiColon
+            );
+            return Err(Syntax(Box::new(SyntaxError::NoToken(loc, msg))));
+        };
+
         if let Some(p) = parent {
-            p.assignments.push(assignment.unwrap());
+            p.assignments.push(assignment);
         } else {
-            self.root.assignments.push(assignment.unwrap());
+            self.root.assignments.push(assignment);
         }
 
         Ok(())
     }
 
-    fn expect_semicolon(&mut self) -> Result<(), ParseError> {
+    // Skip the rest of a malformed assignment, stopping after its `;` or before anything that can
+    // start a new statement
+    fn skip_assignment(&mut self) {
+        while let Some(&tok) = self.tokens.peek() {
+            match tok.kind {
+                TokenKind::SemiColon => {
+                    self.tokens.next();
+                    break;
+                }
+                TokenKind::CloseBrace | TokenKind::Keyword | TokenKind::Directive => break,
+                TokenKind::Identifier => {
+                    let mut ahead = self.tokens.clone();
+                    ahead.next();
+                    if ahead.peek().is_some_and(|t| t.kind == TokenKind::Eq) {
+                        break;
+                    }
+                    self.tokens.next();
+                }
+                _ => {
+                    self.tokens.next();
+                }
+            }
+        }
+    }
+
+    // Skip a block whose header could not be parsed, including its (possibly nested) body
+    fn skip_block(&mut self) {
+        let mut depth = 0;
+        while let Some(&tok) = self.tokens.peek() {
+            match tok.kind {
+                TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseBrace if depth == 0 => break,
+                TokenKind::CloseBrace if depth == 1 => {
+                    self.tokens.next();
+                    break;
+                }
+                TokenKind::CloseBrace => depth -= 1,
+                TokenKind::Keyword | TokenKind::Directive if depth == 0 => break,
+                _ => {}
+            }
+            self.tokens.next();
+        }
+    }
+
+    // An identifier followed by another identifier or `{` is a block header with a misspelled
+    // keyword rather than the start of an assignment
+    fn is_block_like(&self) -> bool {
+        let mut ahead = self.tokens.clone();
+        ahead.next();
+        ahead
+            .peek()
+            .is_some_and(|t| matches!(t.kind, TokenKind::Identifier | TokenKind::OpenBrace))
+    }
+
+    fn expect_semicolon(&mut self) -> Result<Token, ParseError> {
         self.expect_next(
             TokenKind::SemiColon,
             format!("Missing {} to end assignment", TokenKind::SemiColon,),
-        )?;
-        Ok(())
+        )
     }
 
     fn expect_next(&mut self, exp: TokenKind, msg: String) -> Result<Token, ParseError> {
@@ -385,13 +579,18 @@ impl<'a> Parser<'a> {
                 if expect_semicolon {
                     let cur_tok = cur_tok.unwrap();
                     loc = self.get_loc(cur_tok);
-                    loc.col += cur_tok.len() - 1;
+                    loc.col += cur_tok.len();
+                    loc.len = 1;
+                    loc.span = Span {
+                        start: cur_tok.span.end,
+                        end: cur_tok.span.end,
+                    };
                 } else {
                     loc = self.get_loc(next_tok);
                 }
 
                 let msg = format!("{msg}; next token is {}", next_tok.kind);
-                return Err(Syntax(SyntaxError::UnexpectedToken(loc, msg)));
+                return Err(Syntax(Box::new(SyntaxError::UnexpectedToken(loc, msg))));
             }
 
             return Ok(next_tok.clone());
@@ -404,11 +603,13 @@ impl<'a> Parser<'a> {
                 path: self.path.to_string(),
                 line: 0,
                 col: 0,
+                len: 0,
+                span: Span::default(),
             }
         };
 
         let msg = format!("{msg}; got no token");
-        Err(Syntax(SyntaxError::NoToken(loc, msg)))
+        Err(Syntax(Box::new(SyntaxError::NoToken(loc, msg))))
     }
 
     fn get_loc(&self, tok: &Token) -> Loc {
@@ -416,21 +617,61 @@ impl<'a> Parser<'a> {
             path: self.path.to_string(),
             line: tok.line,
             col: tok.col,
+            len: tok.len(),
+            span: tok.span,
         }
     }
 }
 
-pub fn parse(path: &Path) -> Result<(), ParseError> {
-    let content = fs::read_to_string(path)
-        .map_err(|_| "Could not read file".to_string())
-        .unwrap();
+// Map each block's header line to its last line
+fn block_ends(block: &Block, content: &str, ends: &mut HashMap<usize, usize>) {
+    for b in &block.blocks {
+        let end = content[..b.span.end].matches('\n').count() + 1;
+        let e = ends.entry(b.line).or_insert(end);
+        *e = (*e).max(end);
+        block_ends(b, content, ends);
+    }
+}
 
-    let tokens = tokenize(&content, path)?;
+pub fn parse(path: &Path, content: &str) -> (Result<Program, Vec<ParseError>>, Vec<Warning>) {
+    let (allowed, mut warnings) = pragmas(content, &path.to_string_lossy());
+    let (tokens, lex_errors) = tokenize(content, path);
     let mut parser = Parser::new(&tokens, path);
 
-    parser.parse()?;
-
-    println!("{:#?}", parser.root);
+    parser.parse();
+
+    // A pragma above a block header covers the whole block
+    let mut ends = HashMap::new();
+    block_ends(&parser.root, content, &mut ends);
+    warnings.append(&mut parser.warnings);
+    warnings.retain(|w| {
+        let suppressed = allowed.iter().any(|(line, lints)| {
+            let end = ends.get(line).copied().unwrap_or(*line);
+            (*line..=end).contains(&w.loc.line) && lints.contains(&w.lint)
+        });
+        w.level != Level::Allow && !suppressed
+    });
+    warnings.sort_by_key(|w| (w.loc.line, w.loc.col));
+
+    // A malformed token leaves a hole in its statement, so syntax errors on the same line only
+    // repeat the lexical error
+    let lex_lines: HashSet<usize> = lex_errors.iter().map(|e| e.loc().line).collect();
+    let mut errors = lex_errors;
+    errors.extend(
+        parser
+            .errors
+            .into_iter()
+            .filter(|e| !matches!(e, Syntax(_)) || !lex_lines.contains(&e.loc().line)),
+    );
+    match analyze(&parser.root, &parser.path) {
+        Ok(program) if errors.is_empty() => return (Ok(program), warnings),
+        Ok(_) => {}
+        Err(e) => errors.extend(e),
+    }
 
-    Ok(())
+    errors.sort_by_key(|e| {
+        let loc = e.loc();
+        (loc.line, loc.col)
+    });
+    (Err(errors), warnings)
 }
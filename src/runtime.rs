@@ -0,0 +1,823 @@
+use crate::common::json::Json;
+use crate::help;
+use crate::spec::{Action, ArgType, Command, Names, Nargs, Options, Program, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Version of the JSON document emitted by [`Output::Json`], bumped on incompatible changes.
+pub const JSON_VERSION: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgKind {
+    Flag,
+    Positional,
+    Named,
+}
+
+impl ArgKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgKind::Flag => "flag",
+            ArgKind::Positional => "positional",
+            ArgKind::Named => "named",
+        }
+    }
+}
+
+/// Where a matched argument's values came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Argv,
+    Default,
+    Unset,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Argv => "argv",
+            Source::Default => "default",
+            Source::Unset => "unset",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedArg {
+    pub name: String,
+    pub kind: ArgKind,
+    pub ty: ArgType,
+    pub values: Vec<Value>,
+    pub source: Source,
+    /// Whether the argument accepts more than one value (its `nargs` maximum is not 1).
+    pub multiple: bool,
+}
+
+/// Result of matching argv against a spec. `command` is the path of selected subcommands and `args`
+/// holds every argument along that path, outer commands first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matches {
+    pub command: Vec<String>,
+    pub args: Vec<MatchedArg>,
+    pub action: Option<Action>,
+    pub trailing: Vec<String>,
+}
+
+impl Matches {
+    pub fn to_json(&self) -> Json {
+        let args = self
+            .args
+            .iter()
+            .map(|a| {
+                let value = if a.multiple {
+                    Json::Array(a.values.iter().map(value_json).collect())
+                } else {
+                    a.values.first().map_or(Json::Null, value_json)
+                };
+                let fields = Json::object([
+                    ("kind", Json::str(a.kind.as_str())),
+                    ("type", Json::str(a.ty.as_str())),
+                    ("source", Json::str(a.source.as_str())),
+                    ("value", value),
+                ]);
+                (a.name.clone(), fields)
+            })
+            .collect();
+
+        Json::object([
+            ("version", Json::Int(JSON_VERSION)),
+            ("command", strings_json(&self.command)),
+            ("action", Json::opt_str(self.action.map(|a| a.as_str()))),
+            ("args", Json::Object(args)),
+            ("trailing", strings_json(&self.trailing)),
+        ])
+    }
+}
+
+impl Matches {
+    /// `NAME=value` assignments for `eval` in bash or zsh. Every value is single-quoted, arguments
+    /// that accept several values become arrays, and unset arguments are assigned empty values.
+    pub fn to_shell(&self, prefix: &str) -> String {
+        let mut out = String::new();
+        let mut assign = |name: &str, value: String| {
+            out.push_str(&format!("{}={value}\n", shell_var(prefix, name)));
+        };
+
+        assign("command", shell_quote(&self.command.join(" ")));
+        if let Some(action) = self.action {
+            assign("action", shell_quote(action.as_str()));
+        }
+        for arg in &self.args {
+            let value = if arg.multiple {
+                shell_array(arg.values.iter().map(Value::to_string))
+            } else {
+                shell_quote(&arg.values.first().map(Value::to_string).unwrap_or_default())
+            };
+            assign(&arg.name, value);
+        }
+        assign("trailing", shell_array(self.trailing.iter().cloned()));
+        out
+    }
+}
+
+fn value_json(v: &Value) -> Json {
+    match v {
+        Value::Str(s) => Json::str(s.as_str()),
+        Value::Int(i) => Json::Int(*i),
+        Value::Float(x) => Json::Float(*x),
+        Value::Bool(b) => Json::Bool(*b),
+    }
+}
+
+fn strings_json(v: &[String]) -> Json {
+    Json::Array(v.iter().map(|s| Json::str(s.as_str())).collect())
+}
+
+fn fmt_value(v: &Value) -> String {
+    match v {
+        Value::Str(s) => format!("{s:?}"),
+        v => v.to_string(),
+    }
+}
+
+// One `name = value` line per argument, with lists for multiple values and the source noted when
+// the value did not come from argv
+impl fmt::Display for Matches {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.command.is_empty() {
+            writeln!(f, "command = {}", self.command.join(" "))?;
+        }
+        if let Some(action) = self.action {
+            return writeln!(f, "action = {action}");
+        }
+
+        for arg in &self.args {
+            let value = if arg.multiple {
+                let values: Vec<String> = arg.values.iter().map(fmt_value).collect();
+                format!("[{}]", values.join(", "))
+            } else {
+                arg.values.first().map(fmt_value).unwrap_or_default()
+            };
+            match arg.source {
+                Source::Argv => writeln!(f, "{} = {value}", arg.name)?,
+                Source::Default => writeln!(f, "{} = {value} (default)", arg.name)?,
+                Source::Unset => writeln!(f, "{} = (unset)", arg.name)?,
+            }
+        }
+
+        if !self.trailing.is_empty() {
+            let words: Vec<String> = self.trailing.iter().map(|t| format!("{t:?}")).collect();
+            writeln!(f, "trailing = [{}]", words.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnknownOption(String),
+    UnexpectedArgument(String),
+    FlagWithValue(String),
+    MissingValue(String, Nargs),
+    TooManyValues(String, Nargs),
+    MissingRequired(String),
+    InvalidValue(String, ArgType, String),
+    NotAllowed(String, String, Vec<String>),
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::UnknownOption(_) => "unknown_option",
+            ErrorKind::UnexpectedArgument(_) => "unexpected_argument",
+            ErrorKind::FlagWithValue(_) => "flag_with_value",
+            ErrorKind::MissingValue(..) => "missing_value",
+            ErrorKind::TooManyValues(..) => "too_many_values",
+            ErrorKind::MissingRequired(_) => "missing_required",
+            ErrorKind::InvalidValue(..) => "invalid_value",
+            ErrorKind::NotAllowed(..) => "not_allowed",
+        }
+    }
+
+    /// The option or argument the error is about, as the user wrote it.
+    pub fn subject(&self) -> &str {
+        match self {
+            ErrorKind::UnknownOption(s)
+            | ErrorKind::UnexpectedArgument(s)
+            | ErrorKind::FlagWithValue(s)
+            | ErrorKind::MissingValue(s, _)
+            | ErrorKind::TooManyValues(s, _)
+            | ErrorKind::MissingRequired(s)
+            | ErrorKind::InvalidValue(s, ..)
+            | ErrorKind::NotAllowed(s, ..) => s,
+        }
+    }
+}
+
+/// A usage error, with the command path it occurred in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchError {
+    pub command: Vec<String>,
+    pub kind: ErrorKind,
+}
+
+impl MatchError {
+    pub fn to_json(&self) -> Json {
+        let error = Json::object([
+            ("kind", Json::str(self.kind.as_str())),
+            ("subject", Json::str(self.kind.subject())),
+            ("message", Json::str(self.to_string())),
+        ]);
+        Json::object([
+            ("version", Json::Int(JSON_VERSION)),
+            ("command", strings_json(&self.command)),
+            ("error", error),
+        ])
+    }
+}
+
+/// How matched arguments are written to stdout. `Shell` holds the prefix for variable names.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    Text,
+    Json,
+    Shell(String),
+}
+
+impl Output {
+    pub const NAMES: [&str; 3] = ["text", "json", "shell"];
+    pub const SHELL_PREFIX: &str = "ARGOL_";
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(Output::Text),
+            "json" => Some(Output::Json),
+            "shell" => Some(Output::Shell(Output::SHELL_PREFIX.into())),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, matches: &Matches) -> String {
+        match self {
+            Output::Text => matches.to_string(),
+            Output::Json => format!("{}\n", matches.to_json()),
+            Output::Shell(prefix) => matches.to_shell(prefix),
+        }
+    }
+
+    /// What to write to stdout for a usage error, on top of the message on stderr. Shell output
+    /// exits so that a script running `eval "$(...)"` stops instead of carrying on without values.
+    pub fn render_error(&self, err: &MatchError) -> Option<String> {
+        match self {
+            Output::Text => None,
+            Output::Json => Some(format!("{}\n", err.to_json())),
+            Output::Shell(_) => Some("exit 2\n".into()),
+        }
+    }
+}
+
+/// Text an action flag asks to print, such as the help for the selected command.
+pub fn action_text(program: &Program, matches: &Matches) -> Option<String> {
+    match matches.action? {
+        Action::ShowHelp => Some(help::render(program, &matches.command, help::width())),
+        Action::ShowVersion => {
+            let version = program.version.as_deref().unwrap_or_default();
+            match &program.name {
+                Some(name) => Some(format!("{name} {version}\n")),
+                None => Some(format!("{version}\n")),
+            }
+        }
+    }
+}
+
+/// The line pointing at the help flag of the command at `path`, if it has one.
+pub fn help_hint(program: &Program, path: &[String]) -> Option<String> {
+    let mut cmd = &program.cmd;
+    for name in path {
+        cmd = cmd.commands.iter().find(|c| c.names.contains(name))?;
+    }
+    let flag = cmd
+        .flags
+        .iter()
+        .find(|f| f.action == Some(Action::ShowHelp))?;
+    let switch = match (flag.names.long.first(), flag.names.short) {
+        (Some(l), _) => format!("--{l}"),
+        (None, s) => format!("-{}", s?),
+    };
+    let words: Vec<&str> = program
+        .name
+        .as_deref()
+        .into_iter()
+        .chain(path.iter().map(String::as_str))
+        .chain([switch.as_str()])
+        .collect();
+    Some(format!("For more information, try '{}'.", words.join(" ")))
+}
+
+/// Print the outcome of [`match_args`] in `output`'s format and return the exit status. Text an
+/// action asks for replaces text output, is printed by the `eval`ed script for shell output, and
+/// goes to stderr alongside the JSON document.
+pub fn report(program: &Program, result: &Result<Matches, MatchError>, output: &Output) -> i32 {
+    let matches = match result {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("error: {e}");
+            if let Some(hint) = help_hint(program, &e.command) {
+                eprintln!("\n{hint}");
+            }
+            if let Some(doc) = output.render_error(e) {
+                print!("{doc}");
+            }
+            return 2;
+        }
+    };
+
+    match (output, action_text(program, matches)) {
+        (_, None) => print!("{}", output.render(matches)),
+        (Output::Text, Some(text)) => print!("{text}"),
+        (Output::Json, Some(text)) => {
+            eprint!("{text}");
+            print!("{}", output.render(matches));
+        }
+        (Output::Shell(_), Some(text)) => {
+            println!("printf '%s\\n' {}", shell_quote(text.trim_end()));
+            println!("exit 0");
+        }
+    }
+    0
+}
+
+/// Whether `s` can start a shell variable name, i.e. matches `[A-Za-z_][A-Za-z0-9_]*`. An empty
+/// prefix is refused: it would let arguments like `path` assign `PATH`.
+pub fn is_shell_prefix(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Each pair of names that would assign the same variable in shell output with `prefix`: an
+/// argument named like a variable every command sets, or two arguments in scope of one command
+/// whose names differ only in case or punctuation.
+pub fn shell_clashes(program: &Program, prefix: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    shell_clashes_in(&program.cmd, Vec::new(), prefix, &mut out);
+    out
+}
+
+// `names` are the arguments of the commands above `cmd`, which its own replace by name like in
+// the output
+fn shell_clashes_in(cmd: &Command, mut names: Vec<String>, prefix: &str, out: &mut Vec<String>) {
+    let own: Vec<String> = cmd
+        .flags
+        .iter()
+        .map(|f| f.names.primary())
+        .chain(cmd.positionals.iter().map(|p| p.name.clone()))
+        .chain(cmd.named.iter().map(|n| n.names.primary()))
+        .collect();
+    names.retain(|n| !own.contains(n));
+    names.extend(own);
+
+    let mut first: HashMap<String, String> = ["command", "action", "trailing"]
+        .iter()
+        .map(|v| (shell_var(prefix, v), format!("the `{v}` variable")))
+        .collect();
+    for name in &names {
+        let var = shell_var(prefix, name);
+        let origin = format!("argument `{name}`");
+        match first.get(&var) {
+            Some(prev) => {
+                let clash = format!("{prev} and {origin} both set `{var}`");
+                if !out.contains(&clash) {
+                    out.push(clash);
+                }
+            }
+            None => {
+                first.insert(var, origin);
+            }
+        }
+    }
+    for sub in &cmd.commands {
+        shell_clashes_in(sub, names.clone(), prefix, out);
+    }
+}
+
+/// `s` in single quotes, safe to paste into a shell script.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// The variable `name` is assigned to, e.g. `ARGOL_DRY_RUN` for `dry-run`.
+pub fn shell_var(prefix: &str, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{prefix}{name}")
+}
+
+fn shell_array(items: impl Iterator<Item = String>) -> String {
+    let items: Vec<String> = items.map(|s| shell_quote(&s)).collect();
+    format!("({})", items.join(" "))
+}
+
+/// How many values `n` allows, as used in error messages.
+pub fn fmt_nargs(n: &Nargs) -> String {
+    match (n.min, n.max) {
+        (min, Some(max)) if min == max => format!("{min}"),
+        (0, None) => "any number of".into(),
+        (min, None) => format!("at least {min}"),
+        (min, Some(max)) => format!("{min} to {max}"),
+    }
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::UnknownOption(s) => write!(f, "unknown option '{s}'"),
+            ErrorKind::UnexpectedArgument(s) => write!(f, "unexpected argument '{s}'"),
+            ErrorKind::FlagWithValue(s) => write!(f, "flag '{s}' does not take a value"),
+            ErrorKind::MissingValue(s, n) => {
+                write!(f, "'{s}' expects {} value(s)", fmt_nargs(n))
+            }
+            ErrorKind::TooManyValues(s, n) => {
+                write!(f, "'{s}' expects {} value(s)", fmt_nargs(n))
+            }
+            ErrorKind::MissingRequired(s) => write!(f, "missing required argument '{s}'"),
+            ErrorKind::InvalidValue(s, ty, v) => {
+                write!(f, "invalid value '{v}' for '{s}': expected {ty}")
+            }
+            ErrorKind::NotAllowed(s, v, allowed) => write!(
+                f,
+                "invalid value '{v}' for '{s}' (possible values: {})",
+                allowed.join(", ")
+            ),
+        }?;
+        if !self.command.is_empty() {
+            write!(f, " (in {})", self.command.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Match `args` (without the program name) against `program`.
+pub fn match_args(program: &Program, args: &[String]) -> Result<Matches, MatchError> {
+    let mut matches = Matches {
+        command: Vec::new(),
+        args: Vec::new(),
+        action: None,
+        trailing: Vec::new(),
+    };
+    let mut cmd = &program.cmd;
+    let mut rest = args;
+
+    loop {
+        let err = |kind| MatchError {
+            command: matches.command.clone(),
+            kind,
+        };
+        let mut state = CommandMatcher::new(cmd);
+        let sub = state.run(rest).map_err(err)?;
+
+        if let Some(action) = state.action {
+            matches.action = Some(action);
+            return Ok(matches);
+        }
+
+        let (args, trailing) = state.finish().map_err(err)?;
+        let names: Vec<&str> = args.iter().map(|a| a.name.as_str()).collect();
+        matches.args.retain(|a| !names.contains(&a.name.as_str()));
+        matches.args.extend(args);
+        matches.trailing.extend(trailing);
+
+        match sub {
+            Some((next, i)) => {
+                matches.command.push(next.names[0].clone());
+                cmd = next;
+                rest = &rest[i + 1..];
+            }
+            None => return Ok(matches),
+        }
+    }
+}
+
+struct CommandMatcher<'a> {
+    cmd: &'a Command,
+    flags: Vec<bool>,
+    named: Vec<Vec<String>>,
+    positionals: Vec<String>,
+    // How many positionals were given before `--`, once it has been seen
+    after_dashes: Option<usize>,
+    action: Option<Action>,
+}
+
+impl<'a> CommandMatcher<'a> {
+    fn new(cmd: &'a Command) -> Self {
+        CommandMatcher {
+            cmd,
+            flags: vec![false; cmd.flags.len()],
+            named: vec![Vec::new(); cmd.named.len()],
+            positionals: Vec::new(),
+            after_dashes: None,
+            action: None,
+        }
+    }
+
+    // Consume arguments until they run out or a subcommand is found, returning the subcommand and
+    // its index in `args`
+    fn run(&mut self, args: &[String]) -> Result<Option<(&'a Command, usize)>, ErrorKind> {
+        let mut i = 0;
+        while i < args.len() {
+            let arg = &args[i];
+            i += 1;
+
+            if self.after_dashes.is_some() {
+                self.positionals.push(arg.clone());
+            } else if arg == "--" {
+                self.after_dashes = Some(self.positionals.len());
+            } else if let Some(long) = arg.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((n, v)) => (n, Some(v.to_string())),
+                    None => (long, None),
+                };
+                i = self.long(name, value, args, i)?;
+            } else if arg.len() > 1 && arg.starts_with('-') && !self.is_value(arg) {
+                i = self.short(&arg[1..], args, i)?;
+            } else if let Some(sub) = self.subcommand(arg) {
+                return Ok(Some((sub, i - 1)));
+            } else {
+                self.positionals.push(arg.clone());
+            }
+
+            if self.action.is_some() {
+                return Ok(None);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn subcommand(&self, arg: &str) -> Option<&'a Command> {
+        if !self.positionals.is_empty() {
+            return None;
+        }
+        self.cmd
+            .commands
+            .iter()
+            .find(|c| c.names.iter().any(|n| n == arg))
+    }
+
+    // A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short name claims it
+    fn is_value(&self, arg: &str) -> bool {
+        let Some(c) = arg[1..].chars().next() else {
+            return false;
+        };
+        let claimed = self.cmd.flags.iter().any(|f| f.names.short == Some(c))
+            || self.cmd.named.iter().any(|n| n.names.short == Some(c));
+        !claimed && is_number(&arg[1..])
+    }
+
+    fn long(
+        &mut self,
+        name: &str,
+        value: Option<String>,
+        args: &[String],
+        i: usize,
+    ) -> Result<usize, ErrorKind> {
+        let has_name = |names: &Names| names.long.iter().any(|l| l == name);
+
+        if let Some(idx) = self.cmd.flags.iter().position(|f| has_name(&f.names)) {
+            if value.is_some() {
+                return Err(ErrorKind::FlagWithValue(format!("--{name}")));
+            }
+            self.set_flag(idx);
+            return Ok(i);
+        }
+
+        if let Some(idx) = self.cmd.named.iter().position(|n| has_name(&n.names)) {
+            return self.take_named(idx, value, args, i);
+        }
+
+        Err(ErrorKind::UnknownOption(format!("--{name}")))
+    }
+
+    fn short(&mut self, cluster: &str, args: &[String], i: usize) -> Result<usize, ErrorKind> {
+        for (pos, c) in cluster.char_indices() {
+            if let Some(idx) = self.cmd.flags.iter().position(|f| f.names.short == Some(c)) {
+                self.set_flag(idx);
+                if self.action.is_some() {
+                    return Ok(i);
+                }
+                continue;
+            }
+
+            if let Some(idx) = self.cmd.named.iter().position(|n| n.names.short == Some(c)) {
+                let rest = &cluster[pos + c.len_utf8()..];
+                let rest = rest.strip_prefix('=').unwrap_or(rest);
+                let value = (!rest.is_empty()).then(|| rest.to_string());
+                return self.take_named(idx, value, args, i);
+            }
+
+            return Err(ErrorKind::UnknownOption(format!("-{c}")));
+        }
+
+        Ok(i)
+    }
+
+    fn set_flag(&mut self, idx: usize) {
+        self.flags[idx] = true;
+        if let Some(action) = self.cmd.flags[idx].action {
+            self.action = Some(action);
+        }
+    }
+
+    // Collect the values of a named argument: an inline `=value` counts as one, then following
+    // words are taken up to the maximum, stopping at anything that looks like an option
+    fn take_named(
+        &mut self,
+        idx: usize,
+        inline: Option<String>,
+        args: &[String],
+        mut i: usize,
+    ) -> Result<usize, ErrorKind> {
+        let named = &self.cmd.named[idx];
+        let max = named.nargs.max.unwrap_or(usize::MAX);
+        let mut values: Vec<String> = inline.into_iter().collect();
+
+        while values.len() < max && i < args.len() {
+            let arg = &args[i];
+            let is_option =
+                arg == "--" || arg.len() > 1 && arg.starts_with('-') && !self.is_value(arg);
+            if is_option {
+                break;
+            }
+            values.push(arg.clone());
+            i += 1;
+        }
+
+        if values.len() < named.nargs.min {
+            let name = format!("--{}", named.names.primary());
+            return Err(ErrorKind::MissingValue(name, named.nargs));
+        }
+
+        if named.nargs.max == Some(1) {
+            self.named[idx] = values;
+        } else {
+            self.named[idx].extend(values);
+        }
+        Ok(i)
+    }
+
+    fn finish(self) -> Result<(Vec<MatchedArg>, Vec<String>), ErrorKind> {
+        let mut out = Vec::new();
+
+        for (flag, &set) in self.cmd.flags.iter().zip(&self.flags) {
+            out.push(MatchedArg {
+                name: flag.names.primary(),
+                kind: ArgKind::Flag,
+                ty: ArgType::Bool,
+                values: vec![Value::Bool(set)],
+                source: if set { Source::Argv } else { Source::Default },
+                multiple: false,
+            });
+        }
+
+        // Hand out positional words in order, leaving enough for the minimum of later positionals
+        let total = self.positionals.len();
+        let mut words = self.positionals.into_iter();
+        let mut remaining = total;
+        let mins: Vec<usize> = self.cmd.positionals.iter().map(|p| p.nargs.min).collect();
+        for (i, pos) in self.cmd.positionals.iter().enumerate() {
+            let reserved: usize = mins[i + 1..].iter().sum();
+            let available = remaining.saturating_sub(reserved);
+            let take = pos.nargs.max.map_or(available, |m| m.min(available));
+            let given: Vec<String> = words.by_ref().take(take).collect();
+            remaining -= given.len();
+
+            if !given.is_empty() && given.len() < pos.nargs.min {
+                return Err(ErrorKind::MissingValue(pos.name.clone(), pos.nargs));
+            }
+            out.push(MatchedArg {
+                multiple: pos.nargs.max != Some(1),
+                ..resolve(
+                    &pos.name,
+                    ArgKind::Positional,
+                    pos.ty,
+                    given,
+                    pos.required,
+                    &pos.default,
+                    pos.options.as_ref(),
+                )?
+            });
+        }
+
+        // Leftover words are only allowed after `--`, where they are passed through as trailing
+        let extra: Vec<String> = words.collect();
+        if !extra.is_empty() && self.after_dashes.is_none_or(|n| total - extra.len() < n) {
+            return Err(ErrorKind::UnexpectedArgument(extra[0].clone()));
+        }
+
+        for (named, given) in self.cmd.named.iter().zip(self.named) {
+            let name = named.names.primary();
+            if let Some(max) = named.nargs.max
+                && given.len() > max
+            {
+                return Err(ErrorKind::TooManyValues(format!("--{name}"), named.nargs));
+            }
+            out.push(MatchedArg {
+                multiple: named.nargs.max != Some(1),
+                ..resolve(
+                    &name,
+                    ArgKind::Named,
+                    named.ty,
+                    given,
+                    named.required,
+                    &named.default,
+                    named.options.as_ref(),
+                )?
+            });
+        }
+
+        Ok((out, extra))
+    }
+}
+
+fn resolve(
+    name: &str,
+    kind: ArgKind,
+    ty: ArgType,
+    given: Vec<String>,
+    required: bool,
+    default: &[Value],
+    options: Option<&Options>,
+) -> Result<MatchedArg, ErrorKind> {
+    let (values, source) = if !given.is_empty() {
+        let values = given
+            .iter()
+            .map(|v| convert(name, ty, v, options))
+            .collect::<Result<_, _>>()?;
+        (values, Source::Argv)
+    } else if required {
+        return Err(ErrorKind::MissingRequired(name.to_string()));
+    } else if !default.is_empty() {
+        (default.to_vec(), Source::Default)
+    } else {
+        (Vec::new(), Source::Unset)
+    };
+
+    Ok(MatchedArg {
+        name: name.to_string(),
+        kind,
+        ty,
+        values,
+        source,
+        multiple: false,
+    })
+}
+
+pub fn convert(
+    name: &str,
+    ty: ArgType,
+    s: &str,
+    options: Option<&Options>,
+) -> Result<Value, ErrorKind> {
+    let invalid = || ErrorKind::InvalidValue(name.to_string(), ty, s.to_string());
+    let value = match ty {
+        ArgType::Str | ArgType::Path => Value::Str(s.to_string()),
+        ArgType::Int => Value::Int(s.parse().map_err(|_| invalid())?),
+        ArgType::UInt => {
+            let n: u64 = s.parse().map_err(|_| invalid())?;
+            Value::Int(i64::try_from(n).map_err(|_| invalid())?)
+        }
+        ArgType::Float if is_number(s) => Value::Float(s.parse().map_err(|_| invalid())?),
+        ArgType::Float => return Err(invalid()),
+        ArgType::Bool => Value::Bool(s.parse().map_err(|_| invalid())?),
+    };
+
+    let allowed = options.is_none_or(|o| o.allows(&value));
+
+    if !allowed {
+        let choices = match options {
+            Some(Options::Values(vals)) => vals.iter().map(|v| v.to_string()).collect(),
+            Some(Options::Range(r)) => vec![r.to_string()],
+            None => Vec::new(),
+        };
+        return Err(ErrorKind::NotAllowed(
+            name.to_string(),
+            s.to_string(),
+            choices,
+        ));
+    }
+
+    Ok(value)
+}
+
+// A finite float: `inf`, `nan` and literals that overflow to infinity are not numbers here
+fn is_number(s: &str) -> bool {
+    s.parse::<f64>().is_ok_and(f64::is_finite)
+}
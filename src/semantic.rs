@@ -0,0 +1,525 @@
+use crate::{
+    ast::{Assignment, Block},
+    common::errors::{Loc, ParseError, ParseError::*, SemanticError},
+    common::tokens::{Span, Token, TokenKind, allowed_keys},
+    spec::{
+        Action, ArgType, Command, Flag, Named, Names, Nargs, Options, Positional, Program, Range,
+        Value,
+    },
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+const LITERAL_KINDS: [TokenKind; 4] = [
+    TokenKind::String,
+    TokenKind::Int,
+    TokenKind::Float,
+    TokenKind::Boolean,
+];
+
+struct Analyzer<'a> {
+    path: &'a str,
+    has_version: bool,
+    errors: RefCell<Vec<ParseError>>,
+}
+
+pub fn analyze(root: &Block, path: &str) -> Result<Program, Vec<ParseError>> {
+    let analyzer = Analyzer {
+        path,
+        has_version: find(root, "!version").is_some(),
+        errors: RefCell::new(Vec::new()),
+    };
+    analyzer.check_keys(root);
+
+    let name = analyzer.recover(analyzer.string(root, "!program"));
+    let version = analyzer.recover(analyzer.string(root, "!version"));
+    let cmd = analyzer.lower_command(root);
+
+    let errors = analyzer.errors.into_inner();
+    if errors.is_empty() {
+        Ok(Program { name, version, cmd })
+    } else {
+        Err(errors)
+    }
+}
+
+impl Analyzer<'_> {
+    // Record a failed lowering step and carry on with a placeholder so later checks still run
+    fn recover<T: Default>(&self, res: Result<T, ParseError>) -> T {
+        res.unwrap_or_else(|e| {
+            self.errors.borrow_mut().push(e);
+            T::default()
+        })
+    }
+
+    fn lower_command(&self, block: &Block) -> Command {
+        self.check_sibling_names(block);
+        let mut cmd = Command {
+            names: block.identifiers.iter().map(|t| t.lexeme.clone()).collect(),
+            desc: self.recover(self.string(block, "desc")),
+            flags: Vec::new(),
+            positionals: Vec::new(),
+            named: Vec::new(),
+            commands: Vec::new(),
+        };
+
+        for child in &block.blocks {
+            self.check_keys(child);
+            match child.keyword.as_str() {
+                "command" => cmd.commands.push(self.lower_command(child)),
+                "flag" => cmd.flags.push(self.lower_flag(child)),
+                "pos" => cmd.positionals.push(self.lower_positional(child)),
+                "named" => cmd.named.push(self.lower_named(child)),
+                _ => unreachable!(),
+            }
+        }
+
+        cmd
+    }
+
+    // Arguments of a command share one namespace (their names become option words and output
+    // keys), and subcommands another
+    fn check_sibling_names(&self, block: &Block) {
+        let mut args: HashMap<&str, Loc> = HashMap::new();
+        let mut commands: HashMap<&str, Loc> = HashMap::new();
+        for child in &block.blocks {
+            let seen = match child.keyword.as_str() {
+                "command" => &mut commands,
+                _ => &mut args,
+            };
+            // A name repeated within one block is reported by the parser
+            let mut own = HashSet::new();
+            for ident in &child.identifiers {
+                if !own.insert(ident.lexeme.as_str()) {
+                    continue;
+                }
+                let loc = self.get_loc(ident);
+                if let Some(first) = seen.get(ident.lexeme.as_str()) {
+                    let err = SemanticError::DuplicateSiblingName(
+                        loc,
+                        Box::new(first.clone()),
+                        ident.lexeme.clone(),
+                    );
+                    self.errors.borrow_mut().push(Semantic(Box::new(err)));
+                } else {
+                    seen.insert(&ident.lexeme, loc);
+                }
+            }
+        }
+    }
+
+    fn lower_flag(&self, block: &Block) -> Flag {
+        let tok = self.recover(self.single(block, "action", &[TokenKind::Action]));
+        let action = tok.and_then(|tok| Action::from_lexeme(&tok.lexeme));
+        if let Some(tok) = tok
+            && action == Some(Action::ShowVersion)
+            && !self.has_version
+        {
+            let err = SemanticError::MissingVersion(self.get_loc(tok));
+            self.errors.borrow_mut().push(Semantic(Box::new(err)));
+        }
+
+        Flag {
+            names: names(block),
+            desc: self.recover(self.string(block, "desc")),
+            action,
+        }
+    }
+
+    fn lower_positional(&self, block: &Block) -> Positional {
+        let (ty, default, options) = self.typed_values(block);
+        let nargs = self.recover(self.nargs(block));
+        self.check_default(block, &default, options.as_ref(), nargs);
+        Positional {
+            name: names(block).primary(),
+            desc: self.recover(self.string(block, "desc")),
+            required: self
+                .recover(self.boolean(block, "required"))
+                .unwrap_or(false),
+            default,
+            ty,
+            options,
+            nargs,
+        }
+    }
+
+    fn lower_named(&self, block: &Block) -> Named {
+        let (ty, default, options) = self.typed_values(block);
+        let nargs = self.recover(self.nargs(block));
+        self.check_default(block, &default, options.as_ref(), nargs);
+        Named {
+            names: names(block),
+            desc: self.recover(self.string(block, "desc")),
+            required: self
+                .recover(self.boolean(block, "required"))
+                .unwrap_or(false),
+            default,
+            ty,
+            options,
+            nargs,
+        }
+    }
+
+    // The type, and the default and options typed by it. Values are left unchecked when the type
+    // is invalid, instead of being reported against the `string` it falls back to
+    fn typed_values(&self, block: &Block) -> (ArgType, Vec<Value>, Option<Options>) {
+        let ty = match self.ty(block) {
+            Ok(_) if block.invalid_keys.iter().any(|k| k == "type") => None,
+            Ok(ty) => Some(ty),
+            Err(e) => {
+                self.errors.borrow_mut().push(e);
+                None
+            }
+        };
+        match ty {
+            Some(ty) => (
+                ty,
+                self.recover(self.default(block, ty)),
+                self.recover(self.options(block, ty)),
+            ),
+            None => (ArgType::default(), Vec::new(), None),
+        }
+    }
+
+    // A default must be a value the argument could have been given: one of its options, and as
+    // many values as `nargs` takes
+    fn check_default(
+        &self,
+        block: &Block,
+        default: &[Value],
+        options: Option<&Options>,
+        nargs: Nargs,
+    ) {
+        let Some(a) = find(block, "default") else {
+            return;
+        };
+        // `default` only lowers when every value typed correctly
+        if default.is_empty() || default.len() != a.values.len() {
+            return;
+        }
+
+        let mut errors = self.errors.borrow_mut();
+        if let Some(options) = options {
+            for (v, tok) in default.iter().zip(&a.values) {
+                if !options.allows(v) {
+                    let err = SemanticError::DefaultNotAllowed(
+                        self.get_loc(tok),
+                        tok.lexeme.clone(),
+                        options.clone(),
+                    );
+                    errors.push(Semantic(Box::new(err)));
+                }
+            }
+        }
+
+        let n = default.len();
+        if n < nargs.min || nargs.max.is_some_and(|max| n > max) {
+            // Point at the first value too many, or the last one when there are too few
+            let tok = &a.values[nargs.max.unwrap_or(n).min(n - 1)];
+            let err = SemanticError::DefaultCount(self.get_loc(tok), n, nargs);
+            errors.push(Semantic(Box::new(err)));
+        }
+    }
+
+    fn string(&self, block: &Block, key: &str) -> Result<Option<String>, ParseError> {
+        let tok = self.single(block, key, &[TokenKind::String])?;
+        Ok(tok.map(|t| t.lexeme.clone()))
+    }
+
+    fn boolean(&self, block: &Block, key: &str) -> Result<Option<bool>, ParseError> {
+        let tok = self.single(block, key, &[TokenKind::Boolean])?;
+        Ok(tok.map(|t| t.lexeme == "true"))
+    }
+
+    fn ty(&self, block: &Block) -> Result<ArgType, ParseError> {
+        let tok = self.single(block, "type", &[TokenKind::Type])?;
+        Ok(tok
+            .and_then(|t| ArgType::from_lexeme(&t.lexeme))
+            .unwrap_or(ArgType::Str))
+    }
+
+    fn default(&self, block: &Block, ty: ArgType) -> Result<Vec<Value>, ParseError> {
+        let Some(a) = find(block, "default") else {
+            return Ok(Vec::new());
+        };
+
+        a.values
+            .iter()
+            .map(|tok| {
+                self.expect_kind(a, tok, &LITERAL_KINDS)?;
+                self.typed(ty, self.literal(tok)?, self.get_loc(tok), &tok.lexeme)
+            })
+            .collect()
+    }
+
+    fn options(&self, block: &Block, ty: ArgType) -> Result<Option<Options>, ParseError> {
+        let Some(a) = find(block, "options") else {
+            return Ok(None);
+        };
+
+        if a.kind == TokenKind::ExclusiveRange || a.kind == TokenKind::InclusiveRange {
+            let tok = self.single(block, "options", &[a.kind])?.unwrap();
+            return Ok(Some(Options::Range(self.typed_range(ty, tok)?)));
+        }
+
+        let values = a
+            .values
+            .iter()
+            .map(|tok| {
+                self.expect_kind(a, tok, &LITERAL_KINDS)?;
+                self.typed(ty, self.literal(tok)?, self.get_loc(tok), &tok.lexeme)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(Options::Values(values)))
+    }
+
+    fn typed_range(&self, ty: ArgType, tok: &Token) -> Result<Range, ParseError> {
+        if !matches!(ty, ArgType::Int | ArgType::UInt | ArgType::Float) {
+            return Err(Semantic(Box::new(SemanticError::TypeMismatch(
+                self.get_loc(tok),
+                ty,
+                tok.lexeme.clone(),
+            ))));
+        }
+
+        let range = self.range(tok)?;
+        let (start, end) = tok.lexeme.split_once("..").unwrap();
+        let end_col = tok.col + tok.lexeme.len() - end.trim_start_matches('=').len();
+
+        let bound = |v: Option<Value>, s: &str, col: usize| {
+            let mut loc = self.get_loc(tok);
+            loc.col = col;
+            loc.len = s.trim_start_matches('=').len();
+            loc.span.start = tok.span.start + (col - tok.col);
+            loc.span.end = loc.span.start + loc.len;
+            v.map(|v| self.typed(ty, v, loc, s.trim_start_matches('=')))
+                .transpose()
+        };
+
+        Ok(Range {
+            start: bound(range.start, start, tok.col)?,
+            end: bound(range.end, end, end_col)?,
+            inclusive: range.inclusive,
+        })
+    }
+
+    fn typed(&self, ty: ArgType, v: Value, loc: Loc, lexeme: &str) -> Result<Value, ParseError> {
+        match (ty, v) {
+            (ArgType::Str | ArgType::Path, v @ Value::Str(_))
+            | (ArgType::Int, v @ Value::Int(_))
+            | (ArgType::Float, v @ Value::Float(_))
+            | (ArgType::Bool, v @ Value::Bool(_)) => Ok(v),
+            (ArgType::Float, Value::Int(i)) => Ok(Value::Float(i as f64)),
+            (ArgType::UInt, Value::Int(i)) if i < 0 => Err(Semantic(Box::new(
+                SemanticError::NegativeUnsigned(loc, lexeme.to_string()),
+            ))),
+            (ArgType::UInt, v @ Value::Int(_)) => Ok(v),
+            _ => Err(Semantic(Box::new(SemanticError::TypeMismatch(
+                loc,
+                ty,
+                lexeme.to_string(),
+            )))),
+        }
+    }
+
+    fn nargs(&self, block: &Block) -> Result<Nargs, ParseError> {
+        let kinds = [
+            TokenKind::Int,
+            TokenKind::Wildcard,
+            TokenKind::ExclusiveRange,
+            TokenKind::InclusiveRange,
+        ];
+        let Some(tok) = self.single(block, "nargs", &kinds)? else {
+            return Ok(Nargs::ONE);
+        };
+
+        let count = |v: &Value| match v {
+            Value::Int(i) if *i >= 0 => Ok(*i as usize),
+            _ => Err(Semantic(Box::new(SemanticError::InvalidNargs(
+                self.get_loc(tok),
+                tok.lexeme.clone(),
+            )))),
+        };
+
+        match tok.kind {
+            TokenKind::Wildcard => Ok(Nargs { min: 0, max: None }),
+            TokenKind::Int => {
+                let n = count(&self.literal(tok)?)?;
+                Ok(Nargs {
+                    min: n,
+                    max: Some(n),
+                })
+            }
+            _ => {
+                let range = self.range(tok)?;
+                let min = range.start.as_ref().map(count).transpose()?.unwrap_or(0);
+                let max = match range.end.as_ref().map(count).transpose()? {
+                    Some(end) if range.inclusive => Some(end),
+                    Some(end) if end > 0 => Some(end - 1),
+                    Some(_) => {
+                        return Err(Semantic(Box::new(SemanticError::InvalidNargs(
+                            self.get_loc(tok),
+                            tok.lexeme.clone(),
+                        ))));
+                    }
+                    None => None,
+                };
+
+                if max.is_some_and(|m| m < min) {
+                    return Err(Semantic(Box::new(SemanticError::InvalidNargs(
+                        self.get_loc(tok),
+                        tok.lexeme.clone(),
+                    ))));
+                }
+
+                Ok(Nargs { min, max })
+            }
+        }
+    }
+
+    fn range(&self, tok: &Token) -> Result<Range, ParseError> {
+        let (start, end) = tok.lexeme.split_once("..").unwrap();
+        let (end, inclusive) = match end.strip_prefix('=') {
+            Some(e) => (e, true),
+            None => (end, false),
+        };
+
+        let bound = |s: &str| {
+            if s.is_empty() {
+                Ok(None)
+            } else {
+                parse_number(s).map(Some).ok_or_else(|| {
+                    Semantic(Box::new(SemanticError::NumberOutOfRange(
+                        self.get_loc(tok),
+                        s.to_string(),
+                    )))
+                })
+            }
+        };
+
+        Ok(Range {
+            start: bound(start)?,
+            end: bound(end)?,
+            inclusive,
+        })
+    }
+
+    fn literal(&self, tok: &Token) -> Result<Value, ParseError> {
+        match tok.kind {
+            TokenKind::String => Ok(Value::Str(tok.lexeme.clone())),
+            TokenKind::Boolean => Ok(Value::Bool(tok.lexeme == "true")),
+            _ => parse_number(&tok.lexeme).ok_or_else(|| {
+                Semantic(Box::new(SemanticError::NumberOutOfRange(
+                    self.get_loc(tok),
+                    tok.lexeme.clone(),
+                )))
+            }),
+        }
+    }
+
+    fn single<'b>(
+        &self,
+        block: &'b Block,
+        key: &str,
+        kinds: &[TokenKind],
+    ) -> Result<Option<&'b Token>, ParseError> {
+        let Some(a) = find(block, key) else {
+            return Ok(None);
+        };
+
+        if a.values.len() > 1 {
+            let loc = self.get_loc(&a.values[1]);
+            return Err(Semantic(Box::new(SemanticError::MultipleValues(
+                loc,
+                a.key.clone(),
+            ))));
+        }
+
+        let tok = &a.values[0];
+        self.expect_kind(a, tok, kinds)?;
+        Ok(Some(tok))
+    }
+
+    fn expect_kind(
+        &self,
+        a: &Assignment,
+        tok: &Token,
+        kinds: &[TokenKind],
+    ) -> Result<(), ParseError> {
+        if kinds.contains(&tok.kind) {
+            Ok(())
+        } else {
+            Err(Semantic(Box::new(SemanticError::InvalidValueKind(
+                self.get_loc(tok),
+                a.key.clone(),
+                kinds.to_vec(),
+                tok.kind,
+            ))))
+        }
+    }
+
+    fn check_keys(&self, block: &Block) {
+        let allowed = allowed_keys(&block.keyword);
+        let mut seen: HashMap<&str, Loc> = HashMap::new();
+        for a in &block.assignments {
+            let loc = Loc {
+                path: self.path.to_string(),
+                line: a.line,
+                col: a.col,
+                len: a.key.len(),
+                span: Span {
+                    start: a.span.start,
+                    end: a.span.start + a.key.len(),
+                },
+            };
+
+            let err = if !allowed.contains(&a.key.as_str()) {
+                SemanticError::InvalidKey(loc, block.keyword.clone(), a.key.clone())
+            } else if let Some(first) = seen.get(a.key.as_str()) {
+                SemanticError::DuplicateKey(loc, Box::new(first.clone()), a.key.clone())
+            } else {
+                seen.insert(&a.key, loc);
+                continue;
+            };
+            self.errors.borrow_mut().push(Semantic(Box::new(err)));
+        }
+    }
+
+    fn get_loc(&self, tok: &Token) -> Loc {
+        Loc {
+            path: self.path.to_string(),
+            line: tok.line,
+            col: tok.col,
+            len: tok.len(),
+            span: tok.span,
+        }
+    }
+}
+
+fn find<'b>(block: &'b Block, key: &str) -> Option<&'b Assignment> {
+    block.assignments.iter().find(|a| a.key == key)
+}
+
+fn names(block: &Block) -> Names {
+    let mut short = None;
+    let mut long = Vec::new();
+    for ident in &block.identifiers {
+        let mut chars = ident.lexeme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => short = Some(c),
+            _ => long.push(ident.lexeme.clone()),
+        }
+    }
+
+    Names { short, long }
+}
+
+fn parse_number(lexeme: &str) -> Option<Value> {
+    let cleaned: String = lexeme.chars().filter(|&c| c != '_').collect();
+    if cleaned.contains(['.', 'e', 'E']) {
+        cleaned.parse().ok().map(Value::Float)
+    } else {
+        cleaned.parse().ok().map(Value::Int)
+    }
+}
@@ -0,0 +1,241 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ArgType {
+    #[default]
+    Str,
+    Int,
+    UInt,
+    Float,
+    Bool,
+    Path,
+}
+
+impl ArgType {
+    pub fn from_lexeme(s: &str) -> Option<Self> {
+        match s {
+            "string" => Some(ArgType::Str),
+            "int" => Some(ArgType::Int),
+            "uint" => Some(ArgType::UInt),
+            "float" => Some(ArgType::Float),
+            "bool" => Some(ArgType::Bool),
+            "path" => Some(ArgType::Path),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgType::Str => "string",
+            ArgType::Int => "int",
+            ArgType::UInt => "uint",
+            ArgType::Float => "float",
+            ArgType::Bool => "bool",
+            ArgType::Path => "path",
+        }
+    }
+}
+
+impl fmt::Display for ArgType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    ShowHelp,
+    ShowVersion,
+}
+
+impl Action {
+    pub fn from_lexeme(s: &str) -> Option<Self> {
+        match s {
+            "show_help" => Some(Action::ShowHelp),
+            "show_version" => Some(Action::ShowVersion),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::ShowHelp => "show_help",
+            Action::ShowVersion => "show_version",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Value {
+    // Order two numbers, integers exactly; strings and booleans are not ordered
+    fn cmp_number(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+            (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "{s}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(x) => write!(f, "{x}"),
+            Value::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: Option<Value>,
+    pub end: Option<Value>,
+    pub inclusive: bool,
+}
+
+impl Range {
+    /// Whether `v` is a number between the bounds.
+    pub fn contains(&self, v: &Value) -> bool {
+        let after_start = self
+            .start
+            .as_ref()
+            .is_none_or(|start| v.cmp_number(start).is_some_and(Ordering::is_ge));
+        let before_end = self.end.as_ref().is_none_or(|end| match v.cmp_number(end) {
+            Some(Ordering::Equal) => self.inclusive,
+            ord => ord == Some(Ordering::Less),
+        });
+        after_start && before_end
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(start) = &self.start {
+            write!(f, "{start}")?;
+        }
+        write!(f, "..{}", if self.inclusive { "=" } else { "" })?;
+        if let Some(end) = &self.end {
+            write!(f, "{end}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Options {
+    Values(Vec<Value>),
+    Range(Range),
+}
+
+impl Options {
+    /// Whether `v` is one of the values, or within the range. Shared by the checks on defaults
+    /// and on the values given at run time.
+    pub fn allows(&self, v: &Value) -> bool {
+        match self {
+            Options::Values(vals) => vals.contains(v),
+            Options::Range(r) => r.contains(v),
+        }
+    }
+}
+
+/// Number of values an argument consumes: `min..=max`, with `max == None` meaning unbounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nargs {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Nargs {
+    pub const ONE: Nargs = Nargs {
+        min: 1,
+        max: Some(1),
+    };
+}
+
+impl Default for Nargs {
+    fn default() -> Self {
+        Nargs::ONE
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Names {
+    pub short: Option<char>,
+    pub long: Vec<String>,
+}
+
+impl Names {
+    /// The name used to refer to the argument in output (first long name, else the short one).
+    pub fn primary(&self) -> String {
+        match (self.long.first(), self.short) {
+            (Some(l), _) => l.clone(),
+            (None, Some(s)) => s.to_string(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flag {
+    pub names: Names,
+    pub desc: Option<String>,
+    pub action: Option<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Positional {
+    pub name: String,
+    pub desc: Option<String>,
+    pub required: bool,
+    pub default: Vec<Value>,
+    pub ty: ArgType,
+    pub options: Option<Options>,
+    pub nargs: Nargs,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Named {
+    pub names: Names,
+    pub desc: Option<String>,
+    pub required: bool,
+    pub default: Vec<Value>,
+    pub ty: ArgType,
+    pub options: Option<Options>,
+    pub nargs: Nargs,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub names: Vec<String>,
+    pub desc: Option<String>,
+    pub flags: Vec<Flag>,
+    pub positionals: Vec<Positional>,
+    pub named: Vec<Named>,
+    pub commands: Vec<Command>,
+}
+
+/// Typed model of an argol spec; `cmd` holds the top-level arguments and subcommands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub cmd: Command,
+}
@@ -3,49 +3,112 @@ use std::path::Path;
 use std::str::Chars;
 
 use crate::common::errors::{Loc, ParseError, ParseError::*, SyntaxError};
-use crate::common::tokens::{Token, TokenKind};
+use crate::common::tokens::{
+    ACTIONS, BOOLEANS, DIRECTIVES, KEYWORDS, Span, TYPES, Token, TokenKind,
+};
 use crate::common::util::CharExtensions;
 
-pub fn tokenize(content: &str, path: &Path) -> Result<Vec<Token>, ParseError> {
+/// Tokens for `content`, skipping any malformed token so later stages still see the rest, and the
+/// lexical errors found along the way.
+pub fn tokenize(content: &str, path: &Path) -> (Vec<Token>, Vec<ParseError>) {
     let mut tokens: Vec<Token> = Vec::new();
+    let mut errors: Vec<ParseError> = Vec::new();
     let mut col = 0;
     let mut line = 1;
     let mut chars = content.chars().peekable();
     let path = path.to_string_lossy().to_string();
+    let index = LineIndex::new(content);
 
     while let Some(&ch) = chars.peek() {
-        match ch {
-            '#' | '\t' | '\n' | ' ' => skip_tokens(&mut chars, &mut line, &mut col),
+        let res = match ch {
+            '#' | '\t' | '\n' | ' ' => {
+                skip_tokens(&mut chars, &mut line, &mut col);
+                continue;
+            }
 
             ';' | '=' | '{' | '}' | '/' | ',' | '*' => {
-                let tok = parse_symbol(&mut chars, ch, &mut line, &mut col);
-                tokens.push(tok);
+                Ok(parse_symbol(&mut chars, ch, &mut line, &mut col))
             }
 
             'a'..='z' | 'A'..='Z' | '_' | '!' => {
-                let tok = parse_ident(&mut chars, &mut line, &mut col, &path)?;
-                tokens.push(tok);
+                parse_ident(&mut chars, &mut line, &mut col, &path)
             }
 
             '-' | '+' | '.' | '0'..='9' => {
-                let tok = parse_range_or_number(&mut chars, &mut line, &mut col, &path)?;
-                tokens.push(tok);
+                parse_range_or_number(&mut chars, &mut line, &mut col, &path)
             }
 
-            '"' => {
-                let tok = parse_str(&mut chars, &mut line, &mut col, &path)?;
-                tokens.push(tok);
-            }
+            '"' => parse_str(&mut chars, &mut line, &mut col, &path),
 
             _ => {
+                chars.next();
                 col += 1;
-                let loc = Loc { path, line, col };
-                return Err(Syntax(SyntaxError::InvalidChar(loc, ch)));
+                let loc = Loc {
+                    path: path.clone(),
+                    line,
+                    col,
+                    len: 1,
+                    span: Span::default(),
+                };
+                Err(Syntax(Box::new(SyntaxError::InvalidChar(loc, ch))))
+            }
+        };
+
+        match res {
+            Ok(mut tok) => {
+                tok.span = index.span(tok.line, tok.col, tok.end_col + 1);
+                tokens.push(tok);
+            }
+            Err(mut e) => {
+                let loc = e.loc_mut();
+                loc.span = index.span(loc.line, loc.col, loc.col + loc.len);
+                errors.push(e);
+                skip_word(&mut chars, &mut col);
             }
         }
     }
 
-    Ok(tokens)
+    (tokens, errors)
+}
+
+// Maps the tokenizer's 1-based (line, char column) positions back to byte offsets
+struct LineIndex<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(content: &'a str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { content, starts }
+    }
+
+    fn offset(&self, line: usize, col: usize) -> usize {
+        let start = self.starts[line - 1];
+        self.content[start..]
+            .char_indices()
+            .nth(col.saturating_sub(1))
+            .map_or(self.content.len(), |(i, _)| start + i)
+    }
+
+    fn span(&self, line: usize, col: usize, end_col: usize) -> Span {
+        Span {
+            start: self.offset(line, col),
+            end: self.offset(line, end_col),
+        }
+    }
+}
+
+// Discard the remainder of a malformed token so lexing resumes at the next boundary
+fn skip_word(chars: &mut Peekable<Chars>, col: &mut usize) {
+    while let Some(&ch) = chars.peek() {
+        if ch.is_other_char() || ch == '"' {
+            break;
+        }
+        chars.next();
+        *col += 1;
+    }
 }
 
 fn skip_tokens(chars: &mut Peekable<Chars>, line: &mut usize, col: &mut usize) {
@@ -97,6 +160,8 @@ fn parse_symbol(chars: &mut Peekable<Chars>, ch: char, line: &mut usize, col: &m
                 lexeme: ch.to_string(),
                 line: *line,
                 col: *col,
+                end_col: *col,
+                span: Span::default(),
             }
         }
         _ => unreachable!(),
@@ -107,7 +172,7 @@ fn parse_str(
     chars: &mut Peekable<Chars>,
     line: &mut usize,
     col: &mut usize,
-    path: &String,
+    path: &str,
 ) -> Result<Token, ParseError> {
     chars.next(); // consume "
     *col += 1;
@@ -116,24 +181,30 @@ fn parse_str(
     let mut lexeme = String::new();
     let mut in_esc = false;
 
+    let mut esc_err: Option<ParseError> = None;
+
     while let Some(&next_ch) = chars.peek() {
-        *col += 1;
-        chars.next();
-        if next_ch == '"' && !in_esc {
+        if next_ch == '\n' {
             break;
         }
 
-        if next_ch == '\n' {
-            *col -= 1;
-            let loc = Loc {
-                path: path.to_string(),
-                line: *line,
-                col: *col,
+        *col += 1;
+        chars.next();
+        if next_ch == '"' && !in_esc {
+            return match esc_err {
+                Some(e) => Err(e),
+                None => Ok(Token {
+                    kind: TokenKind::String,
+                    lexeme,
+                    line: *line,
+                    col: start_col,
+                    end_col: *col,
+                    span: Span::default(),
+                }),
             };
-            return Err(Syntax(SyntaxError::UnterminatedStr(loc, lexeme)));
         }
 
-        if next_ch == '\\' {
+        if next_ch == '\\' && !in_esc {
             in_esc = true;
         } else if in_esc {
             let esc_ch = match next_ch {
@@ -143,13 +214,16 @@ fn parse_str(
                 'b' => '\u{08}',
                 'e' => '\u{1B}',
                 _ => {
-                    *col -= 1;
                     let loc = Loc {
                         path: path.to_string(),
                         line: *line,
-                        col: *col,
+                        col: *col - 1,
+                        len: 2,
+                        span: Span::default(),
                     };
-                    return Err(Syntax(SyntaxError::InvalidStrEsc(loc, next_ch)));
+                    esc_err
+                        .get_or_insert(Syntax(Box::new(SyntaxError::InvalidStrEsc(loc, next_ch))));
+                    next_ch
                 }
             };
             lexeme.push(esc_ch);
@@ -159,19 +233,21 @@ fn parse_str(
         }
     }
 
-    Ok(Token {
-        kind: TokenKind::String,
-        lexeme,
+    let loc = Loc {
+        path: path.to_string(),
         line: *line,
         col: start_col,
-    })
+        len: *col + 1 - start_col,
+        span: Span::default(),
+    };
+    Err(Syntax(Box::new(SyntaxError::UnterminatedStr(loc, lexeme))))
 }
 
 fn parse_ident(
     chars: &mut Peekable<Chars>,
     line: &mut usize,
     col: &mut usize,
-    path: &String,
+    path: &str,
 ) -> Result<Token, ParseError> {
     let mut lexeme = String::new();
     let start_col = *col + 1;
@@ -194,30 +270,33 @@ fn parse_ident(
         }
     }
 
-    let ident = if is_directive { &lexeme } else { &lexeme };
-    if ident.starts_with('-') || ident.ends_with('-') {
+    if lexeme.starts_with('-') || lexeme.ends_with('-') {
         let loc = Loc {
             path: path.to_string(),
             line: *line,
-            col: *col,
+            col: start_col,
+            len: lexeme.len(),
+            span: Span::default(),
         };
-        return Err(Syntax(SyntaxError::InvalidIdent(loc, lexeme)));
+        return Err(Syntax(Box::new(SyntaxError::InvalidIdent(loc, lexeme))));
     }
 
     let token_kind = match lexeme.as_str() {
-        "!version" | "!program" => TokenKind::Directive,
-        "show_version" | "show_help" => TokenKind::Action,
-        "true" | "false" => TokenKind::Boolean,
-        "int" | "float" | "uint" | "path" | "bool" => TokenKind::Type,
-        "command" | "flag" | "pos" | "named" => TokenKind::Keyword,
+        s if DIRECTIVES.contains(&s) => TokenKind::Directive,
+        s if ACTIONS.contains(&s) => TokenKind::Action,
+        s if BOOLEANS.contains(&s) => TokenKind::Boolean,
+        s if TYPES.contains(&s) => TokenKind::Type,
+        s if KEYWORDS.contains(&s) => TokenKind::Keyword,
         _ => {
             if is_directive {
                 let loc = Loc {
                     path: path.to_string(),
                     line: *line,
-                    col: *col,
+                    col: start_col,
+                    len: lexeme.len(),
+                    span: Span::default(),
                 };
-                return Err(Syntax(SyntaxError::InvalidDirective(loc, lexeme)));
+                return Err(Syntax(Box::new(SyntaxError::InvalidDirective(loc, lexeme))));
             }
             TokenKind::Identifier
         }
@@ -228,6 +307,8 @@ fn parse_ident(
         lexeme,
         line: *line,
         col: start_col,
+        end_col: *col,
+        span: Span::default(),
     })
 }
 
@@ -235,21 +316,23 @@ fn parse_range_or_number(
     chars: &mut std::iter::Peekable<std::str::Chars>,
     line: &mut usize,
     col: &mut usize,
-    path: &String,
+    path: &str,
 ) -> Result<Token, ParseError> {
     let start_col = *col + 1;
 
     let res = parse_number_inner(chars, line, col, path);
     let (first, fallback_err) = match res {
         Ok(tok) => (tok, None),
-        Err(Syntax(SyntaxError::InvalidStandaloneSymbol(loc, s))) => (
+        Err(Syntax(e)) if matches!(*e, SyntaxError::InvalidStandaloneSymbol(..)) => (
             Token {
                 kind: TokenKind::Int,
                 lexeme: "".into(),
                 line: 0,
                 col: 0,
+                end_col: 0,
+                span: Span::default(),
             },
-            Some(Syntax(SyntaxError::InvalidStandaloneSymbol(loc, s))),
+            Some(Syntax(e)),
         ),
         Err(e) => return Err(e),
     };
@@ -273,11 +356,13 @@ fn parse_range_or_number(
             };
 
             let second = match parse_number_inner(chars, line, col, path) {
-                Err(Syntax(SyntaxError::InvalidStandaloneSymbol(_, _))) => Token {
+                Err(Syntax(e)) if matches!(*e, SyntaxError::InvalidStandaloneSymbol(..)) => Token {
                     kind: TokenKind::Int,
                     lexeme: "".into(),
                     line: 0,
                     col: 0,
+                    end_col: 0,
+                    span: Span::default(),
                 },
                 Ok(tok) => tok,
                 Err(e) => return Err(e),
@@ -298,6 +383,8 @@ fn parse_range_or_number(
                 lexeme: combined_lexeme,
                 line: *line,
                 col: start_col,
+                end_col: *col,
+                span: Span::default(),
             });
         }
     }
@@ -313,7 +400,7 @@ fn parse_number_inner(
     chars: &mut std::iter::Peekable<std::str::Chars>,
     line: &mut usize,
     col: &mut usize,
-    path: &String,
+    path: &str,
 ) -> Result<Token, ParseError> {
     let mut lexeme = String::new();
     let start_col = *col + 1;
@@ -336,11 +423,16 @@ fn parse_number_inner(
     // Only 0 or 1 sign is allowed
     if lexeme.chars().filter(|c| "+-".contains(*c)).count() > 1 {
         let loc = Loc {
-            path: path.clone(),
+            path: path.to_string(),
             line: *line,
             col: start_col,
+            len: lexeme.len(),
+            span: Span::default(),
         };
-        return Err(Syntax(SyntaxError::InvalidNumberSign(loc, lexeme.clone())));
+        return Err(Syntax(Box::new(SyntaxError::InvalidNumberSign(
+            loc,
+            lexeme.clone(),
+        ))));
     }
 
     let mut unrec_c = String::new();
@@ -377,7 +469,7 @@ fn parse_number_inner(
             lexeme.push('.');
             chars.next();
             *col += 1;
-        } else if d.is_other_char() {
+        } else if d.is_other_char() || digit_found && d.eq_ignore_ascii_case(&'e') {
             break;
         } else {
             unrec_c.push(d);
@@ -388,131 +480,153 @@ fn parse_number_inner(
     }
 
     if !unrec_c.is_empty() {
-        return Err(Syntax(SyntaxError::InvalidNumChars(
+        return Err(Syntax(Box::new(SyntaxError::InvalidNumChars(
             (if is_float { "Float" } else { "Integer" }).into(),
             unrec_c,
             Loc {
-                path: path.clone(),
+                path: path.to_string(),
                 line: *line,
-                col: *col,
+                col: start_col,
+                len: lexeme.len(),
+                span: Span::default(),
             },
             lexeme.clone(),
-        )));
+        ))));
     }
 
     if invalid_range {
-        return Err(Syntax(SyntaxError::InvalidRange(
+        return Err(Syntax(Box::new(SyntaxError::InvalidRange(
             Loc {
-                path: path.clone(),
+                path: path.to_string(),
                 line: *line,
                 col: start_col,
+                len: lexeme.len(),
+                span: Span::default(),
             },
             lexeme.clone(),
-        )));
+        ))));
     }
 
     // Validate that something numeric came after sign
     if !digit_found && !is_float {
         let loc = Loc {
-            path: path.clone(),
+            path: path.to_string(),
             line: *line,
             col: start_col,
+            len: lexeme.len(),
+            span: Span::default(),
         };
-        return Err(Syntax(SyntaxError::InvalidStandaloneSymbol(loc, lexeme)));
+        return Err(Syntax(Box::new(SyntaxError::InvalidStandaloneSymbol(
+            loc, lexeme,
+        ))));
     }
 
     // Disallow "." or "-" or "-." etc as standalone
     if lexeme == "." || lexeme == "-" || lexeme == "+" || lexeme == "-." || lexeme == "+." {
         let loc = Loc {
-            path: path.clone(),
+            path: path.to_string(),
             line: *line,
             col: start_col,
+            len: lexeme.len(),
+            span: Span::default(),
         };
-        return Err(Syntax(SyntaxError::InvalidStandaloneSymbol(loc, lexeme)));
+        return Err(Syntax(Box::new(SyntaxError::InvalidStandaloneSymbol(
+            loc, lexeme,
+        ))));
     }
 
     // Handle optional float exponent
-    if let Some(&e) = chars.peek() {
-        if e.eq_ignore_ascii_case(&'e') {
-            is_float = true;
-            lexeme.push(e);
+    if let Some(&e) = chars.peek()
+        && e.eq_ignore_ascii_case(&'e')
+    {
+        is_float = true;
+        lexeme.push(e);
+        chars.next();
+        *col += 1;
+
+        // Optional single sign after e
+        if let Some(&sign) = chars.peek()
+            && sign.is_num_sign()
+        {
+            lexeme.push(sign);
             chars.next();
             *col += 1;
 
-            // Optional single sign after e
-            if let Some(&sign) = chars.peek() {
-                if sign.is_num_sign() {
-                    lexeme.push(sign);
-                    chars.next();
-                    *col += 1;
-
-                    // Disallow multiple signs
-                    if let Some(&next) = chars.peek() {
-                        if "+-".contains(next) {
-                            let loc = Loc {
-                                path: path.clone(),
-                                line: *line,
-                                col: *col + 1,
-                            };
-                            return Err(Syntax(SyntaxError::InvalidNumberSign(loc, lexeme)));
-                        }
-                    }
-                }
+            // Disallow multiple signs
+            if let Some(&next) = chars.peek()
+                && "+-".contains(next)
+            {
+                let loc = Loc {
+                    path: path.to_string(),
+                    line: *line,
+                    col: *col + 1,
+                    len: 1,
+                    span: Span::default(),
+                };
+                return Err(Syntax(Box::new(SyntaxError::InvalidNumberSign(
+                    loc, lexeme,
+                ))));
             }
+        }
 
-            let mut digit_in_exp = false;
-            let mut unrec_c = String::new();
-
-            while let Some(&n) = chars.peek() {
-                if n.is_num_char() {
-                    digit_in_exp = true;
-                    lexeme.push(n);
-                    chars.next();
-                    *col += 1;
-                } else if n.is_other_char() {
-                    break;
-                } else {
-                    unrec_c.push(n);
-                    lexeme.push(n);
-                    chars.next();
-                    *col += 1;
-                }
-            }
+        let mut digit_in_exp = false;
+        let mut unrec_c = String::new();
 
-            if !digit_in_exp {
-                return Err(Syntax(SyntaxError::NoFloatExp(
-                    Loc {
-                        path: path.clone(),
-                        line: *line,
-                        col: *col,
-                    },
-                    lexeme.clone(),
-                )));
+        while let Some(&n) = chars.peek() {
+            if n.is_num_char() {
+                digit_in_exp = true;
+                lexeme.push(n);
+                chars.next();
+                *col += 1;
+            } else if n.is_other_char() {
+                break;
+            } else {
+                unrec_c.push(n);
+                lexeme.push(n);
+                chars.next();
+                *col += 1;
             }
+        }
 
-            if !unrec_c.is_empty() {
-                return Err(Syntax(SyntaxError::InvalidFloatExpChars(
-                    unrec_c,
-                    Loc {
-                        path: path.clone(),
-                        line: *line,
-                        col: *col,
-                    },
-                    lexeme.clone(),
-                )));
-            }
+        if !digit_in_exp {
+            return Err(Syntax(Box::new(SyntaxError::NoFloatExp(
+                Loc {
+                    path: path.to_string(),
+                    line: *line,
+                    col: *col,
+                    len: 1,
+                    span: Span::default(),
+                },
+                lexeme.clone(),
+            ))));
+        }
+
+        if !unrec_c.is_empty() {
+            return Err(Syntax(Box::new(SyntaxError::InvalidFloatExpChars(
+                unrec_c,
+                Loc {
+                    path: path.to_string(),
+                    line: *line,
+                    col: start_col,
+                    len: lexeme.len(),
+                    span: Span::default(),
+                },
+                lexeme.clone(),
+            ))));
         }
     }
 
     if err_dp != 0 {
-        return Err(Syntax(SyntaxError::ExtraDecimalPoint(
+        return Err(Syntax(Box::new(SyntaxError::ExtraDecimalPoint(
             Loc {
-                path: path.clone(),
+                path: path.to_string(),
                 line: *line,
                 col: err_dp,
+                len: 1,
+                span: Span::default(),
             },
             lexeme.clone(),
-        )));
+        ))));
     }
 
     Ok(Token {
@@ -524,5 +638,7 @@ fn parse_number_inner(
         lexeme,
         line: *line,
         col: start_col,
+        end_col: *col,
+        span: Span::default(),
     })
 }
@@ -0,0 +1,332 @@
+mod common;
+
+use common::{argolify, assert_golden, has_tool};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const MANIFEST: &str = r#"[package]
+name = "reader"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+
+[workspace]
+"#;
+
+// Reads documents, one per line, with the bindings module named by its first argument and prints
+// what they were read as
+const MAIN: &str = r#"mod reserved;
+mod test;
+mod types;
+
+use std::fmt::Debug;
+use std::io::BufRead;
+
+fn print<T: Debug + serde::de::DeserializeOwned>(line: &str) {
+    println!("{:?}", serde_json::from_str::<T>(line).unwrap());
+}
+
+fn main() {
+    let module = std::env::args().nth(1).unwrap();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.unwrap();
+        match module.as_str() {
+            "reserved" => print::<reserved::Document>(&line),
+            "test" => print::<test::Document>(&line),
+            "types" => print::<types::Document>(&line),
+            _ => unreachable!(),
+        }
+    }
+}
+"#;
+
+const READER: &str = r#"import importlib
+import sys
+
+sys.path.insert(0, sys.argv[1])
+module = importlib.import_module(sys.argv[2])
+for line in sys.stdin:
+    print(repr(module.parse(line)))
+"#;
+
+// Specs and argument lists covering matches at each level, actions, usage errors and every type
+const CASES: [(&str, &str, &[&str]); 11] = [
+    ("test", "test.argol", &[]),
+    (
+        "test",
+        "test.argol",
+        &["new", "app", "-d", "a", "--color", "3"],
+    ),
+    ("test", "test.argol", &["new", "-h"]),
+    ("test", "test.argol", &["new", "--bogus"]),
+    (
+        "types",
+        "tests/fixtures/types.argol",
+        &["in.txt", "--ratio", "0.5", "--strict", "true"],
+    ),
+    (
+        "types",
+        "tests/fixtures/types.argol",
+        &["c", "a.json", "--format", "json", "--", "x"],
+    ),
+    ("types", "tests/fixtures/types.argol", &["--level", "9"]),
+    (
+        "reserved",
+        "tests/fixtures/reserved.argol",
+        &["--action", "x", "--", "y"],
+    ),
+    (
+        "reserved",
+        "tests/fixtures/reserved.argol",
+        &["root", "--subcommand", "s", "leaf"],
+    ),
+    ("reserved", "tests/fixtures/reserved.argol", &["-h"]),
+    (
+        "reserved",
+        "tests/fixtures/reserved.argol",
+        &["root", "--message", "m"],
+    ),
+];
+
+// Bindings for `spec` in `lang`, written to `dir/<module>.<ext>`
+fn write_bindings(lang: &str, spec: &str, path: &Path) {
+    let out = argolify(&["bindings", "--lang", lang, spec]);
+    assert!(out.status.success(), "{out:?}");
+    fs::write(path, out.stdout).unwrap();
+}
+
+// The documents `argolify run --output json` prints for the cases of `module`, one per line
+fn documents(module: &str) -> String {
+    let mut docs = String::new();
+    for (_, spec, args) in CASES.iter().filter(|(m, ..)| *m == module) {
+        let mut argv = vec!["run", "--output", "json", spec, "--"];
+        argv.extend(*args);
+        docs.push_str(&String::from_utf8(argolify(&argv).stdout).unwrap());
+    }
+    docs
+}
+
+// Run `cmd` with `input` on stdin, returning its stdout lines
+fn read(cmd: &mut Command, input: &str) -> Vec<String> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "{out:?}");
+    String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+// Build a program reading documents with the Rust bindings, or `None` when serde cannot be
+// fetched without the network
+fn build_rust_reader() -> Option<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bindings_rust");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("Cargo.toml"), MANIFEST).unwrap();
+    fs::write(dir.join("src/main.rs"), MAIN).unwrap();
+    for (module, spec, _) in CASES {
+        write_bindings("rust", spec, &dir.join(format!("src/{module}.rs")));
+    }
+
+    let out = Command::new("cargo")
+        .args(["build", "--offline", "--quiet"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !out.status.success() && stderr.contains("offline") {
+        eprintln!("skipping: serde is not in the local registry\n{stderr}");
+        return None;
+    }
+    assert!(out.status.success(), "{stderr}");
+    Some(dir.join("target/debug/reader"))
+}
+
+fn check(lang: &str, spec: &str, golden: &str) {
+    let out = argolify(&["bindings", "--lang", lang, spec]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden(golden, &String::from_utf8(out.stdout).unwrap());
+}
+
+#[test]
+fn rust_shadowed_names() {
+    check("rust", "test.argol", "bindings_test.rs");
+}
+
+#[test]
+fn python_types_and_lists() {
+    check("python", "tests/fixtures/types.argol", "bindings_types.py");
+}
+
+#[test]
+fn unknown_language() {
+    let out = argolify(&["bindings", "--lang", "cobol", "test.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("Unknown language"), "{stderr}");
+}
+
+#[test]
+fn rust_bindings_read_real_documents() {
+    let Some(reader) = build_rust_reader() else {
+        return;
+    };
+    let read = |module: &str| read(Command::new(&reader).arg(module), &documents(module));
+
+    let test = read("test");
+    assert!(
+        test[0]
+            .starts_with("Matches(Matches { command: Root(Args { version: Arg { source: Default")
+    );
+    assert!(test[1].contains("command: New(NewArgs {"), "{}", test[1]);
+    assert!(test[1].contains("deps: Arg { source: Argv, value: Some(\"a\") }"));
+    assert!(test[1].contains("color: Arg { source: Argv, value: Some(3) }"));
+    assert!(test[2].starts_with("Action(ActionRequest { action: ShowHelp, command: [\"new\"] })"));
+    assert!(test[3].starts_with("Error(UsageError { command: [\"new\"], kind: UnknownOption"));
+
+    let types = read("types");
+    assert!(
+        types[0].contains("ratio: Arg { source: Argv, value: Some(0.5) }"),
+        "{}",
+        types[0]
+    );
+    assert!(types[0].contains("input: Arg { source: Argv, value: Some(\"in.txt\") }"));
+    assert!(types[0].contains("strict: Arg { source: Argv, value: Some(true) }"));
+    assert!(
+        types[1].contains("command: Convert(ConvertArgs {"),
+        "{}",
+        types[1]
+    );
+    assert!(types[1].contains("outputs: Arg { source: Argv, value: [\"a.json\", \"x\"] }"));
+    assert!(types[2].starts_with("Error(UsageError { command: [], kind: NotAllowed"));
+
+    let reserved = read("reserved");
+    assert!(
+        reserved[0].contains("command: Root(Args {"),
+        "{}",
+        reserved[0]
+    );
+    assert!(reserved[0].contains("trailing: Arg { source: Argv, value: Some(\"x\") }"));
+    assert!(reserved[0].ends_with("trailing: [\"y\"] })"));
+    assert!(
+        reserved[1].contains("command: RootLeaf(RootLeafArgs {"),
+        "{}",
+        reserved[1]
+    );
+    assert!(reserved[2].starts_with("Action(ActionRequest { action: ShowHelp, command: [] })"));
+    assert!(
+        reserved[3].contains("command: RootCommand(RootArgs {"),
+        "{}",
+        reserved[3]
+    );
+}
+
+#[test]
+fn python_bindings_read_real_documents() {
+    if !has_tool("python3") {
+        return;
+    }
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bindings_python");
+    fs::create_dir_all(&dir).unwrap();
+    for (module, spec, _) in CASES {
+        write_bindings("python", spec, &dir.join(format!("{module}_args.py")));
+    }
+    let read = |module: &str| {
+        let mut cmd = Command::new("python3");
+        cmd.args([
+            "-c",
+            READER,
+            dir.to_str().unwrap(),
+            &format!("{module}_args"),
+        ]);
+        read(&mut cmd, &documents(module))
+    };
+
+    let test = read("test");
+    assert!(test[0].starts_with("Matches(command=Args(version=Arg(source='default', value=False)"));
+    assert!(
+        test[1].starts_with("Matches(command=NewArgs("),
+        "{}",
+        test[1]
+    );
+    assert!(test[1].contains("deps=Arg(source='argv', value='a')"));
+    assert!(test[1].contains("color=Arg(source='argv', value=3)"));
+    assert_eq!(
+        test[2],
+        "ActionRequest(action='show_help', command=['new'])"
+    );
+    assert!(test[3].starts_with("UsageError(command=['new'], kind='unknown_option'"));
+
+    let types = read("types");
+    assert!(
+        types[0].contains("ratio=Arg(source='argv', value=0.5)"),
+        "{}",
+        types[0]
+    );
+    assert!(types[0].contains("input=Arg(source='argv', value=PosixPath('in.txt'))"));
+    assert!(types[0].contains("strict=Arg(source='argv', value=True)"));
+    assert!(
+        types[1].starts_with("Matches(command=ConvertArgs("),
+        "{}",
+        types[1]
+    );
+    assert!(types[1].contains("value=[PosixPath('a.json'), PosixPath('x')]"));
+    assert!(types[2].starts_with("UsageError(command=[], kind='not_allowed'"));
+
+    let reserved = read("reserved");
+    assert!(
+        reserved[0].contains("trailing=Arg(source='argv', value='x')"),
+        "{}",
+        reserved[0]
+    );
+    assert!(reserved[0].ends_with("trailing=['y'])"));
+    assert!(
+        reserved[1].starts_with("Matches(command=RootLeafArgs("),
+        "{}",
+        reserved[1]
+    );
+    assert_eq!(reserved[2], "ActionRequest(action='show_help', command=[])");
+    assert!(
+        reserved[3].starts_with("Matches(command=RootArgs("),
+        "{}",
+        reserved[3]
+    );
+}
+
+#[test]
+fn names_that_become_one_identifier_are_rejected() {
+    for lang in ["python", "rust"] {
+        let out = argolify(&[
+            "bindings",
+            "--lang",
+            lang,
+            "tests/fixtures/same_ident.argol",
+        ]);
+        assert_eq!(out.status.code(), Some(1));
+        let stderr = String::from_utf8(out.stderr).unwrap();
+        assert_eq!(
+            stderr
+                .matches("argument `dry-run` and argument `dry_run` both become `dry_run`")
+                .count(),
+            1,
+            "{stderr}"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{argolify, has_tool};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// A fresh directory under the test target dir for one test to build into
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn build(dir: &Path, extra: &[&str]) -> std::process::Output {
+    let mut args = vec![
+        "build",
+        "test.argol",
+        "--no-build",
+        "-o",
+        dir.to_str().unwrap(),
+    ];
+    args.extend(extra);
+    argolify(&args)
+}
+
+#[test]
+fn emits_a_standalone_project() {
+    let dir = scratch("build_emit");
+    let out = build(&dir, &[]);
+    assert!(out.status.success(), "{out:?}");
+
+    for file in [
+        "Cargo.toml",
+        "src/main.rs",
+        "src/spec.rs",
+        "src/runtime.rs",
+        "src/help.rs",
+        "src/common/json.rs",
+    ] {
+        assert!(dir.join(file).is_file(), "missing {file}");
+    }
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"build_emit\""), "{manifest}");
+    let main = fs::read_to_string(dir.join("src/main.rs")).unwrap();
+    assert!(main.contains("fn program() -> Program"), "{main}");
+    assert!(main.contains("runtime::Output::Json"), "{main}");
+    assert!(!dir.join("target").exists());
+
+    // Regenerating over an earlier build is allowed
+    let out = build(&dir, &["--output", "text"]);
+    assert!(out.status.success(), "{out:?}");
+    let main = fs::read_to_string(dir.join("src/main.rs")).unwrap();
+    assert!(main.contains("runtime::Output::Text"), "{main}");
+}
+
+#[test]
+fn refuses_to_overwrite_another_project() {
+    let dir = scratch("build_foreign");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\nname = \"mine\"\n").unwrap();
+    fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+    let out = build(&dir, &[]);
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    assert_eq!(
+        fs::read_to_string(dir.join("Cargo.toml")).unwrap(),
+        "[package]\nname = \"mine\"\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("src/main.rs")).unwrap(),
+        "fn main() {}\n"
+    );
+
+    let out = build(&dir, &["--force"]);
+    assert!(out.status.success(), "{out:?}");
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"build_foreign\""), "{manifest}");
+}
+
+#[test]
+fn builds_a_binary_that_parses_arguments() {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    if !has_tool(&cargo) {
+        return;
+    }
+    let dir = scratch("build_greet");
+    let out = argolify(&[
+        "build",
+        "tests/fixtures/greet.argol",
+        "--output",
+        "text",
+        "-o",
+        dir.to_str().unwrap(),
+    ]);
+    assert!(out.status.success(), "{out:?}");
+
+    let exe = dir
+        .join("target")
+        .join("release")
+        .join(format!("build_greet{}", std::env::consts::EXE_SUFFIX));
+    let run = |args: &[&str]| Command::new(&exe).args(args).output().unwrap();
+
+    let out = run(&["-l", "--times", "2", "Ann"]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "loud = true\nname = \"Ann\"\ntimes = 2\n"
+    );
+
+    let out = run(&["--times", "5", "Ann"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("invalid value '5' for 'times'"), "{stderr}");
+}
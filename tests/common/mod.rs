@@ -0,0 +1,69 @@
+// Each test crate compiles its own copy of this module and only uses some of the helpers
+#![allow(dead_code)]
+
+use std::fs;
+use std::process::{Command, Output};
+
+pub fn argolify(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_argolify"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Compare `actual` with `tests/golden/<name>`. Set `UPDATE_GOLDEN=1` to rewrite the golden file
+/// instead.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = format!("tests/golden/{name}");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+    assert_eq!(actual, expected, "output differs from {path}");
+}
+
+/// Whether `program` can be run, for tests that need a toolchain the sandbox may not have.
+pub fn has_tool(program: &str) -> bool {
+    let found = Command::new(program).arg("--version").output().is_ok();
+    if !found {
+        eprintln!("skipping: `{program}` is not available");
+    }
+    found
+}
+
+/// The usage error the runtime reports for `args`, as `{"command", "kind", "subject", "message"}`,
+/// or null when they match.
+pub fn runtime_error(spec: &str, args: &[&str]) -> serde_json::Value {
+    let mut argv = vec!["run", "--output", "json", spec, "--"];
+    argv.extend(args);
+    let out = argolify(&argv);
+    let doc: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    match doc.get("error") {
+        Some(error) => {
+            let mut error = error.clone();
+            error["command"] = doc["command"].clone();
+            error
+        }
+        None => serde_json::Value::Null,
+    }
+}
+
+/// Argument lists for `tests/fixtures/leftover.argol` where a leftover word, too many values and
+/// invalid values compete for the error that is reported, and where floats are not finite.
+pub const LEFTOVER_CASES: [&[&str]; 14] = [
+    &["1"],
+    &["1", "--pair", "a", "b", "--level", "3"],
+    &["1", "2"],
+    &["x", "2"],
+    &["1", "--pair", "a", "b", "--pair", "c"],
+    &["1", "2", "--pair", "a", "b", "--pair", "c"],
+    &["1", "--pair", "a", "b", "--pair", "c", "--level", "x"],
+    &["--pair", "a", "b", "c"],
+    &["1", "--level", "x"],
+    &["--", "1", "2"],
+    &["--ratio", "nan"],
+    &["--ratio", "inf"],
+    &["--ratio", "1e999"],
+    &["--ratio", "-inf"],
+];
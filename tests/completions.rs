@@ -0,0 +1,105 @@
+mod common;
+
+use common::{argolify, assert_golden};
+use std::process::Command;
+
+fn check(shell: &str, spec: &str, golden: &str) {
+    let out = argolify(&["completions", shell, spec]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden(golden, &String::from_utf8(out.stdout).unwrap());
+}
+
+// Source the bash script for `spec` and complete the last of `words`, listing paths in
+// tests/fixtures
+fn bash(spec: &str, words: &[&str]) -> Vec<String> {
+    let out = argolify(&["completions", "bash", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let script = String::from_utf8(out.stdout).unwrap();
+    let func = script
+        .lines()
+        .find_map(|l| l.strip_prefix("complete -F "))
+        .and_then(|l| l.split(' ').next())
+        .unwrap()
+        .to_string();
+    let out = Command::new("bash")
+        .arg("-c")
+        .arg(format!(
+            "{script}\ncompopt() {{ :; }}\nCOMP_WORDS=(\"$@\")\nCOMP_CWORD=$(($# - 1))\n\
+             cd tests/fixtures && {func}; printf '%s\\n' \"${{COMPREPLY[@]}}\""
+        ))
+        .arg("bash")
+        .args(words)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn bash_subcommands_and_paths() {
+    let spec = "tests/fixtures/types.argol";
+    assert_eq!(bash(spec, &["types", "c"]), ["convert", "c"]);
+    assert_eq!(bash(spec, &["types", "ty"]), ["types.argol"]);
+    assert_eq!(bash(spec, &["types", "in.txt", ""]), Vec::<String>::new());
+    assert_eq!(bash(spec, &["types", "c", "ty"]), ["types.argol"]);
+    assert_eq!(bash(spec, &["types", "--", "c"]), Vec::<String>::new());
+}
+
+#[test]
+fn bash_options_and_values() {
+    let spec = "tests/fixtures/types.argol";
+    assert_eq!(bash(spec, &["types", "--l"]), ["--level"]);
+    assert_eq!(bash(spec, &["types", "c", "-"]), ["--format"]);
+    assert_eq!(bash(spec, &["types", "-l", ""]), ["1", "2", "3", "4", "5"]);
+    assert_eq!(bash(spec, &["types", "--strict", "=", "f"]), ["false"]);
+    assert_eq!(bash(spec, &["types", "c", "--format", "y"]), ["yaml"]);
+    // `--tags` takes every word up to the next option
+    assert_eq!(
+        bash(spec, &["types", "--tags", "a", "c"]),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        bash(spec, &["types", "--tags", "a", "-q", "c"]),
+        ["convert", "c"]
+    );
+}
+
+#[test]
+fn bash_quotes_values() {
+    let spec = "tests/fixtures/quoting.argol";
+    assert_eq!(
+        bash(spec, &["quoting", "--mode", ""]),
+        [r"fast\ mode", r"it\'s", r"\$\(date\)", r#"a\"b"#]
+    );
+    assert_eq!(bash(spec, &["quoting", "--mode", "f"]), [r"fast\ mode"]);
+    assert_eq!(bash(spec, &["quoting", "--mode", "$"]), [r"\$\(date\)"]);
+}
+
+#[test]
+fn zsh_script() {
+    check("zsh", "tests/fixtures/types.argol", "completions_types.zsh");
+}
+
+#[test]
+fn fish_script() {
+    check("fish", "test.argol", "completions_test.fish");
+}
+
+#[test]
+fn wide_ranges_are_not_listed() {
+    let spec = "tests/fixtures/wide.argol";
+    assert_eq!(bash(spec, &["wide", "--offset", ""]), Vec::<String>::new());
+    assert_eq!(
+        bash(spec, &["wide", "--shift", ""]),
+        ["9223372036854775805", "9223372036854775806"]
+    );
+    for shell in ["zsh", "fish"] {
+        let out = argolify(&["completions", shell, spec]);
+        assert!(out.status.success(), "{out:?}");
+    }
+}
@@ -0,0 +1,266 @@
+mod common;
+
+use common::argolify;
+use std::fs;
+
+// Each diagnostic reported for `spec` as its `(line:col)` and message, in the order reported
+fn diagnostics(spec: &str) -> Vec<(String, String)> {
+    let out = argolify(&[spec]);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    let mut lines = stderr.lines();
+    let mut diags = Vec::new();
+    while let Some(line) = lines.next() {
+        let Some((_, rest)) = line.split_once("] (") else {
+            continue;
+        };
+        let pos = rest.split_once(')').unwrap().0;
+        let msg = lines.next().unwrap().trim_start().trim_start_matches("⤷ ");
+        diags.push((pos.to_string(), msg.to_string()));
+    }
+    diags
+}
+
+// Diagnostics for `source`, checked from a scratch file named after `name`
+fn diagnostics_for(name: &str, source: &str) -> Vec<(String, String)> {
+    let dir = format!("{}/diagnostics", env!("CARGO_TARGET_TMPDIR"));
+    fs::create_dir_all(&dir).unwrap();
+    let path = format!("{dir}/{name}.argol");
+    fs::write(&path, source).unwrap();
+    diagnostics(&path)
+}
+
+#[test]
+fn lexical_error_does_not_hide_later_stages() {
+    assert_eq!(
+        diagnostics("tests/fixtures/lex_and_semantic.argol"),
+        [
+            (
+                "2:1".to_string(),
+                "Invalid directive -> !verison".to_string()
+            ),
+            (
+                "6:5".to_string(),
+                "Unknown key in flag block -> acton (allowed: desc, action)".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn sibling_blocks_cannot_share_names() {
+    let sibling = "Name is already used by another block in this command";
+    assert_eq!(
+        diagnostics("tests/fixtures/duplicate_names.argol"),
+        [
+            ("5:7".to_string(), format!("{sibling} -> v")),
+            ("5:9".to_string(), format!("{sibling} -> verbose")),
+            ("9:5".to_string(), format!("{sibling} -> verbose")),
+            ("17:9".to_string(), format!("{sibling} -> new")),
+            // Repeating a name within one block is a different error
+            (
+                "21:14".to_string(),
+                "Duplicate name(s) found (1) for argument -> deps".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn keys_must_be_allowed_for_their_block() {
+    let cases = [
+        (
+            "flag_default",
+            "flag verbose {\n    default = true;\n}\n",
+            "2:5",
+            "Key default is not allowed in flag block (allowed: desc, action)",
+        ),
+        (
+            "pos_action",
+            "pos target {\n    action = show_help;\n}\n",
+            "2:5",
+            "Key action is not allowed in pos block (allowed: desc, required, default, type, \
+             options, nargs)",
+        ),
+        (
+            "command_required",
+            "command build {\n    desc = \"Build\";\n    required = true;\n}\n",
+            "3:5",
+            "Key required is not allowed in command block (allowed: desc)",
+        ),
+        (
+            "unknown",
+            "named level {\n    requird = true;\n}\n",
+            "2:5",
+            "Unknown key in named block -> requird (allowed: desc, required, default, type, \
+             options, nargs)",
+        ),
+        (
+            "duplicate",
+            "pos target {\n    nargs = 1;\n    nargs = 2;\n}\n",
+            "3:5",
+            "Duplicate assignment -> nargs",
+        ),
+    ];
+    for (name, source, pos, msg) in cases {
+        assert_eq!(
+            diagnostics_for(name, source),
+            [(pos.to_string(), msg.to_string())],
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn defaults_must_fit_options_and_nargs() {
+    let cases = [
+        (
+            "wrong_type",
+            "named level {\n    type = int;\n    default = \"high\";\n}\n",
+            "3:15",
+            "Value is not compatible with type int -> high",
+        ),
+        (
+            "negative_uint",
+            "pos count {\n    type = uint;\n    default = -2;\n}\n",
+            "3:15",
+            "Negative value for type uint -> -2",
+        ),
+        (
+            "outside_range",
+            "named level {\n    type = int;\n    default = 9;\n    options = 1..=5;\n}\n",
+            "3:15",
+            "Default value is not allowed by options (allowed: 1..=5) -> 9",
+        ),
+        (
+            "not_an_option",
+            "named mode {\n    default = \"slow\";\n    options = \"fast\", \"safe\";\n}\n",
+            "2:15",
+            "Default value is not allowed by options (allowed: fast, safe) -> slow",
+        ),
+        (
+            "too_many",
+            "named tag {\n    default = \"a\", \"b\";\n}\n",
+            "2:20",
+            "nargs takes exactly 1 value(s), but default has 2",
+        ),
+        (
+            "too_few",
+            "named pair {\n    type = int;\n    default = 1;\n    nargs = 2;\n}\n",
+            "3:15",
+            "nargs takes exactly 2 value(s), but default has 1",
+        ),
+    ];
+    for (name, source, pos, msg) in cases {
+        assert_eq!(
+            diagnostics_for(name, source),
+            [(pos.to_string(), msg.to_string())],
+            "{source}"
+        );
+    }
+
+    let fitting = "pos files {\n    default = \"a\", \"b\";\n    nargs = 1..;\n}\n\n\
+                   named speed {\n    type = float;\n    default = 2.0, 2.5;\n    options = 1..3;\n    \
+                   nargs = 1..=2;\n}\n";
+    assert_eq!(diagnostics_for("fitting", fitting), []);
+}
+
+#[test]
+fn invalid_type_does_not_check_default() {
+    // Only the type is reported, not the default and options against the `string` fallback
+    let source = "named level {\n    type = 3;\n    default = 3;\n    options = 1..=5;\n}\n";
+    assert_eq!(
+        diagnostics_for("wrong_type_kind", source),
+        [(
+            "2:12".to_string(),
+            "Expected type for type, but got integer".to_string()
+        )]
+    );
+
+    let diags = diagnostics_for(
+        "misspelled_type",
+        "named level {\n    type = intt;\n    default = 3;\n}\n",
+    );
+    assert_eq!(diags.len(), 1, "{diags:?}");
+    assert_eq!(diags[0].0, "2:12");
+}
+
+#[test]
+fn misspellings_suggest_the_closest_name() {
+    let out = argolify(&["tests/fixtures/misspelled.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    // The `(line:col)` of each diagnostic with the tip it ends on, if any
+    let mut tips: Vec<(String, Option<String>)> = Vec::new();
+    for line in String::from_utf8(out.stderr).unwrap().lines() {
+        if let Some((_, rest)) = line.split_once("] (") {
+            tips.push((rest.split_once(')').unwrap().0.to_string(), None));
+        } else if let Some((_, tip)) = line.split_once("= tip: ") {
+            tips.last_mut().unwrap().1 = Some(tip.to_string());
+        }
+    }
+    let expected = [
+        ("1:1", Some("did you mean !version?")),
+        ("3:1", Some("did you mean command?")),
+        ("6:14", Some("did you mean show_help?")),
+        ("10:12", Some("did you mean string?")),
+        ("11:5", Some("did you mean required?")),
+        // Nothing is close enough to `qqqq`
+        ("15:5", None),
+    ];
+    let tips: Vec<(&str, Option<&str>)> = tips
+        .iter()
+        .map(|(pos, tip)| (pos.as_str(), tip.as_deref()))
+        .collect();
+    assert_eq!(tips, expected);
+}
+
+// Exit status and the `[<severity> <code>] (<line>:<col>)` header of each diagnostic reported
+// when checking with `args`
+fn headers(args: &[&str]) -> (Option<i32>, Vec<String>) {
+    let out = argolify(args);
+    let headers = String::from_utf8(out.stderr)
+        .unwrap()
+        .lines()
+        .filter_map(|l| l.split_once(" in `").map(|(h, _)| h.to_string()))
+        .collect();
+    (out.status.code(), headers)
+}
+
+#[test]
+fn pragmas_allow_lints_on_the_next_line_or_block() {
+    let reported = |severity: &str| {
+        [
+            "A0302] (4:1)",
+            "A0301] (17:15)",
+            "A0303] (20:16)",
+            "A0302] (21:1)",
+        ]
+        .map(|h| format!("[{severity} {h}"))
+        .to_vec()
+    };
+    assert_eq!(
+        headers(&["tests/fixtures/pragmas.argol"]),
+        (Some(0), reported("warning"))
+    );
+    assert_eq!(
+        headers(&["--deny-warnings", "tests/fixtures/pragmas.argol"]),
+        (Some(1), reported("error"))
+    );
+
+    let out = argolify(&["--deny-warnings", "tests/fixtures/pragmas.argol"]);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("due to 4 errors"), "{stderr}");
+}
+
+#[test]
+fn json_diagnostics_stay_off_subcommand_output() {
+    let out = argolify(&[
+        "run",
+        "tests/fixtures/empty_block.argol",
+        "--message-format=json",
+    ]);
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout, "verbose = false (default)\n");
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains(r#""code":"A0302""#), "{stderr}");
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::argolify;
+use std::fs;
+
+// The (erroneous, corrected) examples `argolify explain <code>` prints
+fn examples(code: &str) -> (String, String) {
+    let out = argolify(&["explain", code]);
+    assert!(out.status.success(), "{out:?}");
+    let text = String::from_utf8(out.stdout).unwrap();
+    let (_, rest) = text.split_once("Erroneous example:\n\n").unwrap();
+    let (bad, good) = rest.split_once("\n\nCorrected example:\n\n").unwrap();
+    let unindent = |s: &str| {
+        s.lines()
+            .map(|l| l.strip_prefix("    ").unwrap_or(l))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    (unindent(bad), unindent(good))
+}
+
+// Exit status and diagnostic codes from checking `source`
+fn check(code: &str, name: &str, source: &str) -> (Option<i32>, Vec<String>) {
+    let dir = format!("{}/explain", env!("CARGO_TARGET_TMPDIR"));
+    fs::create_dir_all(&dir).unwrap();
+    let path = format!("{dir}/{code}_{name}.argol");
+    fs::write(&path, source).unwrap();
+    let out = argolify(&["--message-format", "json", &path]);
+    let codes = String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| {
+            let (_, rest) = l.split_once(r#""code":""#).unwrap();
+            rest.split_once('"').unwrap().0.to_string()
+        })
+        .collect();
+    (out.status.code(), codes)
+}
+
+#[test]
+fn every_example_produces_its_own_code() {
+    let out = argolify(&["explain"]);
+    assert!(out.status.success(), "{out:?}");
+    let list = String::from_utf8(out.stdout).unwrap();
+    let codes: Vec<&str> = list.lines().map(|l| l.split_once(' ').unwrap().0).collect();
+    assert_eq!(codes.len(), 34);
+
+    for code in codes {
+        let (bad, good) = examples(code);
+        // The misspelled lint in the A0303 example leaves the empty block it meant to allow
+        let expected = match code {
+            "A0303" => vec!["A0303", "A0302"],
+            _ => vec![code],
+        };
+        let (_, reported) = check(code, "bad", &bad);
+        assert_eq!(reported, expected, "erroneous example for {code}:\n{bad}");
+        let (status, reported) = check(code, "good", &good);
+        assert_eq!(
+            (status, reported),
+            (Some(0), Vec::new()),
+            "corrected example for {code}:\n{good}"
+        );
+    }
+}
+
+#[test]
+fn codes_are_case_insensitive_and_unknown_codes_fail() {
+    let upper = argolify(&["explain", "A0213"]);
+    let lower = argolify(&["explain", "a0213"]);
+    assert!(upper.status.success());
+    assert_eq!(upper.stdout, lower.stdout);
+
+    let out = argolify(&["explain", "A9999"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        String::from_utf8(out.stderr)
+            .unwrap()
+            .contains("Unknown error code")
+    );
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use common::{LEFTOVER_CASES, argolify, assert_golden, has_tool, runtime_error};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Prints {"args": ...} with the values parsed for tests/fixtures/leftover.argol, or
+// {"error": ...} with the error kind and message
+const DRIVER: &str = r#"#define ARGOL_IMPLEMENTATION
+#include "cli.h"
+
+#include <inttypes.h>
+#include <stdio.h>
+
+static void print_str(const char *s)
+{
+    putchar('"');
+    for (; *s; s++) {
+        if (*s == '"' || *s == '\\')
+            putchar('\\');
+        putchar(*s);
+    }
+    putchar('"');
+}
+
+int main(int argc, char **argv)
+{
+    struct argol_args args;
+    int code = argol_parse(argc, argv, &args);
+
+    if (code != ARGOL_OK) {
+        printf("{\"error\": {\"kind\": ");
+        print_str(argol_error_kind(code));
+        printf(", \"message\": ");
+        print_str(args.error);
+        printf("}}\n");
+    } else {
+        printf("{\"args\": {\"n\": ");
+        if (args.has_n)
+            printf("%" PRId64, args.n);
+        else
+            printf("null");
+        printf(", \"pair\": [");
+        for (size_t i = 0; i < args.pair_len; i++) {
+            if (i > 0)
+                printf(", ");
+            print_str(args.pair[i]);
+        }
+        printf("], \"level\": ");
+        if (args.has_level)
+            printf("%" PRIu64, args.level);
+        else
+            printf("null");
+        printf("}}\n");
+    }
+    argol_free(&args);
+    return 0;
+}
+"#;
+
+// Prints the members of tests/fixtures/reserved.argol whose names argolify had to change
+const RESERVED_DRIVER: &str = r#"#define ARGOL_IMPLEMENTATION
+#include "cli.h"
+
+#include <stdio.h>
+
+static const char *or_null(const char *s)
+{
+    return s != NULL ? s : "(null)";
+}
+
+int main(int argc, char **argv)
+{
+    struct argol_args args;
+
+    if (argol_parse(argc, argv, &args) != ARGOL_OK) {
+        printf("%s\n", args.error);
+        argol_free(&args);
+        return 1;
+    }
+    printf("action_arg=%d message_arg=%s error_arg=%s trailing_arg=%s trailing=%zu\n",
+           args.action_arg, or_null(args.message_arg), or_null(args.error_arg),
+           or_null(args.trailing_arg), args.trailing_len);
+    if (args.subcommand == ARGOL_COMMAND_ROOT) {
+        printf("root: message=%s subcommand_arg=%s leaf=%d\n", or_null(args.root.message),
+               or_null(args.root.subcommand_arg),
+               args.root.subcommand == ARGOL_ROOT_ARGS_COMMAND_LEAF);
+    }
+    argol_free(&args);
+    return 0;
+}
+"#;
+
+// Compile the header for `spec` with `driver`
+fn build(spec: &str, driver: &str) -> PathBuf {
+    let out = argolify(&["gen", "c", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let stem = Path::new(spec).file_stem().unwrap().to_str().unwrap();
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("gen_c_{stem}"));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("cli.h"), out.stdout).unwrap();
+    fs::write(dir.join("driver.c"), driver).unwrap();
+
+    let out = Command::new("cc")
+        .args(["-std=c99", "-Wall", "-Werror", "-o", "driver", "driver.c"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    dir.join("driver")
+}
+
+// The values of the arguments the runtime matched for `args`
+fn runtime_values(spec: &str, args: &[&str]) -> Value {
+    let mut argv = vec!["run", "--output", "json", spec, "--"];
+    argv.extend(args);
+    let out = argolify(&argv);
+    let doc: Value = serde_json::from_slice(&out.stdout).unwrap();
+    let value = |name: &str| doc["args"][name]["value"].clone();
+    json!({"n": value("n"), "pair": value("pair"), "level": value("level")})
+}
+
+#[test]
+fn test_argol() {
+    let out = argolify(&["gen", "c", "test.argol"]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden("test.h", &String::from_utf8(out.stdout).unwrap());
+}
+
+#[test]
+fn values_and_errors_match_runtime() {
+    if !has_tool("cc") {
+        return;
+    }
+    let spec = "tests/fixtures/leftover.argol";
+    let driver = build(spec, DRIVER);
+    for args in LEFTOVER_CASES {
+        let out = Command::new(&driver).args(args).output().unwrap();
+        assert!(out.status.success(), "{out:?}");
+        let out: Value = serde_json::from_slice(&out.stdout).unwrap();
+
+        let expected = runtime_error(spec, args);
+        if expected.is_null() {
+            assert_eq!(out["args"], runtime_values(spec, args), "{args:?}");
+        } else {
+            let error = json!({"kind": expected["kind"], "message": expected["message"]});
+            assert_eq!(out["error"], error, "{args:?}");
+        }
+    }
+}
+
+#[test]
+fn reserved_names_are_suffixed() {
+    if !has_tool("cc") {
+        return;
+    }
+    let driver = build("tests/fixtures/reserved.argol", RESERVED_DRIVER);
+    let run = |args: &[&str]| {
+        let out = Command::new(&driver).args(args).output().unwrap();
+        assert!(out.status.success(), "{out:?}");
+        String::from_utf8(out.stdout).unwrap()
+    };
+    assert_eq!(
+        run(&[
+            "--action",
+            "--message",
+            "hi",
+            "--error",
+            "e",
+            "x",
+            "--",
+            "y"
+        ]),
+        "action_arg=1 message_arg=hi error_arg=e trailing_arg=x trailing=1\n"
+    );
+    assert_eq!(
+        run(&["root", "--message", "m", "--subcommand", "s", "leaf"]),
+        "action_arg=0 message_arg=(null) error_arg=(null) trailing_arg=(null) trailing=0\n\
+         root: message=m subcommand_arg=s leaf=1\n"
+    );
+}
+
+#[test]
+fn names_that_become_one_identifier_are_rejected() {
+    let out = argolify(&["gen", "c", "tests/fixtures/same_ident.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains(
+        "argument `dry-run` and argument `dry_run` both become `dry_run` in `struct argol_args`"
+    ));
+    assert!(
+        stderr
+            .contains("argument `new` and command `new` both become `new_` in `struct argol_args`")
+    );
+    assert!(stderr.contains(
+        "the constant for no subcommand of the top level and the constant for command `none` \
+         both become `ARGOL_COMMAND_NONE` in the header"
+    ));
+}
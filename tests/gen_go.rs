@@ -0,0 +1,110 @@
+mod common;
+
+use common::{LEFTOVER_CASES, argolify, assert_golden, has_tool, runtime_error};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MAIN: &str = r#"package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+
+	"driver/cli"
+)
+
+func main() {
+	_, err := cli.Parse(os.Args[1:])
+	var e *cli.Error
+	if !errors.As(err, &e) {
+		fmt.Println("null")
+		return
+	}
+	out, _ := json.Marshal(map[string]any{
+		"command": append([]string{}, e.Command...),
+		"kind":    e.Kind,
+		"subject": e.Subject,
+		"message": e.Message,
+	})
+	fmt.Println(string(out))
+}
+"#;
+
+// Build a program that parses its arguments with the Go parser for `spec` and prints the usage
+// error as JSON, or null
+fn build(spec: &str) -> PathBuf {
+    let out = argolify(&["gen", "go", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let stem = Path::new(spec).file_stem().unwrap().to_str().unwrap();
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("gen_go_{stem}"));
+    fs::create_dir_all(dir.join("cli")).unwrap();
+    fs::write(dir.join("go.mod"), "module driver\n\ngo 1.18\n").unwrap();
+    fs::write(dir.join("main.go"), MAIN).unwrap();
+    fs::write(dir.join("cli/cli.go"), out.stdout).unwrap();
+
+    let out = Command::new("go")
+        .args(["build", "-o", "driver", "."])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    dir.join("driver")
+}
+
+#[test]
+fn nested_command() {
+    let out = argolify(&["gen", "go", "test.argol"]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden("test.go", &String::from_utf8(out.stdout).unwrap());
+}
+
+#[test]
+fn errors_match_runtime() {
+    if !has_tool("go") {
+        return;
+    }
+    let spec = "tests/fixtures/leftover.argol";
+    let driver = build(spec);
+    for args in LEFTOVER_CASES {
+        let out = Command::new(&driver).args(args).output().unwrap();
+        assert!(out.status.success(), "{out:?}");
+        let error: Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(error, runtime_error(spec, args), "{args:?}");
+    }
+}
+
+#[test]
+fn reserved_names_are_suffixed() {
+    let out = argolify(&["gen", "go", "tests/fixtures/reserved.argol"]);
+    assert!(out.status.success(), "{out:?}");
+    let source = String::from_utf8(out.stdout).unwrap();
+    for line in [
+        "\tActionArg bool",
+        "\tMessageArg *string",
+        "\tTrailingArg *string",
+    ] {
+        assert!(source.contains(line), "{line}");
+    }
+    assert!(source.contains("var argsSpec = command{"));
+    assert!(source.contains("var rootArgsSpec = command{"));
+
+    if has_tool("go") {
+        build("tests/fixtures/reserved.argol");
+    }
+}
+
+#[test]
+fn names_that_become_one_identifier_are_rejected() {
+    let out = argolify(&["gen", "go", "tests/fixtures/same_ident.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains("argument `dry-run` and argument `dry_run` both become `DryRun` in `Args`")
+    );
+    assert!(stderr.contains("argument `new` and command `new` both become `New` in `Args`"));
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{argolify, assert_golden, has_tool};
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const DRIVER: &str = r#"
+import json, sys
+
+module = {"__name__": "argol"}
+exec(sys.stdin.read(), module)
+try:
+    args = module["parse_args"](sys.argv[1:])
+except SystemExit:
+    print("null")
+else:
+    print(json.dumps(vars(args), default=str))
+"#;
+
+fn check(spec: &str, golden: &str) {
+    let out = argolify(&["gen", "python", spec]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden(golden, &String::from_utf8(out.stdout).unwrap());
+}
+
+// The namespace the generated module parses `args` into, or null when argparse rejects them
+fn python(spec: &str, args: &[&str]) -> Value {
+    let out = argolify(&["gen", "python", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let mut child = Command::new("python3")
+        .args(["-c", DRIVER])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&out.stdout).unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "{out:?}");
+    serde_json::from_slice(&out.stdout).unwrap()
+}
+
+// The runtime's document for `args`, or null when it reports a usage error
+fn runtime(spec: &str, args: &[&str]) -> Value {
+    let mut argv = vec!["run", "--output", "json", spec, "--"];
+    argv.extend(args);
+    let out = argolify(&argv);
+    let doc: Value = serde_json::from_slice(&out.stdout).unwrap();
+    if doc.get("error").is_some() {
+        Value::Null
+    } else {
+        doc
+    }
+}
+
+// Compare the generated parser with the runtime on `args`: both reject them, or both select the
+// same command and agree on every value that was given or defaulted
+fn agree(spec: &str, args: &[&str]) {
+    let ns = python(spec, args);
+    let doc = runtime(spec, args);
+    if doc.is_null() || ns.is_null() {
+        assert_eq!(ns.is_null(), doc.is_null(), "{args:?}: {ns} vs {doc}");
+        return;
+    }
+    assert_eq!(ns["command"], doc["command"][0], "{args:?}");
+    for (name, arg) in doc["args"].as_object().unwrap() {
+        // argparse handles the help action itself
+        if name == "help" {
+            continue;
+        }
+        let value = &ns[name.as_str()];
+        match arg["source"].as_str().unwrap() {
+            "unset" => assert!(
+                value.is_null() || *value == Value::Array(vec![]),
+                "{args:?}: {name} = {value}"
+            ),
+            _ => assert_eq!(*value, arg["value"], "{args:?}: {name}"),
+        }
+    }
+}
+
+#[test]
+fn test_argol() {
+    check("test.argol", "test.py");
+}
+
+#[test]
+fn types_ranges_and_aliases() {
+    check("tests/fixtures/types.argol", "types.py");
+}
+
+#[test]
+fn positional_next_to_subcommands_matches_runtime() {
+    if !has_tool("python3") {
+        return;
+    }
+    let spec = "tests/fixtures/types.argol";
+    let cases: [&[&str]; 17] = [
+        &[],
+        &["in.txt"],
+        &["in.txt", "-q", "-l", "4"],
+        &["c", "a", "--format", "json"],
+        &["convert", "--format", "yaml", "a", "b"],
+        &["-q", "c", "--format", "json"],
+        &["-l", "3", "c", "--format", "yaml", "x"],
+        &["-l3", "--ratio", "0.25", "c", "--format", "json"],
+        &["--offset", "-4", "c", "--format", "json"],
+        &["--tags", "c", "d"],
+        &["--", "c"],
+        &["c", "--format", "xml"],
+        &["--ratio", "nan"],
+        &["--ratio", "1e999"],
+        &["--offset", "-inf", "c", "--format", "json"],
+        &["--lev", "3"],
+        &["--rat", "0.5", "c", "--format", "json"],
+    ];
+    for args in cases {
+        agree(spec, args);
+    }
+
+    // The documented difference: argparse rejects words after `--` that the runtime keeps as
+    // trailing
+    let args = ["in.txt", "--", "x", "y"];
+    assert_eq!(python(spec, &args), Value::Null);
+    assert_eq!(
+        runtime(spec, &args)["trailing"],
+        serde_json::json!(["x", "y"])
+    );
+}
+
+#[test]
+fn names_that_become_one_identifier_are_rejected() {
+    let out = argolify(&["gen", "python", "tests/fixtures/same_ident.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains(
+        "argument `dry-run` and argument `dry_run` both become `dry_run` in the namespace of the \
+         top level"
+    ));
+    assert!(
+        stderr.contains(
+            "command `a-b` and command `a_b` both become `a_b_parser` in `build_parser()`"
+        )
+    );
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::{argolify, assert_golden, has_tool};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const MANIFEST: &str = r#"[package]
+name = "cli"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+clap = { version = "4", features = ["derive"] }
+
+[workspace]
+"#;
+
+const MAIN: &str = r#"mod cli;
+
+use clap::Parser;
+
+fn main() {
+    println!("{:?}", cli::Cli::parse());
+}
+"#;
+
+fn check(spec: &str, golden: &str) {
+    let out = argolify(&["gen", "rust", spec]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden(golden, &String::from_utf8(out.stdout).unwrap());
+}
+
+// Build a binary printing the `Cli` parsed from its arguments, or `None` when clap cannot be
+// fetched without the network
+fn build(spec: &str) -> Option<PathBuf> {
+    let out = argolify(&["gen", "rust", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let stem = PathBuf::from(spec).file_stem().unwrap().to_owned();
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("gen_rust")
+        .join(stem);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("Cargo.toml"), MANIFEST).unwrap();
+    fs::write(dir.join("src/main.rs"), MAIN).unwrap();
+    fs::write(dir.join("src/cli.rs"), out.stdout).unwrap();
+
+    let out = Command::new("cargo")
+        .args(["build", "--offline", "--quiet"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !out.status.success() && stderr.contains("offline") {
+        eprintln!("skipping: clap is not in the local registry\n{stderr}");
+        return None;
+    }
+    assert!(out.status.success(), "{stderr}");
+    Some(dir.join("target/debug/cli"))
+}
+
+fn run(exe: &PathBuf, args: &[&str]) -> Output {
+    Command::new(exe).args(args).output().unwrap()
+}
+
+fn stdout(exe: &PathBuf, args: &[&str]) -> String {
+    let out = run(exe, args);
+    assert!(out.status.success(), "{out:?}");
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn test_argol() {
+    check("test.argol", "test.rs");
+}
+
+#[test]
+fn types_ranges_and_aliases() {
+    check("tests/fixtures/types.argol", "types.rs");
+}
+
+#[test]
+fn generated_parser_compiles_and_runs() {
+    if !has_tool("cargo") {
+        return;
+    }
+    let Some(exe) = build("tests/fixtures/types.argol") else {
+        return;
+    };
+
+    let out = stdout(
+        &exe,
+        &["in.txt", "--offset", "-4", "--ratio", "-0.5", "-l", "3"],
+    );
+    assert!(out.contains(r#"input: Some("in.txt")"#), "{out}");
+    assert!(out.contains("offset: Some(-4)"), "{out}");
+    assert!(out.contains("ratio: Some(-0.5)"), "{out}");
+    assert!(out.contains("level: 3"), "{out}");
+
+    let out = stdout(&exe, &["c", "a", "--format", "json"]);
+    assert!(
+        out.contains(r#"Convert(ConvertArgs { outputs: ["a"], format: "json" })"#),
+        "{out}"
+    );
+
+    for args in [
+        &["-l", "9"][..],
+        &["c", "--format", "xml"],
+        &["--offset", "x"],
+        &["--ratio", "nan"],
+        &["--ratio", "inf"],
+    ] {
+        let out = run(&exe, args);
+        assert_eq!(out.status.code(), Some(2), "{args:?}: {out:?}");
+    }
+}
+
+#[test]
+fn reserved_names_are_suffixed() {
+    if !has_tool("cargo") {
+        return;
+    }
+    let Some(exe) = build("tests/fixtures/keywords.argol") else {
+        return;
+    };
+    assert_eq!(
+        stdout(&exe, &["--crate", "--self", "s", "--type", "t", "x"]),
+        "Cli { crate_: true, super_: Some(\"x\"), self_: Some(\"s\"), type: Some(\"t\") }\n"
+    );
+}
+
+#[test]
+fn names_that_become_one_identifier_are_rejected() {
+    let out = argolify(&["gen", "rust", "tests/fixtures/same_ident.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains("argument `dry-run` and argument `dry_run` both become `dry_run` in `Cli`")
+    );
+    assert!(stderr.contains("command `a-b` and command `a_b` both become `AB` in `Commands`"));
+    assert!(stderr.contains("command `a-b` and command `a_b` both become `ABArgs` in the module"));
+}
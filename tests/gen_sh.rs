@@ -0,0 +1,155 @@
+mod common;
+
+use common::argolify;
+use std::fs;
+use std::process::{Command, Output};
+
+// Generate a parser for `spec`, call it with `args` under /bin/sh, then run `after`
+fn run(spec: &str, args: &[&str], after: &str) -> Output {
+    let out = argolify(&["gen", "sh", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let script = String::from_utf8(out.stdout).unwrap();
+    Command::new("/bin/sh")
+        .arg("-c")
+        .arg(format!("{script}\nargol_parse \"$@\"\n{after}"))
+        .arg("sh")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(spec: &str, args: &[&str], after: &str) -> String {
+    let out = run(spec, args, after);
+    assert!(out.status.success(), "{out:?}");
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn values_and_defaults() {
+    let print = r#"printf '%s\n' "$ARGOL_INPUT" "$ARGOL_LEVEL" "$ARGOL_QUIET" "$ARGOL_RATIO" "$ARGOL_STRICT""#;
+    let args = [
+        "in.txt",
+        "-l",
+        "3",
+        "-q",
+        "--ratio",
+        "-0.5",
+        "--strict=true",
+    ];
+    assert_eq!(
+        stdout("tests/fixtures/types.argol", &args, print),
+        "in.txt\n3\ntrue\n-0.5\ntrue\n"
+    );
+    assert_eq!(
+        stdout("tests/fixtures/types.argol", &[], print),
+        "\n2\nfalse\n\n\n"
+    );
+}
+
+#[test]
+fn lists_expand_with_eval() {
+    let print = r#"eval "set -- $ARGOL_TAGS"; printf '<%s>' "$@""#;
+    let args = ["--tags", "a", "b c", "-q", "--tags", "it's", "$HOME"];
+    assert_eq!(
+        stdout("tests/fixtures/types.argol", &args, print),
+        "<a><b c><it's><$HOME>"
+    );
+}
+
+#[test]
+fn subcommand_and_trailing() {
+    let print = r#"eval "set -- $ARGOL_OUTPUTS"; printf '%s|%s|%s\n' "$ARGOL_COMMAND" "$ARGOL_FORMAT" "$*""#;
+    let args = ["c", "--format", "yaml", "a.yml", "--", "b.yml"];
+    assert_eq!(
+        stdout("tests/fixtures/types.argol", &args, print),
+        "convert|yaml|a.yml b.yml\n"
+    );
+
+    let print = r#"eval "set -- $ARGOL_TRAILING"; printf '%s|%s\n' "$ARGOL_INPUT" "$*""#;
+    let args = ["in.txt", "--", "x", "y"];
+    assert_eq!(
+        stdout("tests/fixtures/types.argol", &args, print),
+        "in.txt|x y\n"
+    );
+}
+
+#[test]
+fn actions_print_and_exit() {
+    assert_eq!(
+        stdout("test.argol", &["-v"], "echo unreachable"),
+        "proj-man 0.1.0\n"
+    );
+    let help = fs::read_to_string("tests/golden/help_new.txt").unwrap();
+    assert_eq!(
+        stdout("test.argol", &["new", "-h"], "echo unreachable"),
+        help
+    );
+}
+
+#[test]
+fn usage_errors() {
+    let cases: [(&[&str], &str); 8] = [
+        (&["--nope"], "unknown option '--nope'"),
+        (
+            &["-l", "9"],
+            "invalid value '9' for 'level' (possible values: 1..=5)",
+        ),
+        (
+            &["--offset", "x"],
+            "invalid value 'x' for 'offset': expected int",
+        ),
+        (
+            &["c", "out"],
+            "missing required argument 'format' (in convert)",
+        ),
+        (&["--quiet=yes"], "flag '--quiet' does not take a value"),
+        (&["a", "b"], "unexpected argument 'b'"),
+        (
+            &["--ratio", "nan"],
+            "invalid value 'nan' for 'ratio': expected float",
+        ),
+        (
+            &["--ratio", "1e999"],
+            "invalid value '1e999' for 'ratio': expected float",
+        ),
+    ];
+    for (args, message) in cases {
+        let out = run("tests/fixtures/types.argol", args, "echo unreachable");
+        assert_eq!(out.status.code(), Some(2), "{args:?}");
+        assert!(out.stdout.is_empty(), "{args:?}");
+        let stderr = String::from_utf8(out.stderr).unwrap();
+        assert_eq!(
+            stderr.lines().next(),
+            Some(format!("error: {message}").as_str())
+        );
+    }
+}
+
+#[test]
+fn names_that_set_the_same_variable_are_rejected() {
+    let out = argolify(&["gen", "sh", "tests/fixtures/same_ident.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(
+        stderr
+            .matches(
+                "argument `dry-run` and argument `dry_run` both become `ARGOL_DRY_RUN` in the \
+                 script's variables"
+            )
+            .count(),
+        1,
+        "{stderr}"
+    );
+    assert!(stderr.contains(
+        "command `a-b` and command `a_b` both become `_argol_parse_a_b` in the script's functions"
+    ));
+
+    let out = argolify(&["gen", "sh", "tests/fixtures/reserved.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains(
+        "the `trailing` variable and argument `trailing` both become `ARGOL_TRAILING` in the \
+         script's variables"
+    ));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{LEFTOVER_CASES, argolify, assert_golden, has_tool, runtime_error};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MAIN: &str = r#"import { parse, ParseError } from "./cli";
+
+const argv: string[] = (globalThis as any).process.argv.slice(2);
+let out: object;
+try {
+  out = { args: parse(argv) };
+} catch (e) {
+  if (!(e instanceof ParseError)) {
+    throw e;
+  }
+  const { command, kind, subject, message } = e;
+  out = { error: { command, kind, subject, message } };
+}
+console.log(JSON.stringify(out));
+"#;
+
+// Compile the TypeScript parser for `spec` with a driver printing `{"args": ...}` or
+// `{"error": ...}` as JSON, returning the script to run with node
+fn build(spec: &str) -> PathBuf {
+    let out = argolify(&["gen", "ts", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let stem = Path::new(spec).file_stem().unwrap().to_str().unwrap();
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("gen_ts_{stem}"));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("cli.ts"), out.stdout).unwrap();
+    fs::write(dir.join("main.ts"), MAIN).unwrap();
+
+    let out = Command::new("tsc")
+        .args(["--strict", "--target", "es2020", "--module", "commonjs"])
+        .args(["--outDir", "out", "cli.ts", "main.ts"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    dir.join("out/main.js")
+}
+
+fn run(script: &Path, args: &[&str]) -> Value {
+    let out = Command::new("node")
+        .arg(script)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    serde_json::from_slice(&out.stdout).unwrap()
+}
+
+#[test]
+fn test_argol() {
+    let out = argolify(&["gen", "ts", "test.argol"]);
+    assert!(out.status.success(), "{out:?}");
+    assert_golden("test.ts", &String::from_utf8(out.stdout).unwrap());
+}
+
+#[test]
+fn errors_and_integers_match_runtime() {
+    if !has_tool("node") || !has_tool("tsc") {
+        return;
+    }
+    let spec = "tests/fixtures/leftover.argol";
+    let script = build(spec);
+    for args in LEFTOVER_CASES {
+        let out = run(&script, args);
+        let error = out.get("error").cloned().unwrap_or(Value::Null);
+        assert_eq!(error, runtime_error(spec, args), "{args:?}");
+    }
+
+    // Whole numbers a double cannot hold exactly are rejected rather than rounded
+    let out = run(&script, &["9007199254740991"]);
+    assert_eq!(out["args"]["n"], json!(9007199254740991_i64));
+    let out = run(&script, &["9007199254740993"]);
+    assert_eq!(out["error"]["kind"], "invalid_value");
+    assert_eq!(out["error"]["subject"], "n");
+}
+
+#[test]
+fn reserved_names_are_suffixed() {
+    let spec = "tests/fixtures/reserved.argol";
+    let out = argolify(&["gen", "ts", spec]);
+    assert!(out.status.success(), "{out:?}");
+    let source = String::from_utf8(out.stdout).unwrap();
+    for line in [
+        "  actionArg: boolean;",
+        "  messageArg?: string;",
+        "  trailingArg?: string;",
+    ] {
+        assert!(source.contains(line), "{line}");
+    }
+    assert!(source.contains("const argsSpec: Command = {"));
+    assert!(source.contains("const rootArgsSpec: Command = {"));
+
+    if !has_tool("node") || !has_tool("tsc") {
+        return;
+    }
+    let script = build(spec);
+    let out = run(&script, &["--action", "--message", "hi", "x", "--", "y"]);
+    assert_eq!(out["args"]["actionArg"], true);
+    assert_eq!(out["args"]["action"], Value::Null);
+    assert_eq!(out["args"]["messageArg"], "hi");
+    assert_eq!(out["args"]["trailingArg"], "x");
+    assert_eq!(out["args"]["trailing"], json!(["y"]));
+}
+
+#[test]
+fn names_that_become_one_identifier_are_rejected() {
+    let out = argolify(&["gen", "ts", "tests/fixtures/same_ident.argol"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains("argument `dry-run` and argument `dry_run` both become `dryRun` in `Args`")
+    );
+    assert!(stderr.contains("argument `new` and command `new` both become `new` in `Args`"));
+}
@@ -0,0 +1,176 @@
+// Generated by argolify from `test.argol`; do not edit.
+//
+// Types for the JSON printed by `argolify run --output json` and by executables from
+// `argolify build` for this spec. Needs `serde` (with the `derive` feature) and `serde_json`:
+//
+//     let doc: Document = serde_json::from_slice(&output.stdout)?;
+
+use serde::Deserialize;
+
+/// Version of the document these types read.
+pub const VERSION: u32 = 1;
+
+/// What the parser printed: the matched arguments, an action a flag asked for, or a usage error.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Raw")]
+pub enum Document {
+    Matches(Matches),
+    Action(ActionRequest),
+    Error(UsageError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matches {
+    pub command: Command,
+    /// Words after `--` that no positional argument took.
+    pub trailing: Vec<String>,
+}
+
+/// The selected command and its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Root(Args),
+    New(NewArgs),
+}
+
+/// A flag with an action was given to the command at `command`; matching stopped there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequest {
+    pub action: Action,
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageError {
+    pub command: Vec<String>,
+    pub kind: ErrorKind,
+    /// The option or argument the error is about.
+    pub subject: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ShowHelp,
+    ShowVersion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    UnknownOption,
+    UnexpectedArgument,
+    FlagWithValue,
+    MissingValue,
+    TooManyValues,
+    MissingRequired,
+    InvalidValue,
+    NotAllowed,
+}
+
+/// Where an argument's value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Argv,
+    Default,
+    Unset,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Arg<T> {
+    pub source: Source,
+    pub value: T,
+}
+
+/// Top-level arguments.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Args {
+    /// Show version & exit
+    pub version: Arg<bool>,
+    /// Show `proj-man` help & exit
+    pub help: Arg<bool>,
+}
+
+/// Arguments of `new`, with those of the commands above it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NewArgs {
+    /// Show `proj-man new` help & exit
+    pub help: Arg<bool>,
+    /// Do not create a virtual environment
+    pub venv: Arg<bool>,
+    /// Show verbose logging for project creation
+    pub verbose: Arg<bool>,
+    /// Project name
+    pub name: Arg<String>,
+    /// Initial project version
+    pub version: Arg<String>,
+    /// Project dependencies to intstall
+    pub deps: Arg<Option<String>>,
+    pub color: Arg<Option<i64>>,
+}
+
+#[derive(Deserialize)]
+struct Raw {
+    version: u32,
+    command: Vec<String>,
+    action: Option<Action>,
+    #[serde(default)]
+    args: serde_json::Value,
+    #[serde(default)]
+    trailing: Vec<String>,
+    error: Option<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawError {
+    kind: ErrorKind,
+    subject: String,
+    message: String,
+}
+
+fn args<T: serde::de::DeserializeOwned>(args: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(args).map_err(|e| e.to_string())
+}
+
+impl TryFrom<Raw> for Document {
+    type Error = String;
+
+    fn try_from(raw: Raw) -> Result<Self, String> {
+        if raw.version != VERSION {
+            return Err(format!("unsupported document version {}", raw.version));
+        }
+        if let Some(error) = raw.error {
+            return Ok(Document::Error(UsageError {
+                command: raw.command,
+                kind: error.kind,
+                subject: error.subject,
+                message: error.message,
+            }));
+        }
+        if let Some(action) = raw.action {
+            return Ok(Document::Action(ActionRequest {
+                action,
+                command: raw.command,
+            }));
+        }
+        let command = match raw.command.join(" ").as_str() {
+            "" => Command::Root(args(raw.args)?),
+            "new" => Command::New(args(raw.args)?),
+            path => return Err(format!("unknown command `{path}`")),
+        };
+        Ok(Document::Matches(Matches {
+            command,
+            trailing: raw.trailing,
+        }))
+    }
+}
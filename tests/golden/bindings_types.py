@@ -0,0 +1,175 @@
+# Generated by argolify from `tests/fixtures/types.argol`; do not edit.
+#
+# Types for the JSON printed by `argolify run --output json` and by executables from
+# `argolify build` for this spec:
+#
+#     doc = parse(proc.stdout)
+
+from __future__ import annotations
+
+import json
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Generic, Literal, TypeVar, Union
+
+VERSION = 1
+
+Action = Literal["show_help", "show_version"]
+Source = Literal["argv", "default", "unset"]
+ErrorKind = Literal[
+    "unknown_option",
+    "unexpected_argument",
+    "flag_with_value",
+    "missing_value",
+    "too_many_values",
+    "missing_required",
+    "invalid_value",
+    "not_allowed",
+]
+
+T = TypeVar("T")
+
+
+@dataclass(frozen=True)
+class Arg(Generic[T]):
+    source: Source
+    value: T
+
+
+def _arg(args, name, convert=None):
+    arg = args[name]
+    value = arg["value"]
+    if convert is not None and isinstance(value, list):
+        value = [convert(v) for v in value]
+    elif convert is not None and value is not None:
+        value = convert(value)
+    return Arg(arg["source"], value)
+
+
+@dataclass(frozen=True)
+class Args:
+    """Top-level arguments."""
+
+    # Show help & exit
+    help: Arg[bool]
+    # Print less
+    quiet: Arg[bool]
+    # Input file
+    input: Arg[Path | None]
+    # Log level
+    level: Arg[int]
+    # Sample ratio
+    ratio: Arg[float | None]
+    # Offset from the start
+    offset: Arg[int | None]
+    # Tags to apply
+    tags: Arg[list[str]]
+    # Fail on warnings
+    strict: Arg[bool | None]
+
+    @classmethod
+    def from_json(cls, args: dict) -> Args:
+        return cls(
+            help=_arg(args, "help"),
+            quiet=_arg(args, "quiet"),
+            input=_arg(args, "input", Path),
+            level=_arg(args, "level"),
+            ratio=_arg(args, "ratio", float),
+            offset=_arg(args, "offset"),
+            tags=_arg(args, "tags"),
+            strict=_arg(args, "strict"),
+        )
+
+
+@dataclass(frozen=True)
+class ConvertArgs:
+    """Arguments of `convert`, with those of the commands above it."""
+
+    # Show help & exit
+    help: Arg[bool]
+    # Print less
+    quiet: Arg[bool]
+    # Input file
+    input: Arg[Path | None]
+    # Log level
+    level: Arg[int]
+    # Sample ratio
+    ratio: Arg[float | None]
+    # Offset from the start
+    offset: Arg[int | None]
+    # Tags to apply
+    tags: Arg[list[str]]
+    # Fail on warnings
+    strict: Arg[bool | None]
+    # Output files
+    outputs: Arg[list[Path]]
+    # Output format
+    format: Arg[str]
+
+    @classmethod
+    def from_json(cls, args: dict) -> ConvertArgs:
+        return cls(
+            help=_arg(args, "help"),
+            quiet=_arg(args, "quiet"),
+            input=_arg(args, "input", Path),
+            level=_arg(args, "level"),
+            ratio=_arg(args, "ratio", float),
+            offset=_arg(args, "offset"),
+            tags=_arg(args, "tags"),
+            strict=_arg(args, "strict"),
+            outputs=_arg(args, "outputs", Path),
+            format=_arg(args, "format"),
+        )
+
+
+# The selected command's arguments
+Command = Union[
+    Args,
+    ConvertArgs,
+]
+
+
+@dataclass(frozen=True)
+class Matches:
+    command: Command
+    # Words after `--` that no positional argument took
+    trailing: list[str]
+
+
+@dataclass(frozen=True)
+class ActionRequest:
+    """A flag with an action was given to the command at `command`."""
+
+    action: Action
+    command: list[str]
+
+
+@dataclass(frozen=True)
+class UsageError:
+    command: list[str]
+    kind: ErrorKind
+    # The option or argument the error is about
+    subject: str
+    message: str
+
+
+_COMMANDS = {
+    "": Args,
+    "convert": ConvertArgs,
+}
+
+
+def parse(text: str | bytes) -> Matches | ActionRequest | UsageError:
+    """Read the JSON printed by the parser."""
+    doc = json.loads(text)
+    if doc["version"] != VERSION:
+        raise ValueError(f"unsupported document version {doc['version']}")
+    if "error" in doc:
+        e = doc["error"]
+        return UsageError(doc["command"], e["kind"], e["subject"], e["message"])
+    if doc["action"] is not None:
+        return ActionRequest(doc["action"], doc["command"])
+    path = " ".join(doc["command"])
+    if path not in _COMMANDS:
+        raise ValueError(f"unknown command {path!r}")
+    return Matches(_COMMANDS[path].from_json(doc["args"]), doc["trailing"])
@@ -0,0 +1,68 @@
+# Generated by argolify from `test.argol`; do not edit.
+#
+# fish completion for `proj-man`: install it as ~/.config/fish/completions/proj-man.fish.
+
+# Print the command selected by the words before the cursor, the number of positional
+# words given to it and whether `--` was seen, walking them the way the parser does
+function __proj_man_state
+    set -l cmd proj_man
+    set -l pos 0
+    set -l left 0
+    set -l dashes 0
+    set -l words (commandline -opc)
+    set -e words[1]
+    for word in $words
+        if test $dashes = 1
+            set pos (math $pos + 1)
+        else if test $left != 0; and not string match -qr -- '^-.' $word
+            set left (math $left - 1)
+        else if test $word = --
+            set dashes 1
+            set left 0
+        else if string match -qr -- '^-.' $word
+            set left 0
+            switch $cmd,$word
+                case proj_man__new,--version proj_man__new,-d proj_man__new,--deps proj_man__new,--dependencies proj_man__new,--color
+                    set left 1
+            end
+        else if test $pos != 0
+            set pos (math $pos + 1)
+        else
+            switch $cmd,$word
+                case proj_man,new
+                    set cmd proj_man__new
+                case '*'
+                    set pos 1
+            end
+        end
+    end
+    echo $cmd $pos $dashes
+end
+
+# Whether the words so far select command $argv[1], with a positional count from $argv[2]
+# up to $argv[3] when given
+function __proj_man_at
+    set -l state (string split ' ' (__proj_man_state))
+    test $state[1] = $argv[1]; or return 1
+    set -q argv[2]; and test $state[2] -lt $argv[2]; and return 1
+    set -q argv[3]; and test $state[2] -gt $argv[3]; and return 1
+    return 0
+end
+
+# Whether a subcommand of $argv[1] can come next
+function __proj_man_sub
+    test (__proj_man_state) = "$argv[1] 0 0"
+end
+
+complete -c 'proj-man' -f
+
+complete -c 'proj-man' -n '__proj_man_at proj_man' -s 'v' -l 'version' -d 'Show version & exit'
+complete -c 'proj-man' -n '__proj_man_at proj_man' -s 'h' -l 'help' -l 'usage' -d 'Show `proj-man` help & exit'
+complete -c 'proj-man' -n '__proj_man_sub proj_man' -a 'new' -d 'Create a new proj-man project'
+
+complete -c 'proj-man' -n '__proj_man_at proj_man__new' -s 'h' -l 'help' -d 'Show `proj-man new` help & exit'
+complete -c 'proj-man' -n '__proj_man_at proj_man__new' -l 'venv' -d 'Do not create a virtual environment'
+complete -c 'proj-man' -n '__proj_man_at proj_man__new' -l 'verbose' -d 'Show verbose logging for project creation'
+complete -c 'proj-man' -n '__proj_man_at proj_man__new' -l 'version' -x -d 'Initial project version'
+complete -c 'proj-man' -n '__proj_man_at proj_man__new' -s 'd' -l 'deps' -l 'dependencies' -x -d 'Project dependencies to intstall'
+complete -c 'proj-man' -n '__proj_man_at proj_man__new' -l 'color' -x -a '3 4'
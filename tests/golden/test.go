@@ -0,0 +1,458 @@
+// Code generated by argolify from `test.argol`; DO NOT EDIT.
+
+package cli
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+	"unicode/utf8"
+)
+
+// Parse matches argv (without the program name) against the spec.
+func Parse(argv []string) (*Args, error) {
+	args := &Args{}
+	if err := args.parse(args, argv); err != nil {
+		return nil, err
+	}
+	return args, nil
+}
+
+// Args holds the arguments matched for proj-man.
+type Args struct {
+	// Command is the path of subcommands given, by their first names.
+	Command []string
+	// Action is "show_help" or "show_version" when a flag with that action was given. Matching
+	// stops at the flag, and Message holds the text to print.
+	Action  string
+	Message string
+	// Trailing holds the words after `--` that no positional took.
+	Trailing []string
+
+	// New is set when the `new` command is given.
+	New *NewArgs
+}
+
+var argsSpec = command{
+	[]option{
+		{"version", 'v', []string{"version"}, true, "show_version", 0, 0},
+		{"help", 'h', []string{"help", "usage"}, true, "show_help", 0, 0},
+	},
+	[]positional{},
+	map[string]string{
+		"new": "new",
+	},
+	"Usage: proj-man [OPTIONS] [COMMAND]\n" +
+		"\n" +
+		"Options:\n" +
+		"  -v, --version        Show version & exit\n" +
+		"  -h, --help, --usage  Show `proj-man` help & exit\n" +
+		"\n" +
+		"Commands:\n" +
+		"  new  Create a new proj-man project\n",
+	"proj-man 0.1.0\n",
+}
+
+func (a *Args) parse(root *Args, argv []string) error {
+	m, err := match(&argsSpec, root.Command, argv)
+	if err != nil {
+		return err
+	}
+	if m.action != "" {
+		root.Action, root.Message = m.action, m.message
+		return nil
+	}
+	root.Trailing = append(root.Trailing, m.trailing...)
+
+	if err := m.leftover(); err != nil {
+		return err
+	}
+
+	switch m.sub {
+	case "new":
+		root.Command = append(root.Command, "new")
+		a.New = &NewArgs{}
+		return a.New.parse(root, m.rest)
+	}
+	return nil
+}
+
+// NewArgs holds the arguments of `proj-man new`.
+type NewArgs struct {
+	// Do not create a virtual environment
+	Venv bool
+	// Show verbose logging for project creation
+	Verbose bool
+	// Project name
+	Name string
+	// Initial project version
+	Version string
+	// Project dependencies to intstall
+	Deps  *string
+	Color *int64
+}
+
+var newArgsSpec = command{
+	[]option{
+		{"help", 'h', []string{"help"}, true, "show_help", 0, 0},
+		{"venv", 0, []string{"venv"}, true, "", 0, 0},
+		{"verbose", 0, []string{"verbose"}, true, "", 0, 0},
+		{"version", 0, []string{"version"}, false, "", 1, 1},
+		{"deps", 'd', []string{"deps", "dependencies"}, false, "", 1, 1},
+		{"color", 0, []string{"color"}, false, "", 1, 1},
+	},
+	[]positional{
+		{"name", 1, 1},
+	},
+	map[string]string{},
+	"Create a new proj-man project\n" +
+		"\n" +
+		"Usage: proj-man new [OPTIONS] <name>\n" +
+		"\n" +
+		"Arguments:\n" +
+		"  <name>  Project name\n" +
+		"\n" +
+		"Options:\n" +
+		"  -h, --help                      Show `proj-man new` help & exit\n" +
+		"      --venv                      Do not create a virtual environment\n" +
+		"      --verbose                   Show verbose logging for project creation\n" +
+		"      --version <VERSION>         Initial project version [default: 0.1.0]\n" +
+		"  -d, --deps, --dependencies <DEPS>\n" +
+		"                                  Project dependencies to intstall\n" +
+		"      --color <COLOR>             [possible values: 3, 4]\n",
+	"",
+}
+
+func (a *NewArgs) parse(root *Args, argv []string) error {
+	m, err := match(&newArgsSpec, root.Command, argv)
+	if err != nil {
+		return err
+	}
+	if m.action != "" {
+		root.Action, root.Message = m.action, m.message
+		return nil
+	}
+	root.Trailing = append(root.Trailing, m.trailing...)
+	a.Venv = m.flags["venv"]
+	a.Verbose = m.flags["verbose"]
+
+	if len(m.values["name"]) == 0 {
+		return m.missing("name")
+	}
+	for _, s := range m.values["name"] {
+		a.Name = s
+	}
+
+	if err := m.leftover(); err != nil {
+		return err
+	}
+
+	a.Version = "0.1.0"
+	for _, s := range m.values["version"] {
+		a.Version = s
+	}
+
+	for _, s := range m.values["deps"] {
+		a.Deps = &s
+	}
+
+	for _, s := range m.values["color"] {
+		x, err := strconv.ParseInt(s, 10, 64)
+		if err != nil {
+			return m.invalid("color", "int", s)
+		}
+		if f := float64(x); !(f == 3 || f == 4) {
+			return m.notAllowed("color", s, "3", "4")
+		}
+		a.Color = &x
+	}
+	return nil
+}
+
+// Error is a usage error. Kind and Subject are those of the error object in argolify's JSON
+// output.
+type Error struct {
+	Kind    string
+	Subject string
+	Command []string
+	Message string
+}
+
+func (e *Error) Error() string {
+	if len(e.Command) == 0 {
+		return e.Message
+	}
+	return e.Message + " (in " + strings.Join(e.Command, " ") + ")"
+}
+
+// option is a flag or named argument: {key, short, long names, flag, action, min, max}, where a max
+// of -1 means no limit.
+type option struct {
+	key    string
+	short  rune
+	long   []string
+	flag   bool
+	action string
+	min    int
+	max    int
+}
+
+// positional is {key, min, max}.
+type positional struct {
+	key string
+	min int
+	max int
+}
+
+// command is {options, positionals, subcommands by every name, help text, version text}.
+type command struct {
+	options     []option
+	positionals []positional
+	commands    map[string]string
+	help        string
+	version     string
+}
+
+func (c *command) long(name string) *option {
+	for i := range c.options {
+		for _, l := range c.options[i].long {
+			if l == name {
+				return &c.options[i]
+			}
+		}
+	}
+	return nil
+}
+
+func (c *command) short(r rune) *option {
+	for i := range c.options {
+		if c.options[i].short == r {
+			return &c.options[i]
+		}
+	}
+	return nil
+}
+
+// A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short name claims it.
+func (c *command) isValue(arg string) bool {
+	r, _ := utf8.DecodeRuneInString(arg[1:])
+	if c.short(r) != nil {
+		return false
+	}
+	_, err := parseFloat(arg[1:])
+	return err == nil
+}
+
+// parseFloat reads a finite decimal the way argolify does, so no inf, nan or hex literals and
+// nothing that overflows.
+func parseFloat(s string) (float64, error) {
+	if strings.ContainsAny(s, "nNxX") {
+		return 0, strconv.ErrSyntax
+	}
+	return strconv.ParseFloat(s, 64)
+}
+
+func (c *command) isOption(arg string) bool {
+	return arg == "--" || len(arg) > 1 && arg[0] == '-' && !c.isValue(arg)
+}
+
+func expects(min, max int) string {
+	switch {
+	case min == max:
+		return strconv.Itoa(min)
+	case max < 0 && min == 0:
+		return "any number of"
+	case max < 0:
+		return "at least " + strconv.Itoa(min)
+	default:
+		return fmt.Sprintf("%d to %d", min, max)
+	}
+}
+
+// matched holds the words given to one command, before they are converted.
+type matched struct {
+	path     []string
+	flags    map[string]bool
+	values   map[string][]string
+	trailing []string
+	// unexpected is set when trailing holds words left over before `--`.
+	unexpected bool
+	action     string
+	message    string
+	sub        string
+	rest       []string
+}
+
+func (m *matched) fail(kind, subject, message string) error {
+	return &Error{Kind: kind, Subject: subject, Command: m.path, Message: message}
+}
+
+func (m *matched) missing(name string) error {
+	return m.fail("missing_required", name, "missing required argument '"+name+"'")
+}
+
+func (m *matched) invalid(name, ty, s string) error {
+	message := fmt.Sprintf("invalid value '%s' for '%s': expected %s", s, name, ty)
+	return m.fail("invalid_value", name, message)
+}
+
+func (m *matched) notAllowed(name, s string, choices ...string) error {
+	message := fmt.Sprintf(
+		"invalid value '%s' for '%s' (possible values: %s)", s, name, strings.Join(choices, ", "))
+	return m.fail("not_allowed", name, message)
+}
+
+func (m *matched) setFlag(c *command, opt *option) {
+	m.flags[opt.key] = true
+	switch opt.action {
+	case "show_help":
+		m.action, m.message = opt.action, c.help
+	case "show_version":
+		m.action, m.message = opt.action, c.version
+	}
+}
+
+// Collect the values of a named argument: given inline ones, then following words up to the
+// maximum, stopping at anything that looks like an option. Returns how many words were used.
+func (m *matched) take(c *command, opt *option, given, rest []string) (int, error) {
+	n := 0
+	for (opt.max < 0 || len(given) < opt.max) && n < len(rest) && !c.isOption(rest[n]) {
+		given = append(given, rest[n])
+		n++
+	}
+	if len(given) < opt.min {
+		name := "--" + opt.key
+		message := fmt.Sprintf("'%s' expects %s value(s)", name, expects(opt.min, opt.max))
+		return 0, m.fail("missing_value", name, message)
+	}
+	if opt.max == 1 {
+		m.values[opt.key] = given
+	} else {
+		m.values[opt.key] = append(m.values[opt.key], given...)
+	}
+	return n, nil
+}
+
+func (m *matched) short(c *command, cluster string, rest []string) (int, error) {
+	for i, r := range cluster {
+		opt := c.short(r)
+		if opt == nil {
+			name := "-" + string(r)
+			return 0, m.fail("unknown_option", name, "unknown option '"+name+"'")
+		}
+		if opt.flag {
+			m.setFlag(c, opt)
+			if m.action != "" {
+				return 0, nil
+			}
+			continue
+		}
+		var given []string
+		if value := strings.TrimPrefix(cluster[i+utf8.RuneLen(r):], "="); value != "" {
+			given = append(given, value)
+		}
+		return m.take(c, opt, given, rest)
+	}
+	return 0, nil
+}
+
+// Hand out positional words in order, leaving enough for the minimum of later positionals. Leftover
+// words are only allowed after `--`, where they are passed through as trailing; see leftover.
+func (m *matched) finish(c *command, words []string, dashes int) error {
+	total := len(words)
+	for i, p := range c.positionals {
+		reserved := 0
+		for _, later := range c.positionals[i+1:] {
+			reserved += later.min
+		}
+		n := len(words) - reserved
+		if n < 0 {
+			n = 0
+		}
+		if p.max >= 0 && n > p.max {
+			n = p.max
+		}
+		given := words[:n]
+		words = words[n:]
+		if len(given) > 0 && len(given) < p.min {
+			message := fmt.Sprintf("'%s' expects %s value(s)", p.key, expects(p.min, p.max))
+			return m.fail("missing_value", p.key, message)
+		}
+		if len(given) > 0 {
+			m.values[p.key] = given
+		}
+	}
+
+	m.trailing = words
+	m.unexpected = len(words) > 0 && (dashes < 0 || total-len(words) < dashes)
+	return nil
+}
+
+// leftover reports the first word left over before `--`, once positionals are converted.
+func (m *matched) leftover() error {
+	if !m.unexpected {
+		return nil
+	}
+	word := m.trailing[0]
+	return m.fail("unexpected_argument", word, "unexpected argument '"+word+"'")
+}
+
+func (m *matched) tooMany(key string, min, max int) error {
+	name := "--" + key
+	message := fmt.Sprintf("'%s' expects %s value(s)", name, expects(min, max))
+	return m.fail("too_many_values", name, message)
+}
+
+// Match argv against c, stopping at the first subcommand.
+func match(c *command, path, argv []string) (*matched, error) {
+	m := &matched{path: path, flags: map[string]bool{}, values: map[string][]string{}}
+	var words []string
+	dashes := -1
+	for i := 0; i < len(argv); i++ {
+		arg := argv[i]
+		switch {
+		case dashes >= 0:
+			words = append(words, arg)
+		case arg == "--":
+			dashes = len(words)
+		case strings.HasPrefix(arg, "--"):
+			name, value, inline := strings.Cut(arg[2:], "=")
+			opt := c.long(name)
+			switch {
+			case opt == nil:
+				return nil, m.fail("unknown_option", "--"+name, "unknown option '--"+name+"'")
+			case opt.flag && inline:
+				message := "flag '--" + name + "' does not take a value"
+				return nil, m.fail("flag_with_value", "--"+name, message)
+			case opt.flag:
+				m.setFlag(c, opt)
+			default:
+				var given []string
+				if inline {
+					given = append(given, value)
+				}
+				n, err := m.take(c, opt, given, argv[i+1:])
+				if err != nil {
+					return nil, err
+				}
+				i += n
+			}
+		case c.isOption(arg):
+			n, err := m.short(c, arg[1:], argv[i+1:])
+			if err != nil {
+				return nil, err
+			}
+			i += n
+		default:
+			if sub, ok := c.commands[arg]; ok && len(words) == 0 {
+				m.sub, m.rest = sub, argv[i+1:]
+				return m, m.finish(c, words, dashes)
+			}
+			words = append(words, arg)
+		}
+		if m.action != "" {
+			return m, nil
+		}
+	}
+	return m, m.finish(c, words, dashes)
+}
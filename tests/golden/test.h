@@ -0,0 +1,745 @@
+/*
+ * Generated by argolify from `test.argol`; do not edit.
+ *
+ * Define ARGOL_IMPLEMENTATION in one C source file before including this header.
+ */
+#ifndef ARGOL_PROJ_MAN_H
+#define ARGOL_PROJ_MAN_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Return codes of argol_parse: ARGOL_OK, or one per kind of error in argolify's JSON output. */
+enum argol_error {
+    ARGOL_OK = 0,
+    ARGOL_UNKNOWN_OPTION,
+    ARGOL_UNEXPECTED_ARGUMENT,
+    ARGOL_FLAG_WITH_VALUE,
+    ARGOL_MISSING_VALUE,
+    ARGOL_TOO_MANY_VALUES,
+    ARGOL_MISSING_REQUIRED,
+    ARGOL_INVALID_VALUE,
+    ARGOL_NOT_ALLOWED,
+    /* The lists of values could not be allocated */
+    ARGOL_NO_MEMORY
+};
+
+enum argol_action {
+    ARGOL_ACTION_NONE = 0,
+    ARGOL_SHOW_HELP,
+    ARGOL_SHOW_VERSION
+};
+
+/* Arguments of `proj-man new`. */
+struct argol_new_args {
+    /* Do not create a virtual environment */
+    bool venv;
+    /* Show verbose logging for project creation */
+    bool verbose;
+    /* Project name */
+    const char *name;
+    /* Initial project version */
+    const char *version;
+    /* Project dependencies to intstall */
+    const char *deps;
+    bool has_color;
+    int64_t color;
+};
+
+enum argol_command {
+    ARGOL_COMMAND_NONE = 0,
+    ARGOL_COMMAND_NEW
+};
+
+/* Arguments matched for proj-man. */
+struct argol_args {
+    /* Set instead of the arguments when a flag like --help is given */
+    enum argol_action action;
+    /* Help or version text to print for the action */
+    const char *message;
+    /* Message for the error argol_parse returned */
+    char error[256];
+    /* Path of subcommands given, by their first names */
+    const char *command[1];
+    size_t command_len;
+    /* Words after `--` that no positional took */
+    char **trailing;
+    size_t trailing_len;
+
+    /* Which of the subcommands below was given */
+    enum argol_command subcommand;
+    struct argol_new_args new_;
+};
+
+/*
+ * Match argv, as given to main, against the spec. Returns ARGOL_OK or an error code, with the
+ * message in out->error. When out->action is set, print out->message and exit instead of using
+ * the arguments. Either way, call argol_free(out) afterwards.
+ */
+int argol_parse(int argc, char **argv, struct argol_args *out);
+
+/* Free the lists of values allocated by argol_parse. */
+void argol_free(struct argol_args *args);
+
+/* Kind of an error code as in argolify's JSON output, e.g. "unknown_option". */
+const char *argol_error_kind(int code);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ARGOL_PROJ_MAN_H */
+
+#if defined(ARGOL_IMPLEMENTATION) && !defined(ARGOL_IMPLEMENTED)
+#define ARGOL_IMPLEMENTED
+
+#include <errno.h>
+#include <math.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+struct argol_option {
+    const char *key;
+    /* One UTF-8 character, or NULL */
+    const char *short_name;
+    /* Space-separated */
+    const char *long_names;
+    bool flag;
+    enum argol_action action;
+    int min;
+    /* -1 when there is no limit */
+    int max;
+};
+
+struct argol_positional {
+    const char *key;
+    int min;
+    int max;
+};
+
+struct argol_spec {
+    const struct argol_option *options;
+    size_t options_len;
+    const struct argol_positional *positionals;
+    size_t positionals_len;
+    /* Space-separated names of each subcommand */
+    const char *const *commands;
+    size_t commands_len;
+    const char *help;
+    const char *version;
+};
+
+struct argol_list {
+    const char **items;
+    size_t len;
+};
+
+/* The words given to one command, before they are converted */
+struct argol_matched {
+    struct argol_args *root;
+    const struct argol_spec *spec;
+    /* By option */
+    bool *flags;
+    /* By option, then by positional */
+    struct argol_list *values;
+    /* First word left over before `--`, reported by argol_leftover */
+    const char *unexpected;
+    /* Index of the subcommand given, or -1, and where its arguments start */
+    int sub;
+    int rest;
+};
+
+static void *argol_alloc(size_t n, size_t size)
+{
+    return calloc(n > 0 ? n : 1, size);
+}
+
+/* Format the message for an error into root->error, with the path of subcommands given. */
+static int argol_fail(struct argol_args *root, int code, const char *format, ...)
+{
+    va_list args;
+    size_t len;
+
+    va_start(args, format);
+    vsnprintf(root->error, sizeof root->error, format, args);
+    va_end(args);
+    for (size_t i = 0; i < root->command_len; i++) {
+        len = strlen(root->error);
+        snprintf(root->error + len, sizeof root->error - len, "%s%s", i == 0 ? " (in " : " ",
+                 root->command[i]);
+    }
+    if (root->command_len > 0) {
+        len = strlen(root->error);
+        snprintf(root->error + len, sizeof root->error - len, ")");
+    }
+    return code;
+}
+
+static int argol_no_memory(struct argol_args *root)
+{
+    return argol_fail(root, ARGOL_NO_MEMORY, "out of memory");
+}
+
+static const char *argol_expects(char *buf, size_t size, int min, int max)
+{
+    if (min == max) {
+        snprintf(buf, size, "%d", min);
+    } else if (max < 0 && min == 0) {
+        snprintf(buf, size, "any number of");
+    } else if (max < 0) {
+        snprintf(buf, size, "at least %d", min);
+    } else {
+        snprintf(buf, size, "%d to %d", min, max);
+    }
+    return buf;
+}
+
+static int argol_count(struct argol_args *root, int code, const char *name, int min, int max)
+{
+    char expects[32];
+    argol_expects(expects, sizeof expects, min, max);
+    return argol_fail(root, code, "'%s' expects %s value(s)", name, expects);
+}
+
+static bool argol_is_digit(char c)
+{
+    return c >= '0' && c <= '9';
+}
+
+/* Whether s is a number as argolify reads one: a finite decimal with an optional exponent */
+static bool argol_is_number(const char *s)
+{
+    const char *start = s;
+    size_t digits = 0;
+
+    if (*s == '+' || *s == '-') {
+        s++;
+    }
+    for (; argol_is_digit(*s); s++) {
+        digits++;
+    }
+    if (*s == '.') {
+        for (s++; argol_is_digit(*s); s++) {
+            digits++;
+        }
+    }
+    if (digits == 0) {
+        return false;
+    }
+    if (*s == 'e' || *s == 'E') {
+        s++;
+        if (*s == '+' || *s == '-') {
+            s++;
+        }
+        if (!argol_is_digit(*s)) {
+            return false;
+        }
+        while (argol_is_digit(*s)) {
+            s++;
+        }
+    }
+    return *s == '\0' && isfinite(strtod(start, NULL));
+}
+
+/* Length of the UTF-8 character s starts with */
+static size_t argol_char_len(const char *s)
+{
+    unsigned char c = (unsigned char)*s;
+    size_t n = c < 0x80 ? 1 : c >= 0xf0 ? 4 : c >= 0xe0 ? 3 : 2;
+    size_t len = strlen(s);
+    return n < len ? n : len;
+}
+
+/* Whether the space-separated names include the first len bytes of name */
+static bool argol_has_name(const char *names, const char *name, size_t len)
+{
+    while (*names != '\0') {
+        size_t n = strcspn(names, " ");
+        if (n == len && strncmp(names, name, len) == 0) {
+            return true;
+        }
+        names += n;
+        if (*names == ' ') {
+            names++;
+        }
+    }
+    return false;
+}
+
+static const struct argol_option *argol_long(const struct argol_spec *spec, const char *name,
+                                             size_t len)
+{
+    for (size_t i = 0; i < spec->options_len; i++) {
+        if (argol_has_name(spec->options[i].long_names, name, len)) {
+            return &spec->options[i];
+        }
+    }
+    return NULL;
+}
+
+static const struct argol_option *argol_short(const struct argol_spec *spec, const char *c,
+                                              size_t len)
+{
+    for (size_t i = 0; i < spec->options_len; i++) {
+        const char *s = spec->options[i].short_name;
+        if (s != NULL && strlen(s) == len && strncmp(s, c, len) == 0) {
+            return &spec->options[i];
+        }
+    }
+    return NULL;
+}
+
+static int argol_subcommand(const struct argol_spec *spec, const char *arg)
+{
+    for (size_t i = 0; i < spec->commands_len; i++) {
+        if (argol_has_name(spec->commands[i], arg, strlen(arg))) {
+            return (int)i;
+        }
+    }
+    return -1;
+}
+
+/* A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short name claims it. */
+static bool argol_is_option(const struct argol_spec *spec, const char *arg)
+{
+    if (strcmp(arg, "--") == 0) {
+        return true;
+    }
+    if (arg[0] != '-' || arg[1] == '\0') {
+        return false;
+    }
+    return argol_short(spec, arg + 1, argol_char_len(arg + 1)) != NULL
+        || !argol_is_number(arg + 1);
+}
+
+/* Lists hold at most one item per word of argv, so their size is fixed when first used. */
+static bool argol_push(struct argol_list *list, const char *s, int argc)
+{
+    if (list->items == NULL) {
+        list->items = (const char **)argol_alloc((size_t)argc, sizeof *list->items);
+        if (list->items == NULL) {
+            return false;
+        }
+    }
+    list->items[list->len++] = s;
+    return true;
+}
+
+/* Record a flag, and the action when it has one. Returns whether it had one. */
+static bool argol_set_flag(struct argol_matched *m, const struct argol_option *opt)
+{
+    m->flags[opt - m->spec->options] = true;
+    if (opt->action == ARGOL_ACTION_NONE) {
+        return false;
+    }
+    m->root->action = opt->action;
+    m->root->message = opt->action == ARGOL_SHOW_HELP ? m->spec->help : m->spec->version;
+    return true;
+}
+
+/*
+ * Collect the values of a named argument: the inline one, if any, then following words up to the
+ * maximum, stopping at anything that looks like an option.
+ */
+static int argol_take(struct argol_matched *m, const struct argol_option *opt, const char *value,
+                      int argc, char **argv, int *i)
+{
+    struct argol_list *list = &m->values[opt - m->spec->options];
+    int given = 0;
+
+    if (opt->max == 1) {
+        list->len = 0;
+    }
+    if (value != NULL) {
+        if (!argol_push(list, value, argc)) {
+            return argol_no_memory(m->root);
+        }
+        given++;
+    }
+    while ((opt->max < 0 || given < opt->max) && *i + 1 < argc
+           && !argol_is_option(m->spec, argv[*i + 1])) {
+        if (!argol_push(list, argv[++*i], argc)) {
+            return argol_no_memory(m->root);
+        }
+        given++;
+    }
+    if (given < opt->min) {
+        char name[128];
+        snprintf(name, sizeof name, "--%s", opt->key);
+        return argol_count(m->root, ARGOL_MISSING_VALUE, name, opt->min, opt->max);
+    }
+    return ARGOL_OK;
+}
+
+static int argol_long_option(struct argol_matched *m, const char *name, int argc, char **argv,
+                             int *i)
+{
+    const char *eq = strchr(name, '=');
+    int len = eq != NULL ? (int)(eq - name) : (int)strlen(name);
+    const struct argol_option *opt = argol_long(m->spec, name, (size_t)len);
+
+    if (opt == NULL) {
+        return argol_fail(m->root, ARGOL_UNKNOWN_OPTION, "unknown option '--%.*s'", len, name);
+    }
+    if (opt->flag && eq != NULL) {
+        return argol_fail(m->root, ARGOL_FLAG_WITH_VALUE, "flag '--%.*s' does not take a value",
+                          len, name);
+    }
+    if (opt->flag) {
+        argol_set_flag(m, opt);
+        return ARGOL_OK;
+    }
+    return argol_take(m, opt, eq != NULL ? eq + 1 : NULL, argc, argv, i);
+}
+
+static int argol_cluster(struct argol_matched *m, const char *c, int argc, char **argv, int *i)
+{
+    while (*c != '\0') {
+        size_t len = argol_char_len(c);
+        const struct argol_option *opt = argol_short(m->spec, c, len);
+        if (opt == NULL) {
+            return argol_fail(m->root, ARGOL_UNKNOWN_OPTION, "unknown option '-%.*s'", (int)len,
+                              c);
+        }
+        c += len;
+        if (opt->flag) {
+            if (argol_set_flag(m, opt)) {
+                return ARGOL_OK;
+            }
+            continue;
+        }
+        if (*c == '=') {
+            c++;
+        }
+        return argol_take(m, opt, *c != '\0' ? c : NULL, argc, argv, i);
+    }
+    return ARGOL_OK;
+}
+
+/*
+ * Hand out positional words in order, leaving enough for the minimum of later positionals.
+ * Leftover words are only allowed after `--`, where they are passed through as trailing; see
+ * argol_leftover.
+ */
+static int argol_finish(struct argol_matched *m, const struct argol_list *words, int dashes,
+                        int argc, char **argv)
+{
+    const struct argol_spec *spec = m->spec;
+    size_t next = 0;
+
+    for (size_t p = 0; p < spec->positionals_len; p++) {
+        const struct argol_positional *pos = &spec->positionals[p];
+        size_t reserved = 0;
+        size_t n;
+        for (size_t q = p + 1; q < spec->positionals_len; q++) {
+            reserved += (size_t)spec->positionals[q].min;
+        }
+        n = words->len - next > reserved ? words->len - next - reserved : 0;
+        if (pos->max >= 0 && n > (size_t)pos->max) {
+            n = (size_t)pos->max;
+        }
+        if (n > 0 && n < (size_t)pos->min) {
+            return argol_count(m->root, ARGOL_MISSING_VALUE, pos->key, pos->min, pos->max);
+        }
+        for (size_t j = 0; j < n; j++) {
+            if (!argol_push(&m->values[spec->options_len + p], words->items[next + j], argc)) {
+                return argol_no_memory(m->root);
+            }
+        }
+        next += n;
+    }
+
+    /* Words after `--` come last in argv */
+    m->root->trailing = argv + argc - (words->len - next);
+    m->root->trailing_len = words->len - next;
+    if (next < words->len && (dashes < 0 || next < (size_t)dashes)) {
+        m->unexpected = words->items[next];
+    }
+    return ARGOL_OK;
+}
+
+/* Match argv[from..] against spec, stopping at the first subcommand or at an action. */
+static int argol_match(struct argol_matched *m, const struct argol_spec *spec,
+                       struct argol_args *root, int argc, char **argv, int from)
+{
+    struct argol_list words = {NULL, 0};
+    int dashes = -1;
+    int err = ARGOL_OK;
+
+    memset(m, 0, sizeof *m);
+    m->root = root;
+    m->spec = spec;
+    m->sub = -1;
+    m->flags = (bool *)argol_alloc(spec->options_len, sizeof *m->flags);
+    m->values = (struct argol_list *)argol_alloc(spec->options_len + spec->positionals_len,
+                                                 sizeof *m->values);
+    if (m->flags == NULL || m->values == NULL) {
+        return argol_no_memory(root);
+    }
+
+    for (int i = from; i < argc && err == ARGOL_OK; i++) {
+        const char *arg = argv[i];
+        if (dashes >= 0) {
+            err = argol_push(&words, arg, argc) ? ARGOL_OK : argol_no_memory(root);
+        } else if (strcmp(arg, "--") == 0) {
+            dashes = (int)words.len;
+        } else if (strncmp(arg, "--", 2) == 0) {
+            err = argol_long_option(m, arg + 2, argc, argv, &i);
+        } else if (argol_is_option(spec, arg)) {
+            err = argol_cluster(m, arg + 1, argc, argv, &i);
+        } else if (words.len == 0 && (m->sub = argol_subcommand(spec, arg)) >= 0) {
+            m->rest = i + 1;
+            break;
+        } else {
+            err = argol_push(&words, arg, argc) ? ARGOL_OK : argol_no_memory(root);
+        }
+        if (root->action != ARGOL_ACTION_NONE) {
+            break;
+        }
+    }
+    if (err == ARGOL_OK && root->action == ARGOL_ACTION_NONE) {
+        err = argol_finish(m, &words, dashes, argc, argv);
+    }
+    free(words.items);
+    return err;
+}
+
+static void argol_release(struct argol_matched *m)
+{
+    if (m->values != NULL) {
+        for (size_t i = 0; i < m->spec->options_len + m->spec->positionals_len; i++) {
+            free(m->values[i].items);
+        }
+    }
+    free(m->values);
+    free(m->flags);
+}
+
+/* Fail on words left over before `--`, after positionals are converted. */
+static int argol_leftover(const struct argol_matched *m)
+{
+    if (m->unexpected == NULL) {
+        return ARGOL_OK;
+    }
+    return argol_fail(m->root, ARGOL_UNEXPECTED_ARGUMENT, "unexpected argument '%s'",
+                      m->unexpected);
+}
+
+static int argol_missing(struct argol_args *root, const char *key)
+{
+    return argol_fail(root, ARGOL_MISSING_REQUIRED, "missing required argument '%s'", key);
+}
+
+static int argol_not_allowed(struct argol_args *root, const char *key, const char *s,
+                             const char *choices)
+{
+    return argol_fail(root, ARGOL_NOT_ALLOWED, "invalid value '%s' for '%s' (possible values: %s)",
+                      s, key, choices);
+}
+
+static int argol_invalid(struct argol_args *root, const char *key, const char *s, const char *type)
+{
+    return argol_fail(root, ARGOL_INVALID_VALUE, "invalid value '%s' for '%s': expected %s", s, key,
+                      type);
+}
+
+static bool argol_is_integer(const char *s, bool negative)
+{
+    if (*s == '+' || (negative && *s == '-')) {
+        s++;
+    }
+    if (!argol_is_digit(*s)) {
+        return false;
+    }
+    while (argol_is_digit(*s)) {
+        s++;
+    }
+    return *s == '\0';
+}
+
+static int argol_to_int(struct argol_args *root, const char *key, const char *s, int64_t *x)
+{
+    errno = 0;
+    *x = strtoll(s, NULL, 10);
+    if (!argol_is_integer(s, true) || errno == ERANGE) {
+        return argol_invalid(root, key, s, "int");
+    }
+    return ARGOL_OK;
+}
+
+static const char argol_new_args_help[] =
+    "Create a new proj-man project\n"
+    "\n"
+    "Usage: proj-man new [OPTIONS] <name>\n"
+    "\n"
+    "Arguments:\n"
+    "  <name>  Project name\n"
+    "\n"
+    "Options:\n"
+    "  -h, --help                      Show `proj-man new` help & exit\n"
+    "      --venv                      Do not create a virtual environment\n"
+    "      --verbose                   Show verbose logging for project creation\n"
+    "      --version <VERSION>         Initial project version [default: 0.1.0]\n"
+    "  -d, --deps, --dependencies <DEPS>\n"
+    "                                  Project dependencies to intstall\n"
+    "      --color <COLOR>             [possible values: 3, 4]\n";
+static const struct argol_option argol_new_args_options[] = {
+    {"help", "h", "help", true, ARGOL_SHOW_HELP, 0, 0},
+    {"venv", NULL, "venv", true, ARGOL_ACTION_NONE, 0, 0},
+    {"verbose", NULL, "verbose", true, ARGOL_ACTION_NONE, 0, 0},
+    {"version", NULL, "version", false, ARGOL_ACTION_NONE, 1, 1},
+    {"deps", "d", "deps dependencies", false, ARGOL_ACTION_NONE, 1, 1},
+    {"color", NULL, "color", false, ARGOL_ACTION_NONE, 1, 1},
+};
+static const struct argol_positional argol_new_args_positionals[] = {
+    {"name", 1, 1},
+};
+static const struct argol_spec argol_new_args_spec = {
+    argol_new_args_options, 6,
+    argol_new_args_positionals, 1,
+    NULL, 0,
+    argol_new_args_help,
+    NULL,
+};
+
+static int argol_build_new_args(struct argol_args *root, struct argol_new_args *out, int argc,
+                                char **argv, int from)
+{
+    struct argol_matched m;
+    const struct argol_list *v;
+    int err = argol_match(&m, &argol_new_args_spec, root, argc, argv, from);
+
+    if (err != ARGOL_OK || root->action != ARGOL_ACTION_NONE) {
+        goto done;
+    }
+    out->venv = m.flags[1];
+    out->verbose = m.flags[2];
+
+    v = &m.values[6];
+    if (v->len == 0) {
+        err = argol_missing(root, "name");
+        goto done;
+    }
+    for (size_t i = 0; i < v->len; i++) {
+        out->name = v->items[i];
+    }
+
+    err = argol_leftover(&m);
+    if (err != ARGOL_OK) {
+        goto done;
+    }
+
+    v = &m.values[3];
+    out->version = "0.1.0";
+    for (size_t i = 0; i < v->len; i++) {
+        out->version = v->items[i];
+    }
+
+    v = &m.values[4];
+    for (size_t i = 0; i < v->len; i++) {
+        out->deps = v->items[i];
+    }
+
+    v = &m.values[5];
+    for (size_t i = 0; i < v->len; i++) {
+        err = argol_to_int(root, "color", v->items[i], &out->color);
+        if (err != ARGOL_OK) {
+            goto done;
+        }
+        if (!(out->color == 3 || out->color == 4)) {
+            err = argol_not_allowed(root, "color", v->items[i], "3, 4");
+            goto done;
+        }
+        out->has_color = true;
+    }
+
+done:
+    argol_release(&m);
+    return err;
+}
+
+static const char argol_args_help[] =
+    "Usage: proj-man [OPTIONS] [COMMAND]\n"
+    "\n"
+    "Options:\n"
+    "  -v, --version        Show version & exit\n"
+    "  -h, --help, --usage  Show `proj-man` help & exit\n"
+    "\n"
+    "Commands:\n"
+    "  new  Create a new proj-man project\n";
+static const char argol_args_version[] =
+    "proj-man 0.1.0\n";
+static const struct argol_option argol_args_options[] = {
+    {"version", "v", "version", true, ARGOL_SHOW_VERSION, 0, 0},
+    {"help", "h", "help usage", true, ARGOL_SHOW_HELP, 0, 0},
+};
+static const char *const argol_args_commands[] = {
+    "new",
+};
+static const struct argol_spec argol_args_spec = {
+    argol_args_options, 2,
+    NULL, 0,
+    argol_args_commands, 1,
+    argol_args_help,
+    argol_args_version,
+};
+
+static int argol_build_args(struct argol_args *root, struct argol_args *out, int argc,
+                            char **argv, int from)
+{
+    struct argol_matched m;
+    int err = argol_match(&m, &argol_args_spec, root, argc, argv, from);
+
+    if (err != ARGOL_OK || root->action != ARGOL_ACTION_NONE) {
+        goto done;
+    }
+
+    err = argol_leftover(&m);
+    if (err != ARGOL_OK) {
+        goto done;
+    }
+
+    switch (m.sub) {
+    case 0:
+        root->command[root->command_len++] = "new";
+        out->subcommand = ARGOL_COMMAND_NEW;
+        err = argol_build_new_args(root, &out->new_, argc, argv, m.rest);
+        break;
+    }
+
+done:
+    argol_release(&m);
+    return err;
+}
+
+void argol_free(struct argol_args *args)
+{
+    (void)args;
+}
+
+int argol_parse(int argc, char **argv, struct argol_args *out)
+{
+    memset(out, 0, sizeof *out);
+    return argol_build_args(out, out, argc, argv, 1);
+}
+
+const char *argol_error_kind(int code)
+{
+    static const char *const kinds[] = {
+        "ok", "unknown_option", "unexpected_argument", "flag_with_value", "missing_value",
+        "too_many_values", "missing_required", "invalid_value", "not_allowed", "no_memory",
+    };
+    if (code < 0 || (size_t)code >= sizeof kinds / sizeof *kinds) {
+        return "unknown";
+    }
+    return kinds[code];
+}
+
+#endif /* ARGOL_IMPLEMENTATION */
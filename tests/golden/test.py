@@ -0,0 +1,59 @@
+# Generated by argolify from `test.argol`; do not edit.
+#
+# Unlike the argolify runtime, argparse does not keep the words after `--` that no positional
+# argument takes: it exits with a usage error instead.
+
+import argparse
+
+
+def build_parser():
+    parser = argparse.ArgumentParser(
+        prog="proj-man", add_help=False, allow_abbrev=False
+    )
+    parser.add_argument(
+        "-v",
+        "--version",
+        action="version",
+        version="proj-man 0.1.0",
+        help="Show version & exit",
+    )
+    parser.add_argument(
+        "-h", "--help", "--usage", action="help", help="Show `proj-man` help & exit"
+    )
+    subparsers = parser.add_subparsers(dest="command")
+
+    new_parser = subparsers.add_parser(
+        "new",
+        help="Create a new proj-man project",
+        description="Create a new proj-man project",
+        add_help=False,
+        allow_abbrev=False,
+    )
+    new_parser.add_argument(
+        "-h", "--help", action="help", help="Show `proj-man new` help & exit"
+    )
+    new_parser.add_argument(
+        "--venv", action="store_true", help="Do not create a virtual environment"
+    )
+    new_parser.add_argument(
+        "--verbose",
+        action="store_true",
+        help="Show verbose logging for project creation",
+    )
+    new_parser.add_argument("name", help="Project name")
+    new_parser.add_argument(
+        "--version", default="0.1.0", help="Initial project version"
+    )
+    new_parser.add_argument(
+        "-d", "--deps", "--dependencies", help="Project dependencies to intstall"
+    )
+    new_parser.add_argument("--color", type=int, choices=[3, 4])
+    return parser
+
+
+def parse_args(argv=None):
+    return build_parser().parse_args(argv)
+
+
+if __name__ == "__main__":
+    print(vars(parse_args()))
@@ -0,0 +1,68 @@
+// Generated by argolify from `test.argol`; do not edit.
+
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "proj-man",
+    version = "0.1.0",
+    disable_help_flag = true,
+    disable_version_flag = true,
+    disable_help_subcommand = true,
+)]
+pub struct Cli {
+    /// Show version & exit
+    #[arg(short = 'v', long = "version", action = clap::ArgAction::Version)]
+    pub version: Option<bool>,
+    /// Show `proj-man` help & exit
+    #[arg(short = 'h', long = "help", visible_aliases = ["usage"], action = clap::ArgAction::Help)]
+    pub help: Option<bool>,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Create a new proj-man project
+    #[command(name = "new")]
+    New(NewArgs),
+}
+
+#[derive(Debug, Args)]
+#[command(disable_help_flag = true)]
+pub struct NewArgs {
+    /// Show `proj-man new` help & exit
+    #[arg(short = 'h', long = "help", action = clap::ArgAction::Help)]
+    pub help: Option<bool>,
+    /// Do not create a virtual environment
+    #[arg(long = "venv")]
+    pub venv: bool,
+    /// Show verbose logging for project creation
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+    /// Project name
+    #[arg(value_name = "name", required = true)]
+    pub name: String,
+    /// Initial project version
+    #[arg(long = "version", default_value = "0.1.0")]
+    pub version: String,
+    /// Project dependencies to intstall
+    #[arg(short = 'd', long = "deps", visible_aliases = ["dependencies"])]
+    pub deps: Option<String>,
+    #[arg(long = "color", allow_negative_numbers = true, value_parser = one_of::<i64>(&[3, 4]))]
+    pub color: Option<i64>,
+}
+
+fn one_of<T>(allowed: &'static [T]) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync
+where
+    T: std::str::FromStr + PartialEq + std::fmt::Display + Send + Sync + 'static,
+{
+    move |s| {
+        let value: T = s.parse().map_err(|_| format!("invalid value '{s}'"))?;
+        if allowed.contains(&value) {
+            return Ok(value);
+        }
+        let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+        Err(format!("possible values: {}", allowed.join(", ")))
+    }
+}
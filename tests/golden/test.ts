@@ -0,0 +1,509 @@
+// Generated by argolify from `test.argol`; do not edit.
+
+export type Action = "show_help" | "show_version";
+
+/** Returned instead of the arguments when a flag like `--help` is given. */
+export interface ActionRequest {
+  action: Action;
+  /** Path of subcommands given, by their first names. */
+  command: string[];
+  /** Text to print: help for the command, or the program version. */
+  message: string;
+}
+
+export type ErrorKind =
+  | "unknown_option"
+  | "unexpected_argument"
+  | "flag_with_value"
+  | "missing_value"
+  | "too_many_values"
+  | "missing_required"
+  | "invalid_value"
+  | "not_allowed";
+
+/**
+ * A usage error. `kind` and `subject` are those of the error object in
+ * argolify's JSON output.
+ */
+export class ParseError extends Error {
+  readonly kind: ErrorKind;
+  readonly subject: string;
+  readonly command: string[];
+
+  constructor(
+    kind: ErrorKind,
+    subject: string,
+    command: string[],
+    message: string,
+  ) {
+    super(
+      command.length > 0 ? `${message} (in ${command.join(" ")})` : message,
+    );
+    this.name = "ParseError";
+    this.kind = kind;
+    this.subject = subject;
+    this.command = command;
+  }
+}
+
+/**
+ * Match `argv` (without the program name, e.g. `process.argv.slice(2)`)
+ * against the spec. Throws a `ParseError` on usage errors.
+ */
+export function parse(argv: string[]): Args | ActionRequest {
+  const state: State = { command: [], trailing: [], request: null };
+  const args = buildArgs(state, argv);
+  return state.request ?? (args as Args);
+}
+
+/** Arguments matched for proj-man. */
+export interface Args {
+  /** Path of subcommands given, by their first names. */
+  command: string[];
+  action: null;
+  /** Words after `--` that no positional took. */
+  trailing: string[];
+  /** Set when the `new` command is given. */
+  new?: NewArgs;
+}
+
+const argsSpec: Command = {
+  options: [
+    flag("version", "v", ["version"], "show_version"),
+    flag("help", "h", ["help", "usage"], "show_help"),
+  ],
+  positionals: [],
+  commands: new Map([["new", "new"]]),
+  help:
+    "Usage: proj-man [OPTIONS] [COMMAND]\n" +
+    "\n" +
+    "Options:\n" +
+    "  -v, --version        Show version & exit\n" +
+    "  -h, --help, --usage  Show `proj-man` help & exit\n" +
+    "\n" +
+    "Commands:\n" +
+    "  new  Create a new proj-man project\n",
+  version: "proj-man 0.1.0\n",
+};
+
+function buildArgs(state: State, argv: string[]): Args | undefined {
+  const m = match(argsSpec, state, argv);
+  if (m === null) {
+    return undefined;
+  }
+  state.trailing.push(...m.trailing);
+  const args: Args = {
+    command: state.command,
+    action: null,
+    trailing: state.trailing,
+  };
+  leftover(m);
+  switch (m.sub) {
+    case "new":
+      state.command.push("new");
+      args.new = buildNewArgs(state, m.rest);
+      break;
+  }
+  return args;
+}
+
+/** Arguments of `proj-man new`. */
+export interface NewArgs {
+  /** Do not create a virtual environment */
+  venv: boolean;
+  /** Show verbose logging for project creation */
+  verbose: boolean;
+  /** Project name */
+  name: string;
+  /** Initial project version */
+  version: string;
+  /** Project dependencies to intstall */
+  deps?: string;
+  color?: number;
+}
+
+const newArgsSpec: Command = {
+  options: [
+    flag("help", "h", ["help"], "show_help"),
+    flag("venv", null, ["venv"]),
+    flag("verbose", null, ["verbose"]),
+    named("version", null, ["version"], 1, 1),
+    named("deps", "d", ["deps", "dependencies"], 1, 1),
+    named("color", null, ["color"], 1, 1),
+  ],
+  positionals: [positional("name", 1, 1)],
+  commands: new Map(),
+  help:
+    "Create a new proj-man project\n" +
+    "\n" +
+    "Usage: proj-man new [OPTIONS] <name>\n" +
+    "\n" +
+    "Arguments:\n" +
+    "  <name>  Project name\n" +
+    "\n" +
+    "Options:\n" +
+    "  -h, --help                      Show `proj-man new` help & exit\n" +
+    "      --venv                      Do not create a virtual environment\n" +
+    "      --verbose                   Show verbose logging for project creation\n" +
+    "      --version <VERSION>         Initial project version [default: 0.1.0]\n" +
+    "  -d, --deps, --dependencies <DEPS>\n" +
+    "                                  Project dependencies to intstall\n" +
+    "      --color <COLOR>             [possible values: 3, 4]\n",
+  version: "",
+};
+
+function buildNewArgs(state: State, argv: string[]): NewArgs | undefined {
+  const m = match(newArgsSpec, state, argv);
+  if (m === null) {
+    return undefined;
+  }
+  state.trailing.push(...m.trailing);
+  const args: NewArgs = {
+    venv: m.flags.has("venv"),
+    verbose: m.flags.has("verbose"),
+    name: required(m, "name", values(m, "name", str))[0],
+    version: optionValues(m, "version", str)[0] ?? "0.1.0",
+    deps: optionValues(m, "deps", str)[0],
+    color: optionValues(m, "color", int, oneOf([3, 4]))[0],
+  };
+  return args;
+}
+
+interface Option {
+  key: string;
+  short: string | null;
+  long: string[];
+  flag: boolean;
+  action: Action | null;
+  min: number;
+  /** `null` when there is no limit. */
+  max: number | null;
+}
+
+interface Positional {
+  key: string;
+  min: number;
+  max: number | null;
+}
+
+interface Command {
+  options: Option[];
+  positionals: Positional[];
+  /** First name of each subcommand, by every name. */
+  commands: Map<string, string>;
+  help: string;
+  version: string;
+}
+
+function flag(
+  key: string,
+  short: string | null,
+  long: string[],
+  action: Action | null = null,
+): Option {
+  return { key, short, long, flag: true, action, min: 0, max: 0 };
+}
+
+function named(
+  key: string,
+  short: string | null,
+  long: string[],
+  min: number,
+  max: number | null,
+): Option {
+  return { key, short, long, flag: false, action: null, min, max };
+}
+
+function positional(key: string, min: number, max: number | null): Positional {
+  return { key, min, max };
+}
+
+interface State {
+  command: string[];
+  trailing: string[];
+  request: ActionRequest | null;
+}
+
+/** The words given to one command, before they are converted. */
+interface Matched {
+  cmd: Command;
+  path: string[];
+  flags: Set<string>;
+  values: Map<string, string[]>;
+  trailing: string[];
+  /** First word left over before `--`, reported by `leftover`. */
+  unexpected: string | null;
+  sub: string | null;
+  rest: string[];
+}
+
+function fail(
+  m: Matched,
+  kind: ErrorKind,
+  subject: string,
+  message: string,
+): never {
+  throw new ParseError(kind, subject, m.path, message);
+}
+
+function expects(min: number, max: number | null): string {
+  if (min === max) {
+    return `${min}`;
+  }
+  if (max === null) {
+    return min === 0 ? "any number of" : `at least ${min}`;
+  }
+  return `${min} to ${max}`;
+}
+
+const NUMBER = /^[-+]?(\d+\.?\d*|\.\d+)(e[-+]?\d+)?$/i;
+
+/**
+ * Whether s is a finite number; literals that overflow to Infinity do not
+ * count.
+ */
+function isNumber(s: string): boolean {
+  return NUMBER.test(s) && Number.isFinite(Number(s));
+}
+
+/**
+ * A dash-prefixed word is a value (e.g. `-5`) when it is numeric and no short
+ * name claims it.
+ */
+function isOption(cmd: Command, arg: string): boolean {
+  if (arg === "--") {
+    return true;
+  }
+  if (arg.length < 2 || !arg.startsWith("-")) {
+    return false;
+  }
+  const first = Array.from(arg.slice(1))[0];
+  const claimed = cmd.options.some((o) => o.short === first);
+  return claimed || !isNumber(arg.slice(1));
+}
+
+/**
+ * Record a flag, and the request when it has an action. Returns whether it had
+ * one.
+ */
+function setFlag(cmd: Command, state: State, m: Matched, opt: Option): boolean {
+  m.flags.add(opt.key);
+  if (opt.action === null) {
+    return false;
+  }
+  const message = opt.action === "show_help" ? cmd.help : cmd.version;
+  state.request = { action: opt.action, command: [...m.path], message };
+  return true;
+}
+
+/**
+ * Collect the values of a named argument: `given` inline ones, then following
+ * words up to the maximum, stopping at anything that looks like an option.
+ * Returns how many words were used.
+ */
+function take(
+  cmd: Command,
+  m: Matched,
+  opt: Option,
+  given: string[],
+  rest: string[],
+): number {
+  let n = 0;
+  while (
+    (opt.max === null || given.length < opt.max) &&
+    n < rest.length &&
+    !isOption(cmd, rest[n])
+  ) {
+    given.push(rest[n]);
+    n++;
+  }
+  if (given.length < opt.min) {
+    const name = `--${opt.key}`;
+    const message = `'${name}' expects ${expects(opt.min, opt.max)} value(s)`;
+    fail(m, "missing_value", name, message);
+  }
+  const previous = opt.max === 1 ? [] : (m.values.get(opt.key) ?? []);
+  m.values.set(opt.key, [...previous, ...given]);
+  return n;
+}
+
+/**
+ * Hand out positional words in order, leaving enough for the minimum of later
+ * positionals. Leftover words are only allowed after `--`, where they are
+ * passed through as trailing; see `leftover`.
+ */
+function finish(cmd: Command, m: Matched, words: string[], dashes: number) {
+  let rest = words;
+  cmd.positionals.forEach((p, i) => {
+    const later = cmd.positionals.slice(i + 1);
+    const reserved = later.reduce((sum, q) => sum + q.min, 0);
+    let n = Math.max(rest.length - reserved, 0);
+    if (p.max !== null) {
+      n = Math.min(n, p.max);
+    }
+    const given = rest.slice(0, n);
+    rest = rest.slice(n);
+    if (given.length > 0 && given.length < p.min) {
+      const message = `'${p.key}' expects ${expects(p.min, p.max)} value(s)`;
+      fail(m, "missing_value", p.key, message);
+    }
+    if (given.length > 0) {
+      m.values.set(p.key, given);
+    }
+  });
+
+  if (rest.length > 0 && (dashes < 0 || words.length - rest.length < dashes)) {
+    m.unexpected = rest[0];
+  }
+  m.trailing = rest;
+}
+
+/** Fail on words left over before `--`, after positionals are converted. */
+function leftover(m: Matched) {
+  if (m.unexpected !== null) {
+    const word = m.unexpected;
+    fail(m, "unexpected_argument", word, `unexpected argument '${word}'`);
+  }
+}
+
+/** Match `argv` against `cmd`, stopping at a subcommand or an action. */
+function match(cmd: Command, state: State, argv: string[]): Matched | null {
+  const m: Matched = {
+    cmd,
+    path: [...state.command],
+    flags: new Set(),
+    values: new Map(),
+    trailing: [],
+    unexpected: null,
+    sub: null,
+    rest: [],
+  };
+  const words: string[] = [];
+  let dashes = -1;
+  for (let i = 0; i < argv.length; i++) {
+    const arg = argv[i];
+    if (dashes >= 0) {
+      words.push(arg);
+    } else if (arg === "--") {
+      dashes = words.length;
+    } else if (arg.startsWith("--")) {
+      const eq = arg.indexOf("=");
+      const name = eq < 0 ? arg.slice(2) : arg.slice(2, eq);
+      const given = eq < 0 ? [] : [arg.slice(eq + 1)];
+      const opt = cmd.options.find((o) => o.long.includes(name));
+      if (opt === undefined) {
+        fail(m, "unknown_option", `--${name}`, `unknown option '--${name}'`);
+      }
+      if (opt.flag && given.length > 0) {
+        const message = `flag '--${name}' does not take a value`;
+        fail(m, "flag_with_value", `--${name}`, message);
+      }
+      if (opt.flag && setFlag(cmd, state, m, opt)) {
+        return null;
+      }
+      if (!opt.flag) {
+        i += take(cmd, m, opt, given, argv.slice(i + 1));
+      }
+    } else if (isOption(cmd, arg)) {
+      const cluster = Array.from(arg.slice(1));
+      for (let j = 0; j < cluster.length; j++) {
+        const opt = cmd.options.find((o) => o.short === cluster[j]);
+        if (opt === undefined) {
+          const name = `-${cluster[j]}`;
+          fail(m, "unknown_option", name, `unknown option '${name}'`);
+        }
+        if (opt.flag) {
+          if (setFlag(cmd, state, m, opt)) {
+            return null;
+          }
+          continue;
+        }
+        const value = cluster.slice(j + 1).join("").replace(/^=/, "");
+        const given = value === "" ? [] : [value];
+        i += take(cmd, m, opt, given, argv.slice(i + 1));
+        break;
+      }
+    } else if (words.length === 0 && cmd.commands.has(arg)) {
+      m.sub = cmd.commands.get(arg) ?? null;
+      m.rest = argv.slice(i + 1);
+      break;
+    } else {
+      words.push(arg);
+    }
+  }
+  finish(cmd, m, words, dashes);
+  return m;
+}
+
+
+interface Type<T> {
+  name: string;
+  parse(s: string): T | undefined;
+}
+
+/** A set of allowed values, and how they are listed in errors. */
+interface Allowed<T> {
+  test(x: T): boolean;
+  choices: string[];
+}
+
+/** Convert and check the values given for `key`. */
+function values<T>(
+  m: Matched,
+  key: string,
+  type: Type<T>,
+  allowed?: Allowed<T>,
+): T[] {
+  return (m.values.get(key) ?? []).map((s) => {
+    const x = type.parse(s);
+    const invalid = `invalid value '${s}' for '${key}'`;
+    if (x === undefined) {
+      fail(m, "invalid_value", key, `${invalid}: expected ${type.name}`);
+    }
+    if (allowed !== undefined && !allowed.test(x)) {
+      const choices = allowed.choices.join(", ");
+      fail(m, "not_allowed", key, `${invalid} (possible values: ${choices})`);
+    }
+    return x;
+  });
+}
+
+/** Like `values`, for a named argument: checks what is left, then how many. */
+function optionValues<T>(
+  m: Matched,
+  key: string,
+  type: Type<T>,
+  allowed?: Allowed<T>,
+): T[] {
+  leftover(m);
+  const opt = m.cmd.options.find((o) => o.key === key && !o.flag);
+  const given = m.values.get(key) ?? [];
+  if (opt !== undefined && opt.max !== null && given.length > opt.max) {
+    const name = `--${key}`;
+    const message = `'${name}' expects ${expects(opt.min, opt.max)} value(s)`;
+    fail(m, "too_many_values", name, message);
+  }
+  return values(m, key, type, allowed);
+}
+
+const str: Type<string> = { name: "str", parse: (s) => s };
+
+const int: Type<number> = {
+  name: "int",
+  // Larger whole numbers would silently lose precision
+  parse: (s) => {
+    const x = /^[-+]?\d+$/.test(s) ? Number(s) : NaN;
+    return Number.isSafeInteger(x) ? x : undefined;
+  },
+};
+
+function required<T>(m: Matched, key: string, given: T[]): T[] {
+  if (given.length === 0) {
+    fail(m, "missing_required", key, `missing required argument '${key}'`);
+  }
+  return given;
+}
+
+function oneOf<T>(items: T[]): Allowed<T> {
+  return { test: (x) => items.includes(x), choices: items.map(String) };
+}
@@ -0,0 +1,167 @@
+# Generated by argolify from `tests/fixtures/types.argol`; do not edit.
+#
+# Unlike the argolify runtime, argparse does not keep the words after `--` that no positional
+# argument takes: it exits with a usage error instead.
+
+import argparse
+import math
+import pathlib
+import sys
+
+
+# Per command path: the most values each option takes (-1 for no limit), the short names
+# its options claim and the paths its subcommand names select
+_COMMANDS = {
+    "": (
+        {
+            "-l": 1,
+            "--level": 1,
+            "--ratio": 1,
+            "--offset": 1,
+            "--tags": -1,
+            "--strict": 1,
+        },
+        "hql",
+        {"convert": "convert", "c": "convert"},
+    ),
+    "convert": (
+        {"--format": 1},
+        "",
+        {},
+    ),
+}
+
+
+def _uint(s):
+    try:
+        value = int(s)
+    except ValueError:
+        value = -1
+    if value < 0:
+        raise argparse.ArgumentTypeError(f"invalid uint value: {s!r}")
+    return value
+
+
+def _float(s):
+    try:
+        value = float(s)
+    except ValueError:
+        value = math.nan
+    if not math.isfinite(value):
+        raise argparse.ArgumentTypeError(f"invalid float value: {s!r}")
+    return value
+
+
+def _bool(s):
+    if s not in ("true", "false"):
+        raise argparse.ArgumentTypeError(f"invalid bool value: {s!r}")
+    return s == "true"
+
+
+def _in_range(convert, start, end, inclusive, text):
+    def check(s):
+        value = convert(s)
+        if (start is not None and value < start) or (
+            end is not None and (value > end if inclusive else value >= end)
+        ):
+            raise argparse.ArgumentTypeError(f"{s} is not in range {text}")
+        return value
+
+    return check
+
+
+def _is_option(word, shorts):
+    # A dash-prefixed finite number is a value unless a short name claims its first digit
+    if word == "--":
+        return True
+    if len(word) < 2 or word[0] != "-":
+        return False
+    if word[1] in shorts:
+        return True
+    try:
+        return not math.isfinite(float(word[1:]))
+    except ValueError:
+        return True
+
+
+def _select(argv):
+    """The path of the command `argv` selects, walking it the way the argolify runtime does."""
+    path = ""
+    i = 0
+    while i < len(argv):
+        takes, shorts, commands = _COMMANDS[path]
+        word = argv[i]
+        i += 1
+        left = 0
+        if word == "--":
+            break
+        if word.startswith("--"):
+            name, eq, _ = word.partition("=")
+            if name in takes:
+                left = takes[name] - len(eq)
+        elif _is_option(word, shorts):
+            for j, c in enumerate(word[1:], 2):
+                if "-" + c in takes:
+                    left = takes["-" + c] - (j < len(word))
+                    break
+        elif word in commands:
+            path = commands[word]
+            continue
+        else:
+            break
+        while left != 0 and i < len(argv) and not _is_option(argv[i], shorts):
+            left -= 1
+            i += 1
+    return path
+
+
+def build_parser(command=""):
+    """`command` is the path of the command the words select: one with both positional
+    arguments and subcommands only takes its positionals when it is selected itself."""
+    parser = argparse.ArgumentParser(prog="types", add_help=False, allow_abbrev=False)
+    parser.add_argument("-h", "--help", action="help", help="Show help & exit")
+    parser.add_argument("-q", "--quiet", action="store_true", help="Print less")
+    parser.add_argument(
+        "-l",
+        "--level",
+        type=_in_range(_uint, 1, 5, True, "1..=5"),
+        default=2,
+        help="Log level",
+    )
+    parser.add_argument("--ratio", type=_float, help="Sample ratio")
+    parser.add_argument("--offset", type=int, help="Offset from the start")
+    parser.add_argument("--tags", nargs="*", help="Tags to apply")
+    parser.add_argument("--strict", type=_bool, help="Fail on warnings")
+    if command == "":
+        parser.add_argument("input", type=pathlib.Path, nargs="?", help="Input file")
+        parser.set_defaults(command=None)
+    else:
+        parser.set_defaults(input=None)
+        subparsers = parser.add_subparsers(dest="command")
+
+        convert_parser = subparsers.add_parser(
+            "convert",
+            aliases=["c"],
+            help="Convert the input",
+            description="Convert the input",
+            add_help=False,
+            allow_abbrev=False,
+        )
+        convert_parser.set_defaults(command="convert")
+        convert_parser.add_argument(
+            "outputs", type=pathlib.Path, nargs="*", help="Output files"
+        )
+        convert_parser.add_argument(
+            "--format", required=True, choices=["json", "yaml"], help="Output format"
+        )
+    return parser
+
+
+def parse_args(argv=None):
+    if argv is None:
+        argv = sys.argv[1:]
+    return build_parser(_select(argv)).parse_args(argv)
+
+
+if __name__ == "__main__":
+    print(vars(parse_args()))
@@ -0,0 +1,69 @@
+// Generated by argolify from `tests/fixtures/types.argol`; do not edit.
+
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "types",
+    disable_help_flag = true,
+    disable_version_flag = true,
+    disable_help_subcommand = true,
+)]
+pub struct Cli {
+    /// Show help & exit
+    #[arg(short = 'h', long = "help", action = clap::ArgAction::Help)]
+    pub help: Option<bool>,
+    /// Print less
+    #[arg(short = 'q', long = "quiet")]
+    pub quiet: bool,
+    /// Input file
+    #[arg(value_name = "input")]
+    pub input: Option<std::path::PathBuf>,
+    /// Log level
+    #[arg(
+        short = 'l',
+        long = "level",
+        default_value = "2",
+        value_parser = clap::value_parser!(u64).range(1..=5),
+    )]
+    pub level: u64,
+    /// Sample ratio
+    #[arg(long = "ratio", allow_negative_numbers = true, value_parser = finite)]
+    pub ratio: Option<f64>,
+    /// Offset from the start
+    #[arg(long = "offset", allow_negative_numbers = true)]
+    pub offset: Option<i64>,
+    /// Tags to apply
+    #[arg(long = "tags", num_args = 0..)]
+    pub tags: Vec<String>,
+    /// Fail on warnings
+    #[arg(long = "strict", action = clap::ArgAction::Set)]
+    pub strict: Option<bool>,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Convert the input
+    #[command(name = "convert", visible_aliases = ["c"])]
+    Convert(ConvertArgs),
+}
+
+#[derive(Debug, Args)]
+#[command(disable_help_flag = true)]
+pub struct ConvertArgs {
+    /// Output files
+    #[arg(value_name = "outputs", num_args = 1..)]
+    pub outputs: Vec<std::path::PathBuf>,
+    /// Output format
+    #[arg(long = "format", required = true, value_parser = ["json", "yaml"])]
+    pub format: String,
+}
+
+fn finite(s: &str) -> Result<f64, String> {
+    s.parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite())
+        .ok_or_else(|| format!("invalid value '{s}'"))
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::assert_golden;
+use std::process::Command;
+
+// Render help by running the spec with `args` at a terminal width of `columns`
+fn check(spec: &str, args: &[&str], columns: usize, golden: &str) {
+    let out = Command::new(env!("CARGO_BIN_EXE_argolify"))
+        .args(["run", spec, "--"])
+        .args(args)
+        .env("COLUMNS", columns.to_string())
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    assert_golden(golden, &String::from_utf8(out.stdout).unwrap());
+}
+
+#[test]
+fn top_level() {
+    check("test.argol", &["--help"], 80, "help_root.txt");
+}
+
+#[test]
+fn alias_and_short_name() {
+    check("test.argol", &["--usage"], 80, "help_root.txt");
+    check("test.argol", &["-h"], 80, "help_root.txt");
+}
+
+#[test]
+fn subcommand() {
+    check("test.argol", &["new", "--help"], 80, "help_new.txt");
+}
+
+#[test]
+fn wraps_to_width() {
+    check("test.argol", &["new", "-h"], 50, "help_new_50.txt");
+    check("test.argol", &["new", "-h"], 100, "help_new.txt");
+}
+
+#[test]
+fn defaults_ranges_and_nargs() {
+    check("tests/fixtures/types.argol", &["-h"], 80, "help_types.txt");
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::argolify;
+use serde_json::{Value, json};
+use std::process::Output;
+
+const SCHEMA: &str = include_str!("../schema/matches.v1.schema.json");
+
+fn run(spec: &str, args: &[&str]) -> Output {
+    let mut argv = vec!["run", "--output", "json", spec, "--"];
+    argv.extend(args);
+    argolify(&argv)
+}
+
+// Run the spec, check the exit status and validate stdout against the schema
+fn document(spec: &str, args: &[&str], status: i32) -> Value {
+    let out = run(spec, args);
+    assert_eq!(out.status.code(), Some(status), "{out:?}");
+
+    let doc: Value = serde_json::from_slice(&out.stdout).unwrap();
+    let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let errors: Vec<String> = validator.iter_errors(&doc).map(|e| e.to_string()).collect();
+    assert!(errors.is_empty(), "{doc}\n{errors:#?}");
+    doc
+}
+
+#[test]
+fn schema_is_valid() {
+    let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+    jsonschema::meta::validate(&schema).unwrap();
+}
+
+#[test]
+fn values_have_their_types() {
+    let doc = document(
+        "tests/fixtures/types.argol",
+        &[
+            "in.txt", "-l", "3", "--ratio", "0.25", "--offset", "-4", "--tags", "a", "b",
+            "--strict", "true", "-q",
+        ],
+        0,
+    );
+    let args = &doc["args"];
+    assert_eq!(doc["command"], json!([]));
+    assert_eq!(doc["action"], Value::Null);
+    assert_eq!(
+        args["input"],
+        json!({"kind": "positional", "type": "path", "source": "argv", "value": "in.txt"})
+    );
+    assert_eq!(
+        args["level"],
+        json!({"kind": "named", "type": "uint", "source": "argv", "value": 3})
+    );
+    assert_eq!(args["ratio"]["value"], json!(0.25));
+    assert_eq!(args["offset"]["value"], json!(-4));
+    assert_eq!(args["tags"]["value"], json!(["a", "b"]));
+    assert_eq!(args["strict"]["value"], json!(true));
+    assert_eq!(
+        args["quiet"],
+        json!({"kind": "flag", "type": "bool", "source": "argv", "value": true})
+    );
+}
+
+#[test]
+fn defaults_and_unset() {
+    let doc = document("tests/fixtures/types.argol", &[], 0);
+    let args = &doc["args"];
+    assert_eq!(args["help"]["source"], "default");
+    assert_eq!(args["help"]["value"], false);
+    assert_eq!(args["level"]["source"], "default");
+    assert_eq!(args["level"]["value"], 2);
+    assert_eq!(args["ratio"]["source"], "unset");
+    assert_eq!(args["ratio"]["value"], Value::Null);
+    assert_eq!(args["tags"]["source"], "unset");
+    assert_eq!(args["tags"]["value"], json!([]));
+}
+
+#[test]
+fn subcommand_and_trailing() {
+    let doc = document(
+        "tests/fixtures/types.argol",
+        &["c", "--format", "yaml", "a.yml", "--", "b.yml"],
+        0,
+    );
+    assert_eq!(doc["command"], json!(["convert"]));
+    assert_eq!(doc["args"]["format"]["value"], "yaml");
+    assert_eq!(doc["args"]["outputs"]["value"], json!(["a.yml", "b.yml"]));
+    assert_eq!(doc["trailing"], json!([]));
+
+    let doc = document("tests/fixtures/types.argol", &["in.txt", "--", "x", "y"], 0);
+    assert_eq!(doc["args"]["input"]["value"], "in.txt");
+    assert_eq!(doc["trailing"], json!(["x", "y"]));
+}
+
+#[test]
+fn action() {
+    let doc = document("test.argol", &["new", "-h"], 0);
+    assert_eq!(doc["command"], json!(["new"]));
+    assert_eq!(doc["action"], "show_help");
+}
+
+#[test]
+fn usage_errors() {
+    let cases: [(&[&str], &str, &str); 7] = [
+        (&["--nope"], "unknown_option", "--nope"),
+        (&["-l", "9"], "not_allowed", "level"),
+        (&["--offset", "x"], "invalid_value", "offset"),
+        (&["c", "out"], "missing_required", "format"),
+        (&["--quiet=yes"], "flag_with_value", "--quiet"),
+        (&["--ratio", "nan"], "invalid_value", "ratio"),
+        (&["--ratio", "1e999"], "invalid_value", "ratio"),
+    ];
+    for (args, kind, subject) in cases {
+        let doc = document("tests/fixtures/types.argol", args, 2);
+        assert_eq!(doc["error"]["kind"], kind, "{args:?}");
+        assert_eq!(doc["error"]["subject"], subject, "{args:?}");
+    }
+}
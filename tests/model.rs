@@ -0,0 +1,32 @@
+mod common;
+
+use common::argolify;
+
+// The lowered model printed for `spec`, with each line trimmed and joined by a space
+fn model(spec: &str) -> String {
+    let out = argolify(&[spec]);
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let start = stdout.find("Program {").unwrap();
+    stdout[start..]
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn lowers_names_nargs_and_defaults() {
+    let model = model("tests/fixtures/model.argol");
+    for part in [
+        r#"Program { name: Some( "model", ), version: Some( "0.3.0", ),"#,
+        r#"flags: [ Flag { names: Names { short: Some( 'v', ), long: [ "verbose", ], }, desc: Some( "Verbose output", ), action: None, }, ],"#,
+        r#"Positional { name: "input", desc: Some( "Input file", ), required: true, default: [], ty: Path, options: None, nargs: Nargs { min: 1, max: Some( 1, ), }, }"#,
+        r#"Named { names: Names { short: Some( 'l', ), long: [ "level", ], }, desc: Some( "Log level", ), required: false, default: [ Int( 2, ), ], ty: UInt, options: Some( Range( Range { start: Some( Int( 1, ), ), end: Some( Int( 5, ), ), inclusive: true, }, ), ),"#,
+        r#"Named { names: Names { short: None, long: [ "tags", ], }, desc: None, required: false, default: [ Str( "a", ), Str( "b", ), ], ty: Str, options: None, nargs: Nargs { min: 0, max: None, }, }"#,
+        r#"Command { names: [ "fetch", "f", ], desc: Some( "Fetch sources", ),"#,
+        r#"Positional { name: "urls", desc: None, required: false, default: [], ty: Str, options: None, nargs: Nargs { min: 1, max: None, }, }"#,
+    ] {
+        assert!(model.contains(part), "missing `{part}` in {model}");
+    }
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{argolify, has_tool};
+use std::process::{Command, Output};
+
+fn run(spec: &str, options: &[&str], args: &[&str]) -> Output {
+    let mut argv = vec!["run", "--output", "shell"];
+    argv.extend(options);
+    argv.extend([spec, "--"]);
+    argv.extend(args);
+    argolify(&argv)
+}
+
+// What bash sees after `eval`ing the output for `args` and running `script`
+fn eval(spec: &str, args: &[&str], script: &str) -> String {
+    let out = run(spec, &[], args);
+    assert!(out.status.success(), "{out:?}");
+    let source = String::from_utf8(out.stdout).unwrap();
+    let out = Command::new("bash")
+        .args(["-c", &format!("eval \"$1\"\n{script}"), "bash", &source])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn values_are_single_quoted() {
+    let out = run(
+        "tests/fixtures/types.argol",
+        &[],
+        &[
+            "--tags", "a b", "$x", "q'q", "--ratio", "0.5", "--", "in.txt", "extra",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "ARGOL_COMMAND=''\n\
+         ARGOL_HELP='false'\n\
+         ARGOL_QUIET='false'\n\
+         ARGOL_INPUT='in.txt'\n\
+         ARGOL_LEVEL='2'\n\
+         ARGOL_RATIO='0.5'\n\
+         ARGOL_OFFSET=''\n\
+         ARGOL_TAGS=('a b' '$x' 'q'\\''q')\n\
+         ARGOL_STRICT=''\n\
+         ARGOL_TRAILING=('extra')\n"
+    );
+}
+
+#[test]
+fn eval_round_trip() {
+    if !has_tool("bash") {
+        return;
+    }
+    let printed = eval(
+        "tests/fixtures/types.argol",
+        &[
+            "--tags", "a b", "$(date)", "q'q", "", "-q", "--", "in.txt", "x\ny",
+        ],
+        r#"printf '<%s>\n' "$ARGOL_COMMAND" "$ARGOL_QUIET" "$ARGOL_INPUT" "${ARGOL_TAGS[@]}"
+printf '[%s]\n' "${ARGOL_TRAILING[@]}""#,
+    );
+    assert_eq!(
+        printed,
+        "<>\n<true>\n<in.txt>\n<a b>\n<$(date)>\n<q'q>\n<>\n[x\ny]\n"
+    );
+
+    let printed = eval(
+        "tests/fixtures/quoting.argol",
+        &["--mode", "a\"b"],
+        r#"printf '%s\n' "$ARGOL_MODE""#,
+    );
+    assert_eq!(printed, "a\"b\n");
+
+    let printed = eval(
+        "tests/fixtures/types.argol",
+        &["c", "--format", "json", "out 1", "out 2"],
+        r#"printf '%s\n' "$ARGOL_COMMAND" "${#ARGOL_OUTPUTS[@]}" "${ARGOL_OUTPUTS[1]}""#,
+    );
+    assert_eq!(printed, "convert\n2\nout 2\n");
+}
+
+#[test]
+fn actions_and_errors_end_the_script() {
+    let out = run("tests/fixtures/types.argol", &[], &["-h"]);
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        stdout.starts_with("printf '%s\\n' 'Usage: types"),
+        "{stdout}"
+    );
+    assert!(stdout.ends_with("\nexit 0\n"), "{stdout}");
+
+    let out = run("tests/fixtures/types.argol", &[], &["--bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "exit 2\n");
+}
+
+#[test]
+fn prefix() {
+    let out = run("tests/fixtures/quoting.argol", &["--prefix", "OPT_"], &[]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "OPT_COMMAND=''\nOPT_MODE=''\nOPT_TRAILING=()\n"
+    );
+
+    for prefix in ["", "1X", "A-B"] {
+        let out = run("tests/fixtures/quoting.argol", &["--prefix", prefix], &[]);
+        assert_eq!(out.status.code(), Some(1), "{prefix:?}");
+        let stderr = String::from_utf8(out.stderr).unwrap();
+        assert!(stderr.contains("Invalid prefix"), "{stderr}");
+    }
+}
+
+#[test]
+fn names_that_set_the_same_variable_are_rejected() {
+    let out = run("tests/fixtures/reserved.argol", &[], &[]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains("the `action` variable and argument `action` both set `ARGOL_ACTION`"),
+        "{stderr}"
+    );
+    assert!(
+        stderr
+            .contains("the `trailing` variable and argument `trailing` both set `ARGOL_TRAILING`")
+    );
+
+    let out = run("tests/fixtures/same_ident.argol", &[], &[]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert_eq!(
+        stderr
+            .matches("argument `dry-run` and argument `dry_run` both set `ARGOL_DRY_RUN`")
+            .count(),
+        1,
+        "{stderr}"
+    );
+
+    // JSON output has no variables to clash
+    let out = argolify(&[
+        "run",
+        "--output",
+        "json",
+        "tests/fixtures/reserved.argol",
+        "--",
+    ]);
+    assert!(out.status.success(), "{out:?}");
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::argolify;
+use serde_json::Value;
+
+fn run(spec: &str, output: &str, args: &[&str]) -> (Option<i32>, String, String) {
+    let mut argv = vec!["run", "--output", output, spec, "--"];
+    argv.extend(args);
+    let out = argolify(&argv);
+    (
+        out.status.code(),
+        String::from_utf8(out.stdout).unwrap(),
+        String::from_utf8(out.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn prints_program_and_version() {
+    let (status, stdout, _) = run("test.argol", "text", &["--version"]);
+    assert_eq!((status, stdout.as_str()), (Some(0), "proj-man 0.1.0\n"));
+
+    // The action ends matching, so later words are not checked
+    let (status, stdout, _) = run("test.argol", "text", &["-v", "new", "--bogus"]);
+    assert_eq!((status, stdout.as_str()), (Some(0), "proj-man 0.1.0\n"));
+
+    let (status, stdout, _) = run("tests/fixtures/unnamed_version.argol", "text", &["-V"]);
+    assert_eq!((status, stdout.as_str()), (Some(0), "2.1.0\n"));
+}
+
+#[test]
+fn json_and_shell_output() {
+    let (status, stdout, stderr) = run("test.argol", "json", &["--version"]);
+    assert_eq!(status, Some(0));
+    assert_eq!(stderr, "proj-man 0.1.0\n");
+    let doc: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(doc["action"], "show_version");
+
+    let (status, stdout, _) = run("test.argol", "shell", &["--version"]);
+    assert_eq!(status, Some(0));
+    assert_eq!(stdout, "printf '%s\\n' 'proj-man 0.1.0'\nexit 0\n");
+}
+
+#[test]
+fn show_version_requires_the_directive() {
+    let out = argolify(&[
+        "--message-format",
+        "json",
+        "tests/fixtures/no_version.argol",
+    ]);
+    assert_eq!(out.status.code(), Some(1));
+    let d: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(d["code"], "A0212");
+    assert_eq!((d["line"].as_u64(), d["col"].as_u64()), (Some(5), Some(14)));
+    assert_eq!(d["help"], "add !version = \"...\"; at the top level");
+
+    assert!(
+        argolify(&["tests/fixtures/unnamed_version.argol"])
+            .status
+            .success()
+    );
+}